@@ -1,3 +1,6 @@
 fn main() {
-    tauri_build::build();
+    // Only the desktop app needs Tauri's generated context
+    if std::env::var_os("CARGO_FEATURE_DESKTOP").is_some() {
+        tauri_build::build();
+    }
 }
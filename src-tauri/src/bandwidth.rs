@@ -0,0 +1,305 @@
+//! Time-of-day bandwidth schedule.
+//!
+//! A schedule is a list of weekly rules, each a time range on some weekdays
+//! mapped to global `maxSendKbps`/`maxRecvKbps` and optional per-device
+//! limits. The first rule covering the current minute wins; outside every
+//! rule the default limits apply. The schedule is persisted to
+//! `bandwidth-schedule.json` in the app config dir and applied to whichever
+//! profile is active by the desktop app's scheduler, which falls back to the
+//! default limits when the schedule is turned off.
+
+use crate::device_id;
+use crate::schedule::{self, WeekMinute, WeeklyRange};
+use crate::SyncthingError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SCHEDULE_FILE: &str = "bandwidth-schedule.json";
+
+/// Upload and download limits in KiB/s; 0 means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Limits {
+    pub max_send_kbps: i32,
+    pub max_recv_kbps: i32,
+}
+
+impl fmt::Display for Limits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rate = |kbps: i32| {
+            if kbps == 0 {
+                "unlimited".to_string()
+            } else {
+                format!("{kbps} KiB/s")
+            }
+        };
+        write!(
+            f,
+            "send {}, receive {}",
+            rate(self.max_send_kbps),
+            rate(self.max_recv_kbps)
+        )
+    }
+}
+
+/// Global limits plus limits for individual devices
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BandwidthLimits {
+    #[serde(flatten)]
+    pub global: Limits,
+    /// Keyed by device ID
+    pub devices: BTreeMap<String, Limits>,
+}
+
+/// Limits that apply during a weekly time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthRule {
+    /// Shown in the tray, e.g. `Office hours`
+    pub name: String,
+    #[serde(flatten)]
+    pub range: WeeklyRange,
+    #[serde(flatten)]
+    pub limits: BandwidthLimits,
+}
+
+/// The persisted schedule
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BandwidthSchedule {
+    pub enabled: bool,
+    /// Limits outside every rule
+    #[serde(rename = "default")]
+    pub default_limits: BandwidthLimits,
+    /// Checked in order; the first rule covering a minute wins
+    pub rules: Vec<BandwidthRule>,
+}
+
+impl BandwidthSchedule {
+    /// Check names and limits, and bring device IDs into canonical form
+    pub fn validate(&mut self) -> Result<(), SyncthingError> {
+        validate_limits(&mut self.default_limits, "default")?;
+        let mut names = BTreeSet::new();
+        for (i, rule) in self.rules.iter_mut().enumerate() {
+            let field = format!("rules[{i}]");
+            if rule.name.trim().is_empty() {
+                return Err(SyncthingError::validation(
+                    format!("{field}.name"),
+                    "must not be empty",
+                ));
+            }
+            if !names.insert(rule.name.clone()) {
+                return Err(SyncthingError::validation(
+                    format!("{field}.name"),
+                    format!("`{}` is used by another rule", rule.name),
+                ));
+            }
+            validate_limits(&mut rule.limits, &field)?;
+        }
+        Ok(())
+    }
+
+    /// Index of the rule in effect at `at`, if any
+    pub fn active_rule(&self, at: WeekMinute) -> Option<usize> {
+        self.rules.iter().position(|rule| rule.range.covers(at))
+    }
+
+    /// Every device any rule or the default limits mention
+    fn managed_devices(&self) -> BTreeSet<&str> {
+        std::iter::once(&self.default_limits)
+            .chain(self.rules.iter().map(|rule| &rule.limits))
+            .flat_map(|limits| limits.devices.keys().map(String::as_str))
+            .collect()
+    }
+
+    /// The limits to apply while rule `rule` (or the default) is in effect
+    ///
+    /// Every managed device gets an entry: its limit in the rule, else in the
+    /// default limits, else unlimited. That way a device one rule throttles is
+    /// released again once the rule ends.
+    pub fn resolve(&self, rule: Option<usize>) -> BandwidthLimits {
+        let limits = rule
+            .and_then(|i| self.rules.get(i))
+            .map_or(&self.default_limits, |rule| &rule.limits);
+        BandwidthLimits {
+            global: limits.global,
+            devices: self
+                .managed_devices()
+                .into_iter()
+                .map(|id| {
+                    let device = limits
+                        .devices
+                        .get(id)
+                        .or_else(|| self.default_limits.devices.get(id))
+                        .copied()
+                        .unwrap_or_default();
+                    (id.to_string(), device)
+                })
+                .collect(),
+        }
+    }
+
+    /// Minutes from `at` until a different rule takes over, if one ever does
+    pub fn minutes_until_change(&self, at: WeekMinute) -> Option<u32> {
+        schedule::minutes_until_change(at, |at| self.active_rule(at))
+    }
+}
+
+fn validate_limits(limits: &mut BandwidthLimits, field: &str) -> Result<(), SyncthingError> {
+    let check = |limits: &Limits, field: &str| {
+        if limits.max_send_kbps < 0 || limits.max_recv_kbps < 0 {
+            return Err(SyncthingError::validation(
+                field,
+                "limits must be 0 (unlimited) or more",
+            ));
+        }
+        Ok(())
+    };
+    check(&limits.global, field)?;
+    limits.devices = std::mem::take(&mut limits.devices)
+        .into_iter()
+        .map(|(id, device)| {
+            check(&device, &format!("{field}.devices"))?;
+            Ok((device_id::normalize(&id)?, device))
+        })
+        .collect::<Result<_, SyncthingError>>()?;
+    Ok(())
+}
+
+/// The schedule and where it is persisted
+#[derive(Debug)]
+pub struct ScheduleStore {
+    path: Option<PathBuf>,
+    schedule: BandwidthSchedule,
+}
+
+impl ScheduleStore {
+    /// Load the schedule from `config_dir`; a missing, unreadable or invalid
+    /// file means no schedule
+    pub fn load(config_dir: Option<&Path>) -> Self {
+        let path = config_dir.map(|dir| dir.join(SCHEDULE_FILE));
+        let schedule = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|content| serde_json::from_str::<BandwidthSchedule>(&content).ok())
+            .and_then(|mut schedule| match schedule.validate() {
+                Ok(()) => Some(schedule),
+                Err(e) => {
+                    log::warn!("Ignoring invalid bandwidth schedule: {e}");
+                    None
+                },
+            })
+            .unwrap_or_default();
+        Self { path, schedule }
+    }
+
+    pub const fn schedule(&self) -> &BandwidthSchedule {
+        &self.schedule
+    }
+
+    /// Validate and persist `schedule`
+    pub fn set(&mut self, mut schedule: BandwidthSchedule) -> Result<(), SyncthingError> {
+        schedule.validate()?;
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| SyncthingError::filesystem(parent, e))?;
+            }
+            let json = serde_json::to_string_pretty(&schedule)
+                .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
+            fs::write(path, json).map_err(|e| SyncthingError::filesystem(path, e))?;
+        }
+        self.schedule = schedule;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule::{TimeOfDay, Weekday};
+    use serde_json::json;
+
+    const NAS: &str = "P56IOI7-MZJNU2Y-IQGDREY-DM2MGTI-MGL3BXN-PQ6W5BM-TBBZ4TJ-XZWICQ2";
+
+    fn schedule() -> BandwidthSchedule {
+        let mut schedule: BandwidthSchedule = serde_json::from_value(json!({
+            "enabled": true,
+            "default": { "maxSendKbps": 0, "maxRecvKbps": 0 },
+            "rules": [
+                {
+                    "name": "Office hours",
+                    "days": ["mon", "tue", "wed", "thu", "fri"],
+                    "start": "09:00",
+                    "end": "17:00",
+                    "maxSendKbps": 100,
+                    "maxRecvKbps": 500,
+                    "devices": { NAS.to_lowercase(): { "maxSendKbps": 10 } },
+                },
+                {
+                    "name": "Daytime",
+                    "start": "07:00",
+                    "end": "22:00",
+                    "maxSendKbps": 1000,
+                },
+            ],
+        }))
+        .unwrap();
+        schedule.validate().unwrap();
+        schedule
+    }
+
+    fn at(day: Weekday, hhmm: &str) -> WeekMinute {
+        WeekMinute::new(day, TimeOfDay::try_from(hhmm.to_string()).unwrap())
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let schedule = schedule();
+        assert_eq!(schedule.active_rule(at(Weekday::Mon, "10:00")), Some(0));
+        assert_eq!(schedule.active_rule(at(Weekday::Mon, "18:00")), Some(1));
+        assert_eq!(schedule.active_rule(at(Weekday::Sat, "10:00")), Some(1));
+        assert_eq!(schedule.active_rule(at(Weekday::Mon, "23:00")), None);
+    }
+
+    #[test]
+    fn resolve_releases_devices_outside_their_rule() {
+        let schedule = schedule();
+        let office = schedule.resolve(Some(0));
+        assert_eq!(office.global.max_send_kbps, 100);
+        assert_eq!(office.devices[NAS].max_send_kbps, 10);
+
+        // Rules that do not mention a managed device leave it unlimited
+        for rule in [Some(1), None] {
+            let limits = schedule.resolve(rule);
+            assert_eq!(limits.devices[NAS], Limits::default(), "{rule:?}");
+        }
+        assert_eq!(schedule.resolve(None).global, Limits::default());
+    }
+
+    #[test]
+    fn minutes_until_change_follows_rule_boundaries() {
+        let schedule = schedule();
+        assert_eq!(
+            schedule.minutes_until_change(at(Weekday::Mon, "16:00")),
+            Some(60)
+        );
+        assert_eq!(
+            schedule.minutes_until_change(at(Weekday::Fri, "22:00")),
+            Some(9 * 60)
+        );
+    }
+
+    #[test]
+    fn validate_rejects_duplicate_rule_names() {
+        let mut schedule = schedule();
+        schedule.rules[1].name = "Office hours".into();
+        assert!(matches!(
+            schedule.validate(),
+            Err(SyncthingError::Validation { field, .. }) if field == "rules[1].name"
+        ));
+    }
+}
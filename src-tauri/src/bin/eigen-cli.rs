@@ -0,0 +1,1092 @@
+//! Headless command line interface to Eigen.
+//!
+//! Runs the same `core` operations as the desktop app against the active
+//! connection profile (or `--profile`), so Syncthing can be managed over SSH
+//! or from cron. Every command prints a human-readable summary, or JSON with
+//! `--json`.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use eigen_lib::bandwidth::ScheduleStore;
+use eigen_lib::completion::CompletionMatrix;
+use eigen_lib::core::bandwidth::BandwidthStatus;
+use eigen_lib::core::db::{FileDetails, FileEntry, NeedPage};
+use eigen_lib::core::folders::AddedFolder;
+use eigen_lib::core::invite::InviteOptions;
+use eigen_lib::core::stats::{ConnectionType, DeviceOverview, FolderStats};
+use eigen_lib::core::sync_windows::SyncWindowState;
+use eigen_lib::core::{self, Context};
+use eigen_lib::device_id::DeviceId;
+use eigen_lib::manifest::{Manifest, ManifestFormat};
+use eigen_lib::models::{DeviceConfiguration, FolderConfiguration};
+use eigen_lib::preflight::{self, PathCheck};
+use eigen_lib::profiles::{ConnectionProfile, ProfileStore};
+use eigen_lib::qr::QrCode;
+use eigen_lib::snapshots::SnapshotStore;
+use eigen_lib::sync_windows::{PauseReason, SyncWindowStore, Target, TargetKind};
+use eigen_lib::transaction::ConfigDiff;
+use eigen_lib::{discovery, SyncthingConfig, SyncthingError};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Directory name the desktop app keeps its profiles and snapshots under
+const APP_IDENTIFIER: &str = "com.eigen.sync";
+
+#[derive(Parser)]
+#[command(
+    name = "eigen-cli",
+    version,
+    about = "Manage Syncthing from the command line"
+)]
+struct Cli {
+    /// Connection profile to use instead of the active one
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    /// Syncthing home directory to discover the local instance in (defaults
+    /// to `STHOMEDIR`, then the platform default)
+    #[arg(long, global = true, value_name = "DIR")]
+    home: Option<PathBuf>,
+    /// Print machine-readable JSON
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List folders
+    Folders {
+        #[command(subcommand)]
+        command: FoldersCommand,
+    },
+    /// Change a single folder
+    Folder {
+        #[command(subcommand)]
+        command: FolderCommand,
+    },
+    /// List devices
+    Devices {
+        #[command(subcommand)]
+        command: DevicesCommand,
+    },
+    /// Change a single device
+    Device {
+        #[command(subcommand)]
+        command: DeviceCommand,
+    },
+    /// Pending device and folder requests
+    Pending {
+        #[command(subcommand)]
+        command: PendingCommand,
+    },
+    /// Sync conflict files
+    Conflicts {
+        #[command(subcommand)]
+        command: ConflictsCommand,
+    },
+    /// Old file versions kept in `.stversions`
+    Versions {
+        #[command(subcommand)]
+        command: VersionsCommand,
+    },
+    /// Declarative folder/device manifests
+    Manifest {
+        #[command(subcommand)]
+        command: ManifestCommand,
+    },
+    /// The desktop app's time-of-day bandwidth schedule
+    Bandwidth {
+        #[command(subcommand)]
+        command: BandwidthCommand,
+    },
+    /// The desktop app's folder and device sync windows
+    Windows {
+        #[command(subcommand)]
+        command: WindowsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum FoldersCommand {
+    /// List all configured folders
+    List,
+    /// Show how far each remote device is with each folder
+    Completion,
+    /// Show when each folder was last scanned and what it last synced
+    Stats,
+}
+
+#[derive(Subcommand)]
+enum FolderCommand {
+    /// Add a folder, checking its path first
+    Add {
+        path: String,
+        /// Folder ID (a random `abcde-12345` one by default)
+        #[arg(long)]
+        id: Option<String>,
+        /// Display name (defaults to the folder ID)
+        #[arg(long)]
+        label: Option<String>,
+        /// sendreceive, sendonly, receiveonly or receiveencrypted
+        #[arg(long = "type")]
+        folder_type: Option<String>,
+    },
+    /// Pause syncing a folder
+    Pause { folder_id: String },
+    /// Resume syncing a folder
+    Resume { folder_id: String },
+    /// Rescan a folder now
+    Rescan { folder_id: String },
+    /// Push a send-only folder's state to the other devices
+    Override { folder_id: String },
+    /// Discard local changes in a receive-only folder
+    Revert { folder_id: String },
+    /// List the files a folder still needs and who has them
+    Need {
+        folder_id: String,
+        #[arg(long, default_value_t = 1)]
+        page: u32,
+        #[arg(long, default_value_t = core::db::DEFAULT_PER_PAGE)]
+        per_page: u32,
+        /// Look up which devices have each file (one request per file)
+        #[arg(long)]
+        availability: bool,
+    },
+    /// Show the local and global version of a file and which devices have it
+    File { folder_id: String, name: String },
+}
+
+#[derive(Subcommand)]
+enum DevicesCommand {
+    /// List all configured devices
+    List,
+    /// Show connection state, last seen and shared folders of remote devices
+    Overview,
+}
+
+#[derive(Subcommand)]
+enum DeviceCommand {
+    /// Add a device
+    Add {
+        device_id: String,
+        /// Display name (defaults to the device ID)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Remove a device
+    Remove { device_id: String },
+    /// Pause a device
+    Pause { device_id: String },
+    /// Resume a device
+    Resume { device_id: String },
+    /// Show a QR code other devices can scan to add this one
+    Invite {
+        /// Folder to suggest sharing; may be repeated
+        #[arg(long = "folder")]
+        folder_ids: Vec<String>,
+        /// Suggest adding this device as an introducer
+        #[arg(long)]
+        introducer: bool,
+        /// Hours until the invite expires
+        #[arg(long, value_name = "HOURS")]
+        expires_in: Option<u32>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PendingCommand {
+    /// List pending device and folder requests
+    List,
+    /// Accept a pending request
+    Accept {
+        #[command(subcommand)]
+        request: AcceptCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum AcceptCommand {
+    /// Add a device that asked to connect
+    Device {
+        device_id: String,
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Add a folder a device offered to share
+    Folder {
+        folder_id: String,
+        /// Device that offered the folder
+        #[arg(long = "from")]
+        device_id: String,
+        /// Where to store the folder
+        #[arg(long)]
+        path: String,
+        #[arg(long)]
+        label: Option<String>,
+        /// sendreceive, sendonly, receiveonly or receiveencrypted
+        #[arg(long = "type")]
+        folder_type: Option<String>,
+        /// Share encrypted with the offering device, treating it as untrusted
+        #[arg(long)]
+        encryption_password: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConflictsCommand {
+    /// Find sync conflict files in a folder
+    Scan { folder_id: String },
+}
+
+#[derive(Subcommand)]
+enum VersionsCommand {
+    /// List old versions in a folder
+    List {
+        folder_id: String,
+        /// Directory within `.stversions`
+        prefix: Option<String>,
+    },
+    /// Restore an old version to its original location
+    Restore {
+        folder_id: String,
+        /// Path of the version within `.stversions`
+        version_path: String,
+        /// Restore under this path instead of the original name
+        #[arg(long = "as")]
+        original_name: Option<String>,
+        /// Replace the file if it exists
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ManifestCommand {
+    /// Print the folders, devices and sharing of the live config
+    Export {
+        #[arg(long, value_enum, default_value_t = Format::Toml)]
+        format: Format,
+    },
+    /// Converge the live config on a manifest file
+    Apply {
+        file: PathBuf,
+        /// Remove folders and devices the manifest does not list
+        #[arg(long)]
+        prune: bool,
+        /// Only show what would change
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BandwidthCommand {
+    /// Show the rule in effect and the limits it asks for
+    Status,
+    /// Apply the limits of the rule in effect once, e.g. from cron
+    Apply,
+}
+
+#[derive(Subcommand)]
+enum WindowsCommand {
+    /// Show whether each folder and device is inside a window and why it is
+    /// paused
+    Status,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Toml,
+    Yaml,
+}
+
+impl From<Format> for ManifestFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Toml => Self::Toml,
+            Format::Yaml => Self::Yaml,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            if cli.json {
+                eprintln!("{}", serde_json::to_string_pretty(&e).unwrap_or_default());
+            } else {
+                eprintln!("error: {e}");
+            }
+            ExitCode::FAILURE
+        },
+    }
+}
+
+async fn run(cli: &Cli) -> Result<(), SyncthingError> {
+    let ctx = context(cli.profile.as_deref(), cli.home.as_deref())?;
+    let out = Output { json: cli.json };
+
+    match &cli.command {
+        Command::Folders {
+            command: FoldersCommand::List,
+        } => {
+            let folders = core::folders::list_folders(&ctx).await?;
+            out.print(&folders, |folders| print_folders(folders));
+        },
+        Command::Folders {
+            command: FoldersCommand::Completion,
+        } => {
+            let matrix = core::folders::get_completion_matrix(&ctx).await?;
+            out.print(&matrix, print_completion);
+        },
+        Command::Folders {
+            command: FoldersCommand::Stats,
+        } => {
+            let stats = core::stats::get_folder_stats(&ctx).await?;
+            out.print(&stats, |stats| print_folder_stats(stats));
+        },
+        Command::Folder { command } => match command {
+            FolderCommand::Add {
+                path,
+                id,
+                label,
+                folder_type,
+            } => {
+                let folder_id = match id {
+                    Some(id) => id.clone(),
+                    None => core::folders::generate_folder_id(&ctx).await?,
+                };
+                let label = label.clone().unwrap_or_else(|| folder_id.clone());
+                let mut folder =
+                    core::folders::new_folder(&ctx, folder_id.clone(), label, path.clone()).await?;
+                if let Some(kind) = folder_type {
+                    folder.folder_type.clone_from(kind);
+                }
+                let check = core::folders::add_folder(&ctx, &folder).await?;
+                let added = AddedFolder { folder_id, check };
+                out.print(&added, |added| {
+                    println!("Added folder `{}` at {}", added.folder_id, added.check.path);
+                    print_path_warnings(&added.check);
+                });
+            },
+            FolderCommand::Pause { folder_id } => {
+                core::folders::pause_folder(&ctx, folder_id).await?;
+                forget_scheduled_pause(&ctx, &Target::folder(folder_id.clone()));
+                out.done(&format!("Paused folder `{folder_id}`"));
+            },
+            FolderCommand::Resume { folder_id } => {
+                core::folders::resume_folder(&ctx, folder_id).await?;
+                forget_scheduled_pause(&ctx, &Target::folder(folder_id.clone()));
+                out.done(&format!("Resumed folder `{folder_id}`"));
+            },
+            FolderCommand::Rescan { folder_id } => {
+                core::folders::rescan_folder(&ctx, folder_id).await?;
+                out.done(&format!("Rescanning folder `{folder_id}`"));
+            },
+            FolderCommand::Override { folder_id } => {
+                core::folders::override_folder_changes(&ctx, folder_id).await?;
+                out.done(&format!("Overriding changes in folder `{folder_id}`"));
+            },
+            FolderCommand::Revert { folder_id } => {
+                core::folders::revert_folder_changes(&ctx, folder_id).await?;
+                out.done(&format!("Reverting local changes in folder `{folder_id}`"));
+            },
+            FolderCommand::Need {
+                folder_id,
+                page,
+                per_page,
+                availability,
+            } => {
+                let need = core::db::get_folder_need(
+                    &ctx,
+                    folder_id,
+                    Some(*page),
+                    Some(*per_page),
+                    *availability,
+                )
+                .await?;
+                out.print(&need, print_need);
+            },
+            FolderCommand::File { folder_id, name } => {
+                let details = core::db::get_file_info(&ctx, folder_id, name).await?;
+                out.print(&details, print_file_details);
+            },
+        },
+        Command::Devices {
+            command: DevicesCommand::List,
+        } => {
+            let devices = core::devices::list_devices(&ctx).await?;
+            out.print(&devices, |devices| print_devices(devices));
+        },
+        Command::Devices {
+            command: DevicesCommand::Overview,
+        } => {
+            let overview = core::stats::get_device_overview(&ctx).await?;
+            out.print(&overview, |overview| print_device_overview(overview));
+        },
+        Command::Device { command } => match command {
+            DeviceCommand::Add { device_id, name } => {
+                let device_id = DeviceId::parse(device_id)?.to_string();
+                let name = name.clone().unwrap_or_else(|| device_id.clone());
+                let device = core::devices::new_device(&ctx, &device_id, name).await?;
+                core::devices::add_device(&ctx, &device).await?;
+                out.done(&format!("Added device `{device_id}`"));
+            },
+            DeviceCommand::Remove { device_id } => {
+                core::devices::remove_device(&ctx, device_id).await?;
+                out.done(&format!("Removed device `{device_id}`"));
+            },
+            DeviceCommand::Pause { device_id } => {
+                core::devices::pause_device(&ctx, device_id).await?;
+                forget_scheduled_pause(
+                    &ctx,
+                    &Target::device(DeviceId::parse(device_id)?.to_string()),
+                );
+                out.done(&format!("Paused device `{device_id}`"));
+            },
+            DeviceCommand::Resume { device_id } => {
+                core::devices::resume_device(&ctx, device_id).await?;
+                forget_scheduled_pause(
+                    &ctx,
+                    &Target::device(DeviceId::parse(device_id)?.to_string()),
+                );
+                out.done(&format!("Resumed device `{device_id}`"));
+            },
+            DeviceCommand::Invite {
+                folder_ids,
+                introducer,
+                expires_in,
+            } => {
+                let options = InviteOptions {
+                    introducer: *introducer,
+                    expires_in_hours: *expires_in,
+                };
+                let invite = core::invite::create_invite(&ctx, folder_ids, &options).await?;
+                let qr = QrCode::encode(&invite.payload)?;
+                out.print(&invite, |invite| {
+                    print!("{}", qr.to_text());
+                    println!("{}", invite.payload);
+                });
+            },
+        },
+        Command::Pending { command } => match command {
+            PendingCommand::List => {
+                let pending = core::pending::get_pending_requests(&ctx).await?;
+                out.print(&pending, print_pending);
+            },
+            PendingCommand::Accept {
+                request: AcceptCommand::Device { device_id, name },
+            } => {
+                core::pending::accept_pending_device(&ctx, device_id, name.clone()).await?;
+                out.done(&format!("Accepted device `{device_id}`"));
+            },
+            PendingCommand::Accept {
+                request:
+                    AcceptCommand::Folder {
+                        folder_id,
+                        device_id,
+                        path,
+                        label,
+                        folder_type,
+                        encryption_password,
+                    },
+            } => {
+                let check = core::pending::accept_pending_folder(
+                    &ctx,
+                    folder_id,
+                    device_id,
+                    path.clone(),
+                    label.clone(),
+                    folder_type.clone(),
+                    encryption_password.as_deref(),
+                )
+                .await?;
+                match check {
+                    Some(check) => out.print(&check, |check| {
+                        println!("Accepted folder `{folder_id}` into {}", check.path);
+                        print_path_warnings(check);
+                    }),
+                    None => out.done(&format!(
+                        "Shared existing folder `{folder_id}` with `{device_id}`"
+                    )),
+                }
+            },
+        },
+        Command::Conflicts {
+            command: ConflictsCommand::Scan { folder_id },
+        } => {
+            let root = folder_root(&ctx, folder_id).await?;
+            let conflicts = core::files::scan_for_conflicts(&root);
+            out.print(&conflicts, |conflicts| print_conflicts(conflicts));
+        },
+        Command::Versions { command } => match command {
+            VersionsCommand::List { folder_id, prefix } => {
+                let root = folder_root(&ctx, folder_id).await?;
+                let versions = core::files::browse_versions(&root, prefix.as_deref());
+                out.print(&versions, |versions| print_versions(versions));
+            },
+            VersionsCommand::Restore {
+                folder_id,
+                version_path,
+                original_name,
+                overwrite,
+            } => {
+                let root = folder_root(&ctx, folder_id).await?;
+                let original = original_name
+                    .clone()
+                    .unwrap_or_else(|| original_path(version_path));
+                core::files::restore_version(&root, version_path, &original, *overwrite)?;
+                out.done(&format!("Restored {version_path} to {original}"));
+            },
+        },
+        Command::Manifest { command } => match command {
+            ManifestCommand::Export { format } => {
+                let document =
+                    core::manifest::export_config_manifest(&ctx, (*format).into()).await?;
+                out.print(&document, |document| print!("{document}"));
+            },
+            ManifestCommand::Apply {
+                file,
+                prune,
+                dry_run,
+            } => {
+                let document = std::fs::read_to_string(file)
+                    .map_err(|e| SyncthingError::filesystem(file, e))?;
+                let manifest = Manifest::parse(&document, manifest_format(file))?;
+                let diff = if *dry_run {
+                    core::manifest::preview_config_manifest(&ctx, &manifest, *prune).await?
+                } else {
+                    core::manifest::apply_config_manifest(&ctx, &manifest, *prune).await?
+                };
+                out.print(&diff, print_diff);
+            },
+        },
+        Command::Bandwidth { command } => {
+            let store = ScheduleStore::load(config_dir().as_deref());
+            let status = core::bandwidth::bandwidth_status(store.schedule());
+            match command {
+                BandwidthCommand::Status => out.print(&status, print_bandwidth_status),
+                BandwidthCommand::Apply => {
+                    if !status.enabled {
+                        return Err(SyncthingError::validation(
+                            "schedule",
+                            "the bandwidth schedule is disabled",
+                        ));
+                    }
+                    let changed =
+                        core::bandwidth::apply_bandwidth_limits(&ctx, &status.limits).await?;
+                    let rule = status.rule.as_deref().unwrap_or("default limits");
+                    out.done(&if changed {
+                        format!("Applied {rule}")
+                    } else {
+                        format!("{rule} already in effect")
+                    });
+                },
+            }
+        },
+        Command::Windows {
+            command: WindowsCommand::Status,
+        } => {
+            let mut store = SyncWindowStore::load(config_dir().as_deref());
+            let scheduled = store.scheduled_pauses(&ctx.profile);
+            let state =
+                core::sync_windows::get_sync_window_state(&ctx, store.windows(), &scheduled)
+                    .await?;
+            out.print(&state, |state| print_sync_window_state(state));
+        },
+    }
+    Ok(())
+}
+
+/// Forget a pause the desktop app's scheduler made of `target`, which the
+/// user has now paused or resumed by hand
+fn forget_scheduled_pause(ctx: &Context, target: &Target) {
+    let result = SyncWindowStore::load(config_dir().as_deref()).forget_pause(&ctx.profile, target);
+    if let Err(e) = result {
+        eprintln!("warning: failed to record manual pause: {e}");
+    }
+}
+
+/// Where the desktop app keeps its profiles and schedules
+fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+/// Context for `profile`, or the profile the desktop app has active, with the
+/// local instance discovered in `home` if given
+fn context(profile: Option<&str>, home: Option<&Path>) -> Result<Context, SyncthingError> {
+    let local = SyncthingConfig::from_discovery(&discovery::discover(home));
+    let config_dir = config_dir();
+    let data_dir = dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER));
+
+    let store = ProfileStore::load(config_dir.as_deref(), ConnectionProfile::local(&local));
+    let profile = match profile {
+        Some(name) => store.get(name).ok_or_else(|| {
+            SyncthingError::validation("profile", format!("no profile named `{name}`"))
+        })?,
+        None => store.active(),
+    };
+
+    Context::new(
+        &profile.to_config(),
+        SnapshotStore::new(data_dir.as_deref()),
+        profile.name.clone(),
+    )
+}
+
+/// Local path of a folder, with a leading `~` expanded like Syncthing does
+async fn folder_root(ctx: &Context, folder_id: &str) -> Result<PathBuf, SyncthingError> {
+    let folder = core::folders::get_folder_config(ctx, folder_id).await?;
+    Ok(preflight::expand_home(&folder.path))
+}
+
+/// Original path of a version, e.g. `docs/a~20240101-120000.txt` → `docs/a.txt`
+fn original_path(version_path: &str) -> String {
+    let path = Path::new(version_path);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let (original, _) = core::files::parse_version_filename(&name);
+    path.with_file_name(original).to_string_lossy().into_owned()
+}
+
+/// YAML for `.yaml`/`.yml` files, TOML otherwise
+fn manifest_format(file: &Path) -> ManifestFormat {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml" | "yml") => ManifestFormat::Yaml,
+        _ => ManifestFormat::Toml,
+    }
+}
+
+/// Prints results as JSON or through a human-readable printer
+struct Output {
+    json: bool,
+}
+
+impl Output {
+    fn print<T: Serialize>(&self, value: &T, human: impl FnOnce(&T)) {
+        if self.json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(value).unwrap_or_default()
+            );
+        } else {
+            human(value);
+        }
+    }
+
+    fn done(&self, message: &str) {
+        self.print(
+            &serde_json::json!({ "ok": true, "message": message }),
+            |_| {
+                println!("{message}");
+            },
+        );
+    }
+}
+
+fn print_path_warnings(check: &PathCheck) {
+    for warning in &check.warnings {
+        println!("warning: {}", warning.message);
+    }
+}
+
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", padded.join("  ").trim_end());
+    };
+    line(headers.to_vec());
+    for row in rows {
+        line(row.iter().map(String::as_str).collect());
+    }
+}
+
+fn print_folders(folders: &[FolderConfiguration]) {
+    let rows: Vec<Vec<String>> = folders
+        .iter()
+        .map(|f| {
+            vec![
+                f.id.clone(),
+                f.label.clone(),
+                f.folder_type.clone(),
+                if f.paused { "paused" } else { "active" }.into(),
+                f.devices.len().to_string(),
+                f.path.clone(),
+            ]
+        })
+        .collect();
+    print_table(&["ID", "LABEL", "TYPE", "STATE", "DEVICES", "PATH"], &rows);
+}
+
+fn print_completion(matrix: &CompletionMatrix) {
+    let device_name = |id: &str| {
+        matrix
+            .devices
+            .iter()
+            .find(|d| d.device_id == id && !d.name.is_empty())
+            .map_or_else(|| id.chars().take(7).collect(), |d| d.name.clone())
+    };
+    let rows: Vec<Vec<String>> = matrix
+        .folders
+        .iter()
+        .flat_map(|folder| {
+            folder.devices.iter().map(move |cell| {
+                let c = &cell.completion;
+                vec![
+                    folder.folder_id.clone(),
+                    device_name(&cell.device_id),
+                    format!("{:.1}%", c.completion),
+                    c.need_items.to_string(),
+                    c.need_bytes.to_string(),
+                    c.need_deletes.to_string(),
+                    c.remote_state.clone(),
+                ]
+            })
+        })
+        .collect();
+    print_table(
+        &[
+            "FOLDER",
+            "DEVICE",
+            "DONE",
+            "NEED ITEMS",
+            "NEED BYTES",
+            "NEED DELETES",
+            "STATE",
+        ],
+        &rows,
+    );
+}
+
+fn print_need(need: &NeedPage) {
+    let lists = [
+        ("downloading", &need.progress),
+        ("queued", &need.queued),
+        ("waiting", &need.rest),
+    ];
+    let rows: Vec<Vec<String>> = lists
+        .iter()
+        .flat_map(|(state, files)| {
+            files.iter().map(move |f| {
+                let available = f.availability.as_deref().unwrap_or_default();
+                vec![
+                    f.name.clone(),
+                    (*state).to_string(),
+                    if f.deleted {
+                        "deleted".into()
+                    } else {
+                        f.size.to_string()
+                    },
+                    f.modified.clone(),
+                    if f.availability_error.is_some() {
+                        "unknown".into()
+                    } else {
+                        available
+                            .iter()
+                            .map(|id| id.chars().take(7).collect::<String>())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    },
+                ]
+            })
+        })
+        .collect();
+    print_table(
+        &["NAME", "STATE", "SIZE", "MODIFIED", "AVAILABLE ON"],
+        &rows,
+    );
+}
+
+fn print_file_details(details: &FileDetails) {
+    let describe = |f: &FileEntry| {
+        let state = if f.deleted {
+            "deleted"
+        } else if f.ignored {
+            "ignored"
+        } else if f.invalid {
+            "invalid"
+        } else {
+            "present"
+        };
+        format!(
+            "{state}, {} bytes, modified {} by {} [{}]",
+            f.size,
+            f.modified,
+            f.modified_by,
+            f.version.join(" ")
+        )
+    };
+    println!("global: {}", describe(&details.global));
+    match &details.local {
+        Some(local) => println!("local:  {}", describe(local)),
+        None => println!("local:  missing"),
+    }
+    println!("in sync: {}", if details.in_sync { "yes" } else { "no" });
+
+    let rows: Vec<Vec<String>> = details
+        .availability
+        .iter()
+        .map(|a| {
+            vec![
+                a.device_id.clone(),
+                a.name.clone().unwrap_or_default(),
+                if a.connected { "connected" } else { "offline" }.into(),
+                if a.from_temporary {
+                    "partial"
+                } else {
+                    "complete"
+                }
+                .into(),
+            ]
+        })
+        .collect();
+    print_table(&["DEVICE", "NAME", "STATE", "COPY"], &rows);
+}
+
+fn print_devices(devices: &[DeviceConfiguration]) {
+    let rows: Vec<Vec<String>> = devices
+        .iter()
+        .map(|d| {
+            vec![
+                d.device_id.clone(),
+                d.name.clone(),
+                if d.paused { "paused" } else { "active" }.into(),
+                d.addresses.join(", "),
+            ]
+        })
+        .collect();
+    print_table(&["ID", "NAME", "STATE", "ADDRESSES"], &rows);
+}
+
+fn print_device_overview(overview: &[DeviceOverview]) {
+    let rows: Vec<Vec<String>> = overview
+        .iter()
+        .map(|d| {
+            let state = if d.paused {
+                "paused".to_string()
+            } else if d.connected {
+                format!(
+                    "connected ({})",
+                    d.connection_type.map_or("", ConnectionType::as_str)
+                )
+            } else {
+                "disconnected".to_string()
+            };
+            vec![
+                d.device_id.chars().take(7).collect(),
+                d.name.clone(),
+                state,
+                d.address.clone().unwrap_or_default(),
+                d.client_version.clone().unwrap_or_default(),
+                d.last_seen.clone().unwrap_or_else(|| "never".into()),
+                d.folders
+                    .iter()
+                    .map(|f| f.folder_id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ]
+        })
+        .collect();
+    print_table(
+        &[
+            "ID",
+            "NAME",
+            "STATE",
+            "ADDRESS",
+            "VERSION",
+            "LAST SEEN",
+            "FOLDERS",
+        ],
+        &rows,
+    );
+}
+
+fn print_folder_stats(stats: &[FolderStats]) {
+    let rows: Vec<Vec<String>> = stats
+        .iter()
+        .map(|f| {
+            let last_file = f.last_file.as_ref().map_or_else(String::new, |file| {
+                let deleted = if file.deleted { " (deleted)" } else { "" };
+                format!("{}{deleted} at {}", file.filename, file.at)
+            });
+            vec![
+                f.folder_id.clone(),
+                f.label.clone(),
+                f.last_scan.clone().unwrap_or_else(|| "never".into()),
+                last_file,
+            ]
+        })
+        .collect();
+    print_table(&["ID", "LABEL", "LAST SCAN", "LAST FILE"], &rows);
+}
+
+fn print_bandwidth_status(status: &BandwidthStatus) {
+    if !status.enabled {
+        println!("schedule disabled");
+        return;
+    }
+    println!("rule:   {}", status.rule.as_deref().unwrap_or("default"));
+    println!("limits: {}", status.limits.global);
+    for (device_id, limits) in &status.limits.devices {
+        let short = device_id.split('-').next().unwrap_or(device_id);
+        println!("        {short}: {limits}");
+    }
+    if let Some(at) = status.next_change.and_then(local_time) {
+        println!("next:   {at}");
+    }
+}
+
+fn print_sync_window_state(state: &[SyncWindowState]) {
+    let rows: Vec<Vec<String>> = state
+        .iter()
+        .map(|s| {
+            let kind = match s.target.kind {
+                TargetKind::Folder => "folder",
+                TargetKind::Device => "device",
+            };
+            let id = match s.target.kind {
+                TargetKind::Folder => s.target.id.as_str(),
+                TargetKind::Device => s.target.id.split('-').next().unwrap_or_default(),
+            };
+            let paused = match s.pause_reason {
+                None => "active",
+                Some(PauseReason::Manual) => "paused (manual)",
+                Some(PauseReason::Scheduled) => "paused (scheduled)",
+            };
+            vec![
+                kind.into(),
+                id.into(),
+                s.name.clone().unwrap_or_else(|| "(not configured)".into()),
+                if s.open { "open" } else { "closed" }.into(),
+                paused.into(),
+                s.next_change.and_then(local_time).unwrap_or_default(),
+            ]
+        })
+        .collect();
+    print_table(&["KIND", "ID", "NAME", "WINDOW", "STATE", "NEXT"], &rows);
+}
+
+/// Weekday and local time of `at`, in milliseconds since the Unix epoch
+fn local_time(at: u64) -> Option<String> {
+    let at = chrono::DateTime::from_timestamp_millis(i64::try_from(at).ok()?)?;
+    Some(
+        at.with_timezone(&chrono::Local)
+            .format("%a %H:%M")
+            .to_string(),
+    )
+}
+
+fn print_pending(pending: &core::pending::PendingRequests) {
+    if pending.devices.is_empty() && pending.folders.is_empty() {
+        println!("No pending requests");
+        return;
+    }
+    if !pending.devices.is_empty() {
+        let rows: Vec<Vec<String>> = pending
+            .devices
+            .iter()
+            .map(|d| {
+                vec![
+                    d.device_id.clone(),
+                    d.name.clone().unwrap_or_default(),
+                    d.address.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        print_table(&["DEVICE", "NAME", "ADDRESS"], &rows);
+    }
+    if !pending.folders.is_empty() {
+        if !pending.devices.is_empty() {
+            println!();
+        }
+        let rows: Vec<Vec<String>> = pending
+            .folders
+            .iter()
+            .map(|f| {
+                vec![
+                    f.folder_id.clone(),
+                    f.folder_label.clone().unwrap_or_default(),
+                    f.offered_by.clone(),
+                ]
+            })
+            .collect();
+        print_table(&["FOLDER", "LABEL", "OFFERED BY"], &rows);
+    }
+}
+
+fn print_conflicts(conflicts: &[Value]) {
+    if conflicts.is_empty() {
+        println!("No conflicts");
+        return;
+    }
+    let rows: Vec<Vec<String>> = conflicts
+        .iter()
+        .map(|c| vec![text(&c["name"]), text(&c["original"]), text(&c["size"])])
+        .collect();
+    print_table(&["CONFLICT", "ORIGINAL", "SIZE"], &rows);
+}
+
+fn print_versions(versions: &[Value]) {
+    if versions.is_empty() {
+        println!("No versions");
+        return;
+    }
+    let rows: Vec<Vec<String>> = versions
+        .iter()
+        .map(|v| {
+            vec![
+                text(&v["name"]),
+                text(&v["originalName"]),
+                text(&v["versionTime"]),
+                text(&v["size"]),
+            ]
+        })
+        .collect();
+    print_table(&["VERSION", "ORIGINAL", "TIME", "SIZE"], &rows);
+}
+
+fn print_diff(diff: &ConfigDiff) {
+    if diff.is_empty() {
+        println!("Already up to date");
+        return;
+    }
+    for (kind, changes) in [("folder", &diff.folders), ("device", &diff.devices)] {
+        for change in changes {
+            let fields: Vec<&str> = change.changes.iter().map(|c| c.field.as_str()).collect();
+            let detail = if fields.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", fields.join(", "))
+            };
+            println!("{:?} {kind} {}{detail}", change.kind, change.id);
+        }
+    }
+    for change in &diff.options {
+        println!("Modified option {}", change.field);
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,441 @@
+//! Shared HTTP client for the Syncthing REST API.
+//!
+//! A single [`SyncthingClient`] lives in [`crate::SyncthingState`] so every
+//! command reuses the same connection pool. All responses pass through one
+//! status check before their body is decoded, so a 4xx/5xx from Syncthing is
+//! reported as such instead of surfacing later as a JSON parse failure.
+
+use crate::{tls, SyncthingConfig, SyncthingError};
+use reqwest::header::{HeaderValue, ETAG, IF_MATCH};
+use reqwest::{Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default timeout for a single REST call (long-polls override this per request)
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Timeout for establishing a TCP connection to the GUI listener
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long idle pooled connections are kept around
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// Upper bound on idle connections kept per host
+const POOL_MAX_IDLE_PER_HOST: usize = 16;
+/// Read-modify-write cycles attempted before giving up with a conflict
+const MAX_WRITE_ATTEMPTS: usize = 3;
+
+/// Query string parameters for a REST call
+pub type Query<'a> = &'a [(&'a str, &'a str)];
+
+/// Pooled client bound to one Syncthing instance
+#[derive(Debug, Clone)]
+pub struct SyncthingClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    /// Serializes config writes made through this client
+    write_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl SyncthingClient {
+    /// Build a client for the instance described by `config`
+    ///
+    /// Over HTTPS the certificate fingerprint must be configured and is
+    /// pinned, since Syncthing's self-signed certificate never passes CA
+    /// validation.
+    pub fn new(config: &SyncthingConfig) -> Result<Self, SyncthingError> {
+        let mut builder = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST);
+        if config.use_tls {
+            let fingerprint = config
+                .cert_fingerprint
+                .as_deref()
+                .ok_or_else(tls::missing_fingerprint)?;
+            builder = builder.use_preconfigured_tls(tls::pinned_client_config(fingerprint)?);
+        }
+        let http = builder
+            .build()
+            .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
+
+        Ok(Self {
+            http,
+            base_url: format!(
+                "{}://{}:{}",
+                if config.use_tls { "https" } else { "http" },
+                config.host,
+                config.port
+            ),
+            api_key: config.api_key.clone(),
+            write_lock: Arc::default(),
+        })
+    }
+
+    /// Base URL of the GUI/REST listener, e.g. `http://127.0.0.1:8384`
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Whether Syncthing listens on a loopback address, so its paths are local ones
+    pub fn is_local(&self) -> bool {
+        let Ok(url) = reqwest::Url::parse(&self.base_url) else {
+            return false;
+        };
+        match url.host_str() {
+            Some("localhost") => true,
+            Some(host) => host
+                .trim_matches(['[', ']'])
+                .parse::<std::net::IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback()),
+            None => false,
+        }
+    }
+
+    /// Start an authenticated request to `path` (which must begin with `/rest/`)
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{path}", self.base_url))
+            .header("X-API-Key", &self.api_key)
+    }
+
+    /// Send a request and turn any non-success status into an error
+    pub async fn execute(&self, request: RequestBuilder) -> Result<Response, SyncthingError> {
+        let request = request
+            .build()
+            .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
+        let method = request.method().clone();
+        let path = request.url().path().to_string();
+
+        let response = self.http.execute(request).await.map_err(|e| {
+            if e.is_timeout() {
+                SyncthingError::Timeout {
+                    endpoint: path.clone(),
+                }
+            } else if e.is_connect() {
+                SyncthingError::NotRunning {
+                    endpoint: self.base_url.clone(),
+                }
+            } else {
+                SyncthingError::HttpError(format!("{method} {path} failed: {e}"))
+            }
+        })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        // Syncthing puts a plain-text reason in the body of most error responses
+        let body = response.text().await.unwrap_or_default();
+        Err(SyncthingError::from_status(
+            status.as_u16(),
+            &path,
+            body.trim().to_string(),
+        ))
+    }
+
+    /// Send a request and decode the JSON response body
+    pub async fn execute_json<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, SyncthingError> {
+        self.execute(request)
+            .await?
+            .json()
+            .await
+            .map_err(|e| SyncthingError::ParseError(e.to_string()))
+    }
+
+    /// GET `path` and decode the JSON response
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: Query<'_>,
+    ) -> Result<T, SyncthingError> {
+        self.execute_json(self.request(Method::GET, path).query(query))
+            .await
+    }
+
+    /// PUT a JSON body to `path`
+    pub async fn put<B: Serialize + Sync + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<(), SyncthingError> {
+        self.execute(self.request(Method::PUT, path).json(body))
+            .await
+            .map(drop)
+    }
+
+    /// POST to `path` without a body
+    pub async fn post(&self, path: &str, query: Query<'_>) -> Result<(), SyncthingError> {
+        self.execute(self.request(Method::POST, path).query(query))
+            .await
+            .map(drop)
+    }
+
+    /// POST a JSON body to `path`
+    pub async fn post_json<B: Serialize + Sync + ?Sized>(
+        &self,
+        path: &str,
+        query: Query<'_>,
+        body: &B,
+    ) -> Result<(), SyncthingError> {
+        self.execute(self.request(Method::POST, path).query(query).json(body))
+            .await
+            .map(drop)
+    }
+
+    /// PATCH `path` with a partial JSON object; Syncthing merges it server-side
+    pub async fn patch<B: Serialize + Sync + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<(), SyncthingError> {
+        let _guard = self.write_lock.lock().await;
+        self.execute(self.request(Method::PATCH, path).json(body))
+            .await
+            .map(drop)
+    }
+
+    /// DELETE `path`
+    pub async fn delete(&self, path: &str, query: Query<'_>) -> Result<(), SyncthingError> {
+        self.execute(self.request(Method::DELETE, path).query(query))
+            .await
+            .map(drop)
+    }
+
+    /// PUT `body` to `path` only if nothing exists there yet; returns whether it was created
+    pub async fn create<B: Serialize + Sync + ?Sized>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<bool, SyncthingError> {
+        let _guard = self.write_lock.lock().await;
+        match self.get::<Value>(path, &[]).await {
+            Ok(_) => Ok(false),
+            Err(
+                SyncthingError::NotFound { .. }
+                | SyncthingError::FolderNotFound(_)
+                | SyncthingError::DeviceNotFound(_),
+            ) => {
+                self.execute(self.request(Method::PUT, path).json(body))
+                    .await?;
+                Ok(true)
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Read-modify-write the object at `path` without losing concurrent changes
+    ///
+    /// `modify` returns whether it changed anything. Writes from this app are
+    /// serialized; a change made elsewhere between the read and the write
+    /// (detected by re-reading, or by a 412 when Syncthing sent an `ETag`)
+    /// restarts the cycle on fresh data, and after repeated clashes the update
+    /// fails with `Conflict` instead of overwriting. Returns whether a write happened.
+    pub async fn modify<T, F>(&self, path: &str, mut modify: F) -> Result<bool, SyncthingError>
+    where
+        T: DeserializeOwned + Serialize + Send + Sync,
+        F: FnMut(&mut T) -> Result<bool, SyncthingError> + Send,
+    {
+        let _guard = self.write_lock.lock().await;
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let outcome = self.write_once(path, &mut modify).await?;
+            if let Some(written) = settle(path, attempt, outcome)? {
+                return Ok(written);
+            }
+        }
+    }
+
+    /// One read-modify-write cycle of [`Self::modify`]
+    async fn write_once<T, F>(&self, path: &str, modify: &mut F) -> Result<Attempt, SyncthingError>
+    where
+        T: DeserializeOwned + Serialize + Send + Sync,
+        F: FnMut(&mut T) -> Result<bool, SyncthingError> + Send,
+    {
+        let (original, etag) = self.get_versioned(path).await?;
+        let mut value: T = serde_json::from_value(original.clone())
+            .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
+        if !modify(&mut value)? {
+            return Ok(Attempt::Unchanged);
+        }
+
+        // Changed since we read it: start over from the current state
+        let (current, _) = self.get_versioned(path).await?;
+        if current != original {
+            return Ok(Attempt::ChangedElsewhere);
+        }
+
+        let mut request = self.request(Method::PUT, path).json(&value);
+        if let Some(etag) = etag {
+            request = request.header(IF_MATCH, etag);
+        }
+        Ok(Attempt::Written(self.execute(request).await.map(drop)))
+    }
+
+    /// GET `path` as raw JSON together with its `ETag`, if Syncthing sent one
+    async fn get_versioned(
+        &self,
+        path: &str,
+    ) -> Result<(Value, Option<HeaderValue>), SyncthingError> {
+        let response = self.execute(self.request(Method::GET, path)).await?;
+        let etag = response.headers().get(ETAG).cloned();
+        let value = response
+            .json()
+            .await
+            .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
+        Ok((value, etag))
+    }
+}
+
+/// How one read-modify-write cycle ended
+#[derive(Debug)]
+enum Attempt {
+    /// The modification left the object as it was
+    Unchanged,
+    /// The object changed between the read and the write
+    ChangedElsewhere,
+    /// The write was sent, and Syncthing answered with this
+    Written(Result<(), SyncthingError>),
+}
+
+/// Decide what follows attempt number `attempt` (counting from 1) to update
+/// `path`: `Some(written)` when done, `None` to start another cycle
+fn settle(path: &str, attempt: usize, outcome: Attempt) -> Result<Option<bool>, SyncthingError> {
+    match outcome {
+        Attempt::Unchanged => Ok(Some(false)),
+        Attempt::Written(Ok(())) => Ok(Some(true)),
+        // A 412 means the `ETag` no longer matches: changed elsewhere too
+        Attempt::ChangedElsewhere
+        | Attempt::Written(Err(SyncthingError::Api { status: 412, .. }))
+            if attempt < MAX_WRITE_ATTEMPTS =>
+        {
+            Ok(None)
+        },
+        Attempt::ChangedElsewhere
+        | Attempt::Written(Err(SyncthingError::Api { status: 412, .. })) => {
+            Err(SyncthingError::conflict(
+                path,
+                format!("{path} kept changing while it was being updated; reload and try again"),
+            ))
+        },
+        Attempt::Written(Err(e)) => Err(e),
+    }
+}
+
+/// Percent-encode a value for use as a single URL path segment
+/// (folder IDs may contain characters that are not path-safe)
+pub fn path_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+/// Undo [`path_segment`]; invalid escapes are kept as they are
+pub fn decode_path_segment(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(byte) = escaped {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "/rest/config/options";
+
+    fn precondition_failed() -> SyncthingError {
+        SyncthingError::from_status(412, PATH, String::new())
+    }
+
+    #[test]
+    fn path_segment_escapes_everything_but_unreserved_characters() {
+        assert_eq!(path_segment("abcd-1234_x.y~z"), "abcd-1234_x.y~z");
+        assert_eq!(path_segment("photos/2024"), "photos%2F2024");
+        assert_eq!(path_segment("my folder"), "my%20folder");
+        assert_eq!(path_segment("100%"), "100%25");
+        assert_eq!(path_segment("ü"), "%C3%BC");
+    }
+
+    #[test]
+    fn decode_path_segment_undoes_path_segment() {
+        for value in ["photos/2024", "my folder", "100%", "ü", "abcd-1234"] {
+            assert_eq!(decode_path_segment(&path_segment(value)), value);
+        }
+        assert_eq!(decode_path_segment("50%"), "50%");
+        assert_eq!(decode_path_segment("%zz"), "%zz");
+    }
+
+    #[test]
+    fn finished_attempts_report_whether_they_wrote() {
+        assert!(matches!(
+            settle(PATH, 1, Attempt::Unchanged),
+            Ok(Some(false))
+        ));
+        assert!(matches!(
+            settle(PATH, 1, Attempt::Written(Ok(()))),
+            Ok(Some(true))
+        ));
+    }
+
+    #[test]
+    fn concurrent_changes_are_retried_until_attempts_run_out() {
+        for attempt in 1..MAX_WRITE_ATTEMPTS {
+            assert!(matches!(
+                settle(PATH, attempt, Attempt::ChangedElsewhere),
+                Ok(None)
+            ));
+            let rejected = Attempt::Written(Err(precondition_failed()));
+            assert!(matches!(settle(PATH, attempt, rejected), Ok(None)));
+        }
+
+        let last = MAX_WRITE_ATTEMPTS;
+        assert!(matches!(
+            settle(PATH, last, Attempt::ChangedElsewhere),
+            Err(SyncthingError::Conflict { .. })
+        ));
+        let rejected = Attempt::Written(Err(precondition_failed()));
+        assert!(matches!(
+            settle(PATH, last, rejected),
+            Err(SyncthingError::Conflict { .. })
+        ));
+    }
+
+    #[test]
+    fn other_write_errors_are_not_retried() {
+        let failed = Attempt::Written(Err(SyncthingError::from_status(500, PATH, String::new())));
+        assert!(matches!(
+            settle(PATH, 1, failed),
+            Err(SyncthingError::Api { status: 500, .. })
+        ));
+    }
+}
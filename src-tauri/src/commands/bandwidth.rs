@@ -0,0 +1,52 @@
+//! Bandwidth schedule commands.
+
+use crate::bandwidth::BandwidthSchedule;
+use crate::core::bandwidth::{self, BandwidthStatus};
+use crate::{SyncthingError, SyncthingState};
+use std::sync::PoisonError;
+use tauri::State;
+
+/// The persisted bandwidth schedule
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn get_bandwidth_schedule(state: State<'_, SyncthingState>) -> BandwidthSchedule {
+    state
+        .bandwidth
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .schedule()
+        .clone()
+}
+
+/// Save the schedule; the scheduler applies it straight away
+///
+/// Turning the schedule off puts its default limits back in place.
+#[tauri::command]
+pub async fn set_bandwidth_schedule(
+    state: State<'_, SyncthingState>,
+    schedule: BandwidthSchedule,
+) -> Result<BandwidthStatus, SyncthingError> {
+    let mut store = state
+        .bandwidth
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    store.set(schedule)?;
+    let status = bandwidth::bandwidth_status(store.schedule());
+    drop(store);
+
+    state.schedule_changed();
+    Ok(status)
+}
+
+/// The rule in effect right now and the limits it asks for
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn get_bandwidth_status(state: State<'_, SyncthingState>) -> BandwidthStatus {
+    bandwidth::bandwidth_status(
+        state
+            .bandwidth
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .schedule(),
+    )
+}
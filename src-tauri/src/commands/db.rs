@@ -0,0 +1,58 @@
+//! Out-of-sync item and file detail commands.
+
+use crate::core::db::{self, FileDetails, FilePage, NeedPage};
+use crate::{SyncthingError, SyncthingState};
+use tauri::State;
+
+/// Files the local device still needs in a folder, split into progress, queued and rest,
+/// optionally with the devices that have each of them
+#[tauri::command]
+pub async fn get_folder_need(
+    state: State<'_, SyncthingState>,
+    folder_id: String,
+    page: Option<u32>,
+    per_page: Option<u32>,
+    availability: Option<bool>,
+) -> Result<NeedPage, SyncthingError> {
+    db::get_folder_need(
+        &state.context(),
+        &folder_id,
+        page,
+        per_page,
+        availability.unwrap_or(false),
+    )
+    .await
+}
+
+/// Files a remote device still needs from us in a folder
+#[tauri::command]
+pub async fn get_remote_need(
+    state: State<'_, SyncthingState>,
+    folder_id: String,
+    device_id: String,
+    page: Option<u32>,
+    per_page: Option<u32>,
+) -> Result<FilePage, SyncthingError> {
+    db::get_remote_need(&state.context(), &folder_id, &device_id, page, per_page).await
+}
+
+/// Files changed locally in a receive-only folder
+#[tauri::command]
+pub async fn get_local_changed(
+    state: State<'_, SyncthingState>,
+    folder_id: String,
+    page: Option<u32>,
+    per_page: Option<u32>,
+) -> Result<FilePage, SyncthingError> {
+    db::get_local_changed(&state.context(), &folder_id, page, per_page).await
+}
+
+/// Local and global versions of a file, and which devices have it
+#[tauri::command]
+pub async fn get_file_info(
+    state: State<'_, SyncthingState>,
+    folder_id: String,
+    file: String,
+) -> Result<FileDetails, SyncthingError> {
+    db::get_file_info(&state.context(), &folder_id, &file).await
+}
@@ -1,33 +1,31 @@
 //! Device management commands.
 
+use crate::core::invite::{self, DeviceInvite, Invite, InviteOptions};
+use crate::core::{devices, system};
+use crate::device_id;
+use crate::models::{DeviceConfiguration, Extra};
+use crate::qr::QrCode;
+use crate::sync_windows::Target;
 use crate::{SyncthingError, SyncthingState};
+use tauri::ipc::Response;
 use tauri::State;
 
+/// Pixels per QR code module
+const QR_SCALE: u32 = 8;
+
 /// Get this device's ID
 #[tauri::command]
 pub async fn get_device_id(state: State<'_, SyncthingState>) -> Result<String, SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/system/status",
-        state.config.host, state.config.port
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let json: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
+    system::get_device_id(&state.context()).await
+}
 
-    json["myID"]
-        .as_str()
-        .map(std::string::ToString::to_string)
-        .ok_or_else(|| SyncthingError::ParseError("No device ID found".into()))
+/// Normalize a device ID typed or pasted by the user
+///
+/// Fails with a validation error saying what is wrong with it.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn validate_device_id(device_id: String) -> Result<String, SyncthingError> {
+    device_id::normalize(&device_id)
 }
 
 /// Add a new device to Syncthing
@@ -37,56 +35,9 @@ pub async fn add_device(
     device_id: String,
     name: String,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-
-    let config_url = format!(
-        "http://{}:{}/rest/config",
-        state.config.host, state.config.port
-    );
-
-    let res = client
-        .get(&config_url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    if let Some(devices) = config["devices"].as_array() {
-        for device in devices {
-            if device["deviceID"].as_str() == Some(&device_id) {
-                return Err(SyncthingError::ProcessError("Device already exists".into()));
-            }
-        }
-    }
-
-    let new_device = serde_json::json!({
-        "deviceID": device_id,
-        "name": name,
-        "addresses": ["dynamic"],
-        "compression": "metadata",
-        "introducer": false,
-        "paused": false,
-        "autoAcceptFolders": false,
-    });
-
-    if let Some(devices) = config["devices"].as_array_mut() {
-        devices.push(new_device);
-    }
-
-    client
-        .put(&config_url)
-        .header("X-API-Key", &state.config.api_key)
-        .json(&config)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    Ok(())
+    let ctx = state.context();
+    let device = devices::new_device(&ctx, &device_id, name).await?;
+    devices::add_device(&ctx, &device).await
 }
 
 /// Add device with advanced options
@@ -100,61 +51,32 @@ pub async fn add_device_advanced(
     compression: Option<String>,
     introducer: Option<bool>,
     auto_accept_folders: Option<bool>,
-    max_send_kbps: Option<u32>,
-    max_recv_kbps: Option<u32>,
+    max_send_kbps: Option<i32>,
+    max_recv_kbps: Option<i32>,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-
-    let config_url = format!(
-        "http://{}:{}/rest/config",
-        state.config.host, state.config.port
-    );
-
-    let res = client
-        .get(&config_url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    if let Some(devices) = config["devices"].as_array() {
-        for device in devices {
-            if device["deviceID"].as_str() == Some(&device_id) {
-                return Err(SyncthingError::ProcessError("Device already exists".into()));
-            }
-        }
+    let ctx = state.context();
+    let mut device = devices::new_device(&ctx, &device_id, name).await?;
+    // Settings the caller leaves out keep the user's configured defaults
+    if let Some(addresses) = addresses {
+        device.addresses = addresses;
     }
-
-    let new_device = serde_json::json!({
-        "deviceID": device_id,
-        "name": name,
-        "addresses": addresses.unwrap_or_else(|| vec!["dynamic".to_string()]),
-        "compression": compression.unwrap_or_else(|| "metadata".to_string()),
-        "introducer": introducer.unwrap_or(false),
-        "paused": false,
-        "autoAcceptFolders": auto_accept_folders.unwrap_or(false),
-        "maxSendKbps": max_send_kbps.unwrap_or(0),
-        "maxRecvKbps": max_recv_kbps.unwrap_or(0),
-    });
-
-    if let Some(devices) = config["devices"].as_array_mut() {
-        devices.push(new_device);
+    if let Some(compression) = compression {
+        device.compression = compression;
+    }
+    if let Some(introducer) = introducer {
+        device.introducer = introducer;
+    }
+    if let Some(auto_accept) = auto_accept_folders {
+        device.auto_accept_folders = auto_accept;
+    }
+    if let Some(kbps) = max_send_kbps {
+        device.max_send_kbps = kbps;
+    }
+    if let Some(kbps) = max_recv_kbps {
+        device.max_recv_kbps = kbps;
     }
 
-    client
-        .put(&config_url)
-        .header("X-API-Key", &state.config.api_key)
-        .json(&config)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    Ok(())
+    devices::add_device(&ctx, &device).await
 }
 
 /// Remove a device from Syncthing
@@ -163,20 +85,7 @@ pub async fn remove_device(
     state: State<'_, SyncthingState>,
     device_id: String,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/devices/{}",
-        state.config.host, state.config.port, device_id
-    );
-
-    client
-        .delete(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    Ok(())
+    devices::remove_device(&state.context(), &device_id).await
 }
 
 /// Update device configuration
@@ -184,43 +93,9 @@ pub async fn remove_device(
 pub async fn update_device_config(
     state: State<'_, SyncthingState>,
     device_id: String,
-    updates: serde_json::Value,
+    updates: Extra,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/devices/{}",
-        state.config.host, state.config.port, device_id
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut device_config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    if let (Some(config_obj), Some(updates_obj)) =
-        (device_config.as_object_mut(), updates.as_object())
-    {
-        for (key, value) in updates_obj {
-            config_obj.insert(key.clone(), value.clone());
-        }
-    }
-
-    client
-        .put(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .json(&device_config)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    Ok(())
+    devices::update_device_config(&state.context(), &device_id, &updates).await
 }
 
 /// Get detailed device configuration
@@ -228,98 +103,67 @@ pub async fn update_device_config(
 pub async fn get_device_config(
     state: State<'_, SyncthingState>,
     device_id: String,
-) -> Result<serde_json::Value, SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/devices/{}",
-        state.config.host, state.config.port, device_id
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let json: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    Ok(json)
+) -> Result<DeviceConfiguration, SyncthingError> {
+    devices::get_device_config(&state.context(), &device_id).await
 }
 
-/// Pause a device
+/// Pause a device; the sync window scheduler will not resume it
 #[tauri::command]
 pub async fn pause_device(
     state: State<'_, SyncthingState>,
     device_id: String,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/devices/{}",
-        state.config.host, state.config.port, device_id
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    config["paused"] = serde_json::Value::Bool(true);
-
-    client
-        .put(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .json(&config)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
+    devices::pause_device(&state.context(), &device_id).await?;
+    state.forget_scheduled_pause(&Target::device(device_id::normalize(&device_id)?));
     Ok(())
 }
 
-/// Resume a device
+/// Resume a device, even outside its sync windows
 #[tauri::command]
 pub async fn resume_device(
     state: State<'_, SyncthingState>,
     device_id: String,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/devices/{}",
-        state.config.host, state.config.port, device_id
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
+    devices::resume_device(&state.context(), &device_id).await?;
+    state.forget_scheduled_pause(&Target::device(device_id::normalize(&device_id)?));
+    Ok(())
+}
 
-    config["paused"] = serde_json::Value::Bool(false);
+/// Invitation to this device, optionally suggesting folders to share
+#[tauri::command]
+pub async fn get_device_invite(
+    state: State<'_, SyncthingState>,
+    folder_ids: Option<Vec<String>>,
+    options: Option<InviteOptions>,
+) -> Result<DeviceInvite, SyncthingError> {
+    invite::create_invite(
+        &state.context(),
+        &folder_ids.unwrap_or_default(),
+        &options.unwrap_or_default(),
+    )
+    .await
+}
 
-    client
-        .put(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .json(&config)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
+/// The invitation from `get_device_invite` as a QR code PNG
+#[tauri::command]
+pub async fn render_device_invite_qr(
+    state: State<'_, SyncthingState>,
+    folder_ids: Option<Vec<String>>,
+    options: Option<InviteOptions>,
+) -> Result<Response, SyncthingError> {
+    let invite = invite::create_invite(
+        &state.context(),
+        &folder_ids.unwrap_or_default(),
+        &options.unwrap_or_default(),
+    )
+    .await?;
+    let png = QrCode::encode(&invite.payload)?.to_png(QR_SCALE)?;
+    Ok(Response::new(png))
+}
 
-    Ok(())
+/// Decode a scanned or pasted invitation, or a bare device ID
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn decode_device_invite(payload: String) -> Result<Invite, SyncthingError> {
+    Invite::parse(&payload)
 }
@@ -1,6 +1,11 @@
 //! Folder management commands.
 
+use crate::completion::{Completion, CompletionMatrix};
+use crate::core::folders::{self, AddedFolder, FolderShare};
+use crate::models::{Extra, FolderConfiguration, Versioning};
+use crate::sync_windows::Target;
 use crate::{SyncthingError, SyncthingState};
+use std::collections::BTreeMap;
 use tauri::State;
 
 /// Get folder status
@@ -9,98 +14,46 @@ pub async fn get_folder_status(
     state: State<'_, SyncthingState>,
     folder_id: String,
 ) -> Result<serde_json::Value, SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/db/status?folder={}",
-        state.config.host, state.config.port, folder_id
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
+    folders::get_folder_status(&state.context(), &folder_id).await
+}
 
-    let json: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
+/// How much of a folder a device still needs
+#[tauri::command]
+pub async fn get_folder_completion(
+    state: State<'_, SyncthingState>,
+    folder_id: String,
+    device_id: String,
+) -> Result<Completion, SyncthingError> {
+    folders::get_folder_completion(&state.context(), &folder_id, &device_id).await
+}
 
-    Ok(json)
+/// Completion of every folder on every remote device it is shared with
+#[tauri::command]
+pub async fn get_completion_matrix(
+    state: State<'_, SyncthingState>,
+) -> Result<CompletionMatrix, SyncthingError> {
+    folders::get_completion_matrix(&state.context()).await
 }
 
-/// Pause a folder
+/// Pause a folder; the sync window scheduler will not resume it
 #[tauri::command]
 pub async fn pause_folder(
     state: State<'_, SyncthingState>,
     folder_id: String,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/folders/{}",
-        state.config.host, state.config.port, folder_id
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    config["paused"] = serde_json::Value::Bool(true);
-
-    client
-        .put(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .json(&config)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
+    folders::pause_folder(&state.context(), &folder_id).await?;
+    state.forget_scheduled_pause(&Target::folder(folder_id));
     Ok(())
 }
 
-/// Resume a folder
+/// Resume a folder, even outside its sync windows
 #[tauri::command]
 pub async fn resume_folder(
     state: State<'_, SyncthingState>,
     folder_id: String,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/folders/{}",
-        state.config.host, state.config.port, folder_id
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    config["paused"] = serde_json::Value::Bool(false);
-
-    client
-        .put(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .json(&config)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
+    folders::resume_folder(&state.context(), &folder_id).await?;
+    state.forget_scheduled_pause(&Target::folder(folder_id));
     Ok(())
 }
 
@@ -110,273 +63,89 @@ pub async fn rescan_folder(
     state: State<'_, SyncthingState>,
     folder_id: String,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/db/scan?folder={}",
-        state.config.host, state.config.port, folder_id
-    );
-
-    client
-        .post(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
+    folders::rescan_folder(&state.context(), &folder_id).await
+}
 
-    Ok(())
+/// A random, unused folder ID to prefill the add-folder form with
+#[tauri::command]
+pub async fn generate_folder_id(
+    state: State<'_, SyncthingState>,
+) -> Result<String, SyncthingError> {
+    folders::generate_folder_id(&state.context()).await
 }
 
-/// Add a new folder to Syncthing
+/// Add a new folder to Syncthing after pre-flighting its path
+///
+/// A folder ID is generated when none is given.
 #[tauri::command]
 pub async fn add_folder(
     state: State<'_, SyncthingState>,
-    folder_id: String,
+    folder_id: Option<String>,
     folder_label: String,
     folder_path: String,
-) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-
-    let config_url = format!(
-        "http://{}:{}/rest/config",
-        state.config.host, state.config.port
-    );
-
-    let res = client
-        .get(&config_url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    if let Some(folders) = config["folders"].as_array() {
-        for folder in folders {
-            if folder["id"].as_str() == Some(&folder_id) {
-                return Err(SyncthingError::ProcessError("Folder already exists".into()));
-            }
-        }
-    }
-
-    let new_folder = serde_json::json!({
-        "id": folder_id,
-        "label": folder_label,
-        "path": folder_path,
-        "type": "sendreceive",
-        "rescanIntervalS": 3600,
-        "fsWatcherEnabled": true,
-        "fsWatcherDelayS": 10,
-        "ignorePerms": false,
-        "autoNormalize": true,
-        "paused": false,
-        "devices": [],
-        "minDiskFree": {
-            "value": 1,
-            "unit": "%"
-        },
-        "versioning": {
-            "type": "",
-            "params": {},
-            "cleanupIntervalS": 3600,
-            "fsPath": "",
-            "fsType": "basic"
-        },
-        "copiers": 0,
-        "pullerMaxPendingKiB": 0,
-        "hashers": 0,
-        "order": "random",
-        "ignoreDelete": false,
-        "scanProgressIntervalS": 0,
-        "pullerPauseS": 0,
-        "maxConflicts": 10,
-        "disableSparseFiles": false,
-        "disableTempIndexes": false,
-        "weakHashThresholdPct": 25,
-        "markerName": ".stfolder",
-        "copyOwnershipFromParent": false,
-        "modTimeWindowS": 0,
-        "maxConcurrentWrites": 2,
-        "disableFsync": false,
-        "blockPullOrder": "standard",
-        "copyRangeMethod": "standard",
-        "caseSensitiveFS": false,
-        "junctionsAsDirs": false,
-        "syncOwnership": false,
-        "sendOwnership": false,
-        "syncXattrs": false,
-        "sendXattrs": false,
-        "xattrFilter": {
-            "entries": [],
-            "maxSingleEntrySize": 1024,
-            "maxTotalSize": 4096
-        }
-    });
-
-    if let Some(folders) = config["folders"].as_array_mut() {
-        folders.push(new_folder);
-    }
-
-    client
-        .put(&config_url)
-        .header("X-API-Key", &state.config.api_key)
-        .json(&config)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    Ok(())
+) -> Result<AddedFolder, SyncthingError> {
+    let ctx = state.context();
+    let folder_id = match folder_id {
+        Some(id) => id,
+        None => folders::generate_folder_id(&ctx).await?,
+    };
+    let folder = folders::new_folder(&ctx, folder_id.clone(), folder_label, folder_path).await?;
+    let check = folders::add_folder(&ctx, &folder).await?;
+    Ok(AddedFolder { folder_id, check })
 }
 
 /// Add a folder with advanced configuration options
+///
+/// The folder is created already shared with `devices`, each optionally
+/// encrypted with its own password.
 #[tauri::command]
 #[allow(clippy::too_many_arguments)]
 pub async fn add_folder_advanced(
     state: State<'_, SyncthingState>,
-    folder_id: String,
+    folder_id: Option<String>,
     folder_label: String,
     folder_path: String,
+    folder_type: Option<String>,
     versioning_type: Option<String>,
-    versioning_params: Option<serde_json::Value>,
-    rescan_interval_s: Option<u32>,
+    versioning_params: Option<BTreeMap<String, String>>,
+    rescan_interval_s: Option<i32>,
     fs_watcher_enabled: Option<bool>,
-    fs_watcher_delay_s: Option<u32>,
+    fs_watcher_delay_s: Option<f64>,
     ignore_perms: Option<bool>,
-) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-
-    let config_url = format!(
-        "http://{}:{}/rest/config",
-        state.config.host, state.config.port
-    );
-
-    let res = client
-        .get(&config_url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    if let Some(folders) = config["folders"].as_array() {
-        for folder in folders {
-            if folder["id"].as_str() == Some(&folder_id) {
-                return Err(SyncthingError::ProcessError("Folder already exists".into()));
-            }
-        }
-    }
-
-    let versioning = match versioning_type.as_deref() {
-        Some("simple") => serde_json::json!({
-            "type": "simple",
-            "params": versioning_params.clone().unwrap_or_else(|| serde_json::json!({
-                "keep": "5"
-            })),
-            "cleanupIntervalS": 3600,
-            "fsPath": "",
-            "fsType": "basic"
-        }),
-        Some("staggered") => serde_json::json!({
-            "type": "staggered",
-            "params": versioning_params.clone().unwrap_or_else(|| serde_json::json!({
-                "cleanInterval": "3600",
-                "maxAge": "31536000"
-            })),
-            "cleanupIntervalS": 3600,
-            "fsPath": "",
-            "fsType": "basic"
-        }),
-        Some("trashcan") => serde_json::json!({
-            "type": "trashcan",
-            "params": versioning_params.clone().unwrap_or_else(|| serde_json::json!({
-                "cleanoutDays": "0"
-            })),
-            "cleanupIntervalS": 3600,
-            "fsPath": "",
-            "fsType": "basic"
-        }),
-        Some("external") => serde_json::json!({
-            "type": "external",
-            "params": versioning_params.unwrap_or_else(|| serde_json::json!({
-                "command": ""
-            })),
-            "cleanupIntervalS": 3600,
-            "fsPath": "",
-            "fsType": "basic"
-        }),
-        _ => serde_json::json!({
-            "type": "",
-            "params": {},
-            "cleanupIntervalS": 3600,
-            "fsPath": "",
-            "fsType": "basic"
-        }),
+    devices: Option<Vec<FolderShare>>,
+) -> Result<AddedFolder, SyncthingError> {
+    let ctx = state.context();
+    let folder_id = match folder_id {
+        Some(id) => id,
+        None => folders::generate_folder_id(&ctx).await?,
     };
-
-    let new_folder = serde_json::json!({
-        "id": folder_id,
-        "label": folder_label,
-        "path": folder_path,
-        "type": "sendreceive",
-        "rescanIntervalS": rescan_interval_s.unwrap_or(3600),
-        "fsWatcherEnabled": fs_watcher_enabled.unwrap_or(true),
-        "fsWatcherDelayS": fs_watcher_delay_s.unwrap_or(10),
-        "ignorePerms": ignore_perms.unwrap_or(false),
-        "autoNormalize": true,
-        "paused": false,
-        "devices": [],
-        "minDiskFree": { "value": 1, "unit": "%" },
-        "versioning": versioning,
-        "copiers": 0,
-        "pullerMaxPendingKiB": 0,
-        "hashers": 0,
-        "order": "random",
-        "ignoreDelete": false,
-        "scanProgressIntervalS": 0,
-        "pullerPauseS": 0,
-        "maxConflicts": 10,
-        "disableSparseFiles": false,
-        "disableTempIndexes": false,
-        "weakHashThresholdPct": 25,
-        "markerName": ".stfolder",
-        "copyOwnershipFromParent": false,
-        "modTimeWindowS": 0,
-        "maxConcurrentWrites": 2,
-        "disableFsync": false,
-        "blockPullOrder": "standard",
-        "copyRangeMethod": "standard",
-        "caseSensitiveFS": false,
-        "junctionsAsDirs": false,
-        "syncOwnership": false,
-        "sendOwnership": false,
-        "syncXattrs": false,
-        "sendXattrs": false,
-        "xattrFilter": {
-            "entries": [],
-            "maxSingleEntrySize": 1024,
-            "maxTotalSize": 4096
-        }
-    });
-
-    if let Some(folders) = config["folders"].as_array_mut() {
-        folders.push(new_folder);
+    let mut folder =
+        folders::new_folder(&ctx, folder_id.clone(), folder_label, folder_path).await?;
+    // Settings the caller leaves out keep the user's configured defaults
+    if let Some(kind) = folder_type {
+        folder.folder_type = kind;
+    }
+    if let Some(kind) = versioning_type {
+        folder.versioning = Versioning::with_type(&kind, versioning_params)?;
+    }
+    if let Some(interval) = rescan_interval_s {
+        folder.rescan_interval_s = interval;
+    }
+    if let Some(enabled) = fs_watcher_enabled {
+        folder.fs_watcher_enabled = enabled;
+    }
+    if let Some(delay) = fs_watcher_delay_s {
+        folder.fs_watcher_delay_s = delay;
+    }
+    if let Some(ignore) = ignore_perms {
+        folder.ignore_perms = ignore;
+    }
+    if let Some(devices) = devices {
+        folders::share_with_devices(&mut folder, &devices)?;
     }
 
-    client
-        .put(&config_url)
-        .header("X-API-Key", &state.config.api_key)
-        .json(&config)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    Ok(())
+    let check = folders::add_folder(&ctx, &folder).await?;
+    Ok(AddedFolder { folder_id, check })
 }
 
 /// Remove a folder from Syncthing
@@ -385,20 +154,7 @@ pub async fn remove_folder(
     state: State<'_, SyncthingState>,
     folder_id: String,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/folders/{}",
-        state.config.host, state.config.port, folder_id
-    );
-
-    client
-        .delete(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    Ok(())
+    folders::remove_folder(&state.context(), &folder_id).await
 }
 
 /// Update folder configuration
@@ -406,43 +162,9 @@ pub async fn remove_folder(
 pub async fn update_folder_config(
     state: State<'_, SyncthingState>,
     folder_id: String,
-    updates: serde_json::Value,
+    updates: Extra,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/folders/{}",
-        state.config.host, state.config.port, folder_id
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut folder_config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    if let (Some(config_obj), Some(updates_obj)) =
-        (folder_config.as_object_mut(), updates.as_object())
-    {
-        for (key, value) in updates_obj {
-            config_obj.insert(key.clone(), value.clone());
-        }
-    }
-
-    client
-        .put(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .json(&folder_config)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    Ok(())
+    folders::update_folder_config(&state.context(), &folder_id, &updates).await
 }
 
 /// Get detailed folder configuration
@@ -450,87 +172,25 @@ pub async fn update_folder_config(
 pub async fn get_folder_config(
     state: State<'_, SyncthingState>,
     folder_id: String,
-) -> Result<serde_json::Value, SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/folders/{}",
-        state.config.host, state.config.port, folder_id
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let json: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    Ok(json)
+) -> Result<FolderConfiguration, SyncthingError> {
+    folders::get_folder_config(&state.context(), &folder_id).await
 }
 
-/// Share a folder with a specific device
+/// Share a folder with a specific device, encrypted for it when a password is given
 #[tauri::command]
 pub async fn share_folder(
     state: State<'_, SyncthingState>,
     folder_id: String,
     device_id: String,
+    encryption_password: Option<String>,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/folders/{}",
-        state.config.host, state.config.port, folder_id
-    );
-
-    // 1. Get current folder config
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut folder_config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    // 2. Check if device is already added
-    let mut device_exists = false;
-    if let Some(devices) = folder_config["devices"].as_array() {
-        for device in devices {
-            if device["deviceID"].as_str() == Some(&device_id) {
-                device_exists = true;
-                break;
-            }
-        }
-    }
-
-    // 3. Add device if not exists
-    if !device_exists {
-        let new_device_entry = serde_json::json!({
-            "deviceID": device_id,
-            "introducedBy": ""
-        });
-
-        if let Some(devices) = folder_config["devices"].as_array_mut() {
-            devices.push(new_device_entry);
-        }
-
-        // 4. Update config
-        client
-            .put(&url)
-            .header("X-API-Key", &state.config.api_key)
-            .json(&folder_config)
-            .send()
-            .await
-            .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-    }
-
-    Ok(())
+    folders::share_folder(
+        &state.context(),
+        &folder_id,
+        &device_id,
+        encryption_password.as_deref(),
+    )
+    .await
 }
 
 /// Unshare a folder from a device
@@ -540,35 +200,23 @@ pub async fn unshare_folder(
     folder_id: String,
     device_id: String,
 ) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/config/folders/{}",
-        state.config.host, state.config.port, folder_id
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let mut folder_config: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    if let Some(devices) = folder_config["devices"].as_array_mut() {
-        devices.retain(|d| d["deviceID"].as_str() != Some(&device_id));
-    }
+    folders::unshare_folder(&state.context(), &folder_id, &device_id).await
+}
 
-    client
-        .put(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .json(&folder_config)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
+/// Override Changes on a send-only folder
+#[tauri::command]
+pub async fn override_folder_changes(
+    state: State<'_, SyncthingState>,
+    folder_id: String,
+) -> Result<(), SyncthingError> {
+    folders::override_folder_changes(&state.context(), &folder_id).await
+}
 
-    Ok(())
+/// Revert Local Changes on a receive-only folder
+#[tauri::command]
+pub async fn revert_folder_changes(
+    state: State<'_, SyncthingState>,
+    folder_id: String,
+) -> Result<(), SyncthingError> {
+    folders::revert_folder_changes(&state.context(), &folder_id).await
 }
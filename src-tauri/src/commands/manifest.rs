@@ -0,0 +1,43 @@
+//! Declarative manifest commands.
+
+use crate::core::manifest;
+use crate::manifest::{Manifest, ManifestFormat};
+use crate::transaction::ConfigDiff;
+use crate::{SyncthingError, SyncthingState};
+use tauri::State;
+
+/// Export the folders, devices and sharing of the live config as a manifest document
+#[tauri::command]
+pub async fn export_config_manifest(
+    state: State<'_, SyncthingState>,
+    format: ManifestFormat,
+) -> Result<String, SyncthingError> {
+    manifest::export_config_manifest(&state.context(), format).await
+}
+
+/// Compute what applying a manifest would change, without applying it
+#[tauri::command]
+pub async fn preview_config_manifest(
+    state: State<'_, SyncthingState>,
+    document: String,
+    format: ManifestFormat,
+    prune: bool,
+) -> Result<ConfigDiff, SyncthingError> {
+    let desired = Manifest::parse(&document, format)?;
+    manifest::preview_config_manifest(&state.context(), &desired, prune).await
+}
+
+/// Converge the live config on a manifest in a single validated write
+///
+/// With `prune`, folders and devices the manifest does not list are removed.
+/// Returns the applied diff.
+#[tauri::command]
+pub async fn apply_config_manifest(
+    state: State<'_, SyncthingState>,
+    document: String,
+    format: ManifestFormat,
+    prune: bool,
+) -> Result<ConfigDiff, SyncthingError> {
+    let desired = Manifest::parse(&document, format)?;
+    manifest::apply_config_manifest(&state.context(), &desired, prune).await
+}
@@ -4,19 +4,33 @@
 //! - `system`: Lifecycle, ping, status, restart
 //! - `config`: Configuration, options, connections
 //! - `folders`: Folder management operations
+//! - `db`: Out-of-sync items and per-file details
 //! - `devices`: Device management operations
 //! - `files`: File browser, conflicts, versions, ignores
 //! - `events`: Events, logs, tray updates
 //! - `pending`: Pending device/folder requests
+//! - `profiles`: Connection profiles for multiple Syncthing instances
+//! - `snapshots`: Automatic config snapshots, diff and restore
+//! - `stats`: Device and folder statistics, device overview
+//! - `bandwidth`: Time-of-day bandwidth schedule
+//! - `sync_windows`: Folder and device sync windows
+//! - `manifest`: Declarative TOML/YAML export and apply
 
 // Expose submodules publicly so Tauri's generate_handler! macro can access
 // the __cmd__ prefixed items it generates
+pub mod bandwidth;
 pub mod config;
+pub mod db;
 pub mod devices;
 pub mod events;
 pub mod files;
 pub mod folders;
+pub mod manifest;
 pub mod pending;
+pub mod profiles;
+pub mod snapshots;
+pub mod stats;
+pub mod sync_windows;
 pub mod system;
 
 // Re-export all commands for use in lib.rs invoke_handler
@@ -30,17 +44,39 @@ pub use system::{
 // Config commands
 pub use config::{get_config, get_connections, update_options};
 
+// Connection profile commands
+pub use profiles::{list_profiles, remove_profile, save_profile, switch_profile};
+
+// Declarative manifest commands
+pub use manifest::{apply_config_manifest, export_config_manifest, preview_config_manifest};
+
+// Config snapshot commands
+pub use snapshots::{diff_config_snapshot, list_config_snapshots, restore_config_snapshot};
+
 // Folder commands
 pub use folders::{
-    add_folder, add_folder_advanced, get_folder_config, get_folder_status, pause_folder,
-    remove_folder, rescan_folder, resume_folder, share_folder, unshare_folder,
-    update_folder_config,
+    add_folder, add_folder_advanced, get_folder_config, get_folder_status, override_folder_changes,
+    pause_folder, remove_folder, rescan_folder, resume_folder, revert_folder_changes, share_folder,
+    unshare_folder, update_folder_config,
 };
 
+// Out-of-sync item and file detail commands
+pub use db::{get_file_info, get_folder_need, get_local_changed, get_remote_need};
+
+// Statistics commands
+pub use stats::{get_device_overview, get_device_stats, get_folder_stats};
+
+// Bandwidth schedule commands
+pub use bandwidth::{get_bandwidth_schedule, get_bandwidth_status, set_bandwidth_schedule};
+
+// Sync window commands
+pub use sync_windows::{get_sync_window_state, get_sync_windows, set_sync_windows};
+
 // Device commands
 pub use devices::{
-    add_device, add_device_advanced, get_device_config, get_device_id, pause_device, remove_device,
-    resume_device, update_device_config,
+    add_device, add_device_advanced, decode_device_invite, get_device_config, get_device_id,
+    get_device_invite, pause_device, remove_device, render_device_invite_qr, resume_device,
+    update_device_config, validate_device_id,
 };
 
 // File commands (browser, conflicts, versions, ignores)
@@ -0,0 +1,105 @@
+//! Connection profile commands.
+
+use crate::profiles::{ConnectionProfile, ProfileInfo};
+use crate::{tls, SyncthingError, SyncthingState};
+use std::sync::PoisonError;
+use tauri::{AppHandle, Emitter, State};
+
+/// Emitted with the new profile name whenever the active profile changes
+pub const ACTIVE_PROFILE_CHANGED: &str = "active-profile-changed";
+
+/// List all connection profiles (API keys are never returned)
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn list_profiles(state: State<'_, SyncthingState>) -> Vec<ProfileInfo> {
+    state
+        .profiles
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .list()
+}
+
+/// Add a profile or update an existing one with the same name
+#[tauri::command]
+pub async fn save_profile(
+    state: State<'_, SyncthingState>,
+    profile: ConnectionProfile,
+) -> Result<(), SyncthingError> {
+    let (profile, is_active) = {
+        let store = state
+            .profiles
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let is_active = store.active().name == profile.name;
+        (store.prepare(profile)?, is_active)
+    };
+
+    // Pick up new host/port/key straight away if the active profile was
+    // edited, and only save the edit once it could be connected to
+    if is_active {
+        state.activate(&profile)?;
+    }
+    state
+        .profiles
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .upsert(profile)
+}
+
+/// Remove a profile; removing the active one switches back to `local`
+#[tauri::command]
+pub async fn remove_profile(
+    app: AppHandle,
+    state: State<'_, SyncthingState>,
+    name: String,
+) -> Result<(), SyncthingError> {
+    let mut store = state
+        .profiles
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let was_active = store.active().name == name;
+    store.remove(&name)?;
+    let active = store.active().clone();
+    drop(store);
+
+    if was_active {
+        state.activate(&active)?;
+        let _ = app.emit(ACTIVE_PROFILE_CHANGED, &active.name);
+    }
+    Ok(())
+}
+
+/// Fetch the certificate fingerprint presented by a remote instance, for the
+/// user to confirm before saving it as the profile's pinned fingerprint
+#[tauri::command]
+pub async fn fetch_certificate_fingerprint(
+    host: String,
+    port: u16,
+) -> Result<String, SyncthingError> {
+    tls::fetch_fingerprint(&host, port).await
+}
+
+/// Route all further commands through the named profile
+#[tauri::command]
+pub async fn switch_profile(
+    app: AppHandle,
+    state: State<'_, SyncthingState>,
+    name: String,
+) -> Result<(), SyncthingError> {
+    let profile = state
+        .profiles
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .require(&name)?
+        .clone();
+
+    // Only remember the switch once the profile could be connected to
+    state.activate(&profile)?;
+    state
+        .profiles
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .set_active(&profile.name)?;
+    let _ = app.emit(ACTIVE_PROFILE_CHANGED, &profile.name);
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! Config snapshot commands.
+
+use crate::core::snapshots;
+use crate::snapshots::SnapshotInfo;
+use crate::transaction::ConfigDiff;
+use crate::{SyncthingError, SyncthingState};
+use tauri::State;
+
+/// List config snapshots of the active profile, newest first
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn list_config_snapshots(state: State<'_, SyncthingState>) -> Vec<SnapshotInfo> {
+    snapshots::list_config_snapshots(&state.context())
+}
+
+/// What changed in the live config since snapshot `id` was taken
+#[tauri::command]
+pub async fn diff_config_snapshot(
+    state: State<'_, SyncthingState>,
+    id: String,
+) -> Result<ConfigDiff, SyncthingError> {
+    snapshots::diff_config_snapshot(&state.context(), &id).await
+}
+
+/// Put the folders, devices and options of snapshot `id` back in place
+///
+/// The current config is snapshotted first, so a restore can itself be
+/// undone. Returns the changes the restore made.
+#[tauri::command]
+pub async fn restore_config_snapshot(
+    state: State<'_, SyncthingState>,
+    id: String,
+) -> Result<ConfigDiff, SyncthingError> {
+    snapshots::restore_config_snapshot(&state.context(), &id).await
+}
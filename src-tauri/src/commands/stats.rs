@@ -0,0 +1,29 @@
+//! Device and folder statistics commands.
+
+use crate::core::stats::{self, DeviceOverview, DeviceStats, FolderStats};
+use crate::{SyncthingError, SyncthingState};
+use tauri::State;
+
+/// When each device was last seen and how long it stayed connected
+#[tauri::command]
+pub async fn get_device_stats(
+    state: State<'_, SyncthingState>,
+) -> Result<Vec<DeviceStats>, SyncthingError> {
+    stats::get_device_stats(&state.context()).await
+}
+
+/// When each folder was last scanned and which file it last synced
+#[tauri::command]
+pub async fn get_folder_stats(
+    state: State<'_, SyncthingState>,
+) -> Result<Vec<FolderStats>, SyncthingError> {
+    stats::get_folder_stats(&state.context()).await
+}
+
+/// Connection state, statistics and shared folders of every remote device
+#[tauri::command]
+pub async fn get_device_overview(
+    state: State<'_, SyncthingState>,
+) -> Result<Vec<DeviceOverview>, SyncthingError> {
+    stats::get_device_overview(&state.context()).await
+}
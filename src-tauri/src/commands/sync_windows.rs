@@ -0,0 +1,54 @@
+//! Folder and device sync window commands.
+
+use crate::core::sync_windows::{self, SyncWindowState};
+use crate::sync_windows::SyncWindows;
+use crate::{SyncthingError, SyncthingState};
+use std::sync::PoisonError;
+use tauri::State;
+
+/// The persisted sync windows
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn get_sync_windows(state: State<'_, SyncthingState>) -> SyncWindows {
+    state
+        .sync_windows
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .windows()
+        .clone()
+}
+
+/// Save the sync windows; the scheduler applies them straight away
+#[tauri::command]
+pub async fn set_sync_windows(
+    state: State<'_, SyncthingState>,
+    windows: SyncWindows,
+) -> Result<(), SyncthingError> {
+    state
+        .sync_windows
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .set_windows(windows)?;
+    state.schedule_changed();
+    Ok(())
+}
+
+/// Whether each folder and device under sync windows is inside one, whether
+/// it is paused and whether that pause was manual or scheduled
+#[tauri::command]
+pub async fn get_sync_window_state(
+    state: State<'_, SyncthingState>,
+) -> Result<Vec<SyncWindowState>, SyncthingError> {
+    let ctx = state.context();
+    let (windows, scheduled) = {
+        let mut store = state
+            .sync_windows
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        (
+            store.windows().clone(),
+            store.scheduled_pauses(&ctx.profile),
+        )
+    };
+    sync_windows::get_sync_window_state(&ctx, &windows, &scheduled).await
+}
@@ -1,10 +1,12 @@
 //! System lifecycle and status commands.
 
+use crate::core::system;
+use crate::discovery::Discovery;
+use crate::sidecar::{self, OutputLine};
 use crate::{SyncthingError, SyncthingState};
 use serde::Serialize;
 use tauri::AppHandle;
 use tauri::State;
-use tauri_plugin_shell::ShellExt;
 
 /// Information about Syncthing installation
 #[derive(Debug, Serialize)]
@@ -96,41 +98,14 @@ fn find_syncthing_path() -> Option<String> {
     }
 }
 
-/// Start the Syncthing sidecar process
+/// Start the Syncthing sidecar process under supervision
 #[tauri::command]
-pub async fn start_syncthing_sidecar(
-    app: AppHandle,
-    state: State<'_, SyncthingState>,
-) -> Result<String, SyncthingError> {
-    let mut child_guard = state
-        .sidecar_child
-        .lock()
-        .map_err(|e| SyncthingError::ProcessError(format!("Failed to acquire lock: {e}")))?;
-
-    if child_guard.is_some() {
-        return Ok("Syncthing already running".into());
+pub async fn start_syncthing_sidecar(app: AppHandle) -> Result<String, SyncthingError> {
+    if sidecar::start(&app) {
+        Ok("Syncthing sidecar started successfully".into())
+    } else {
+        Ok("Syncthing already running".into())
     }
-
-    let sidecar_command = app
-        .shell()
-        .sidecar("syncthing")
-        .map_err(|e| {
-            SyncthingError::ProcessError(format!("Failed to create sidecar command: {e}"))
-        })?
-        .args([
-            "-no-browser",
-            "-no-restart",
-            &format!("-gui-apikey={}", state.config.api_key),
-            &format!("-gui-address={}:{}", state.config.host, state.config.port),
-        ]);
-
-    let (_rx, child) = sidecar_command.spawn().map_err(|e| {
-        SyncthingError::ProcessError(format!("Failed to spawn syncthing sidecar: {e}"))
-    })?;
-
-    *child_guard = Some(child);
-    drop(child_guard);
-    Ok("Syncthing sidecar started successfully".into())
 }
 
 /// Stop the Syncthing sidecar process
@@ -138,49 +113,26 @@ pub async fn start_syncthing_sidecar(
 pub async fn stop_syncthing_sidecar(
     state: State<'_, SyncthingState>,
 ) -> Result<String, SyncthingError> {
-    let mut child_guard = state
-        .sidecar_child
-        .lock()
-        .map_err(|e| SyncthingError::ProcessError(format!("Failed to acquire lock: {e}")))?;
-
-    if let Some(child) = child_guard.take() {
-        child.kill().map_err(|e| {
-            SyncthingError::ProcessError(format!("Failed to kill sidecar process: {e}"))
-        })?;
+    if state.sidecar.stop()? {
         Ok("Syncthing sidecar stopped".into())
     } else {
         Ok("Syncthing sidecar was not running".into())
     }
 }
 
+/// Get buffered sidecar stdout/stderr, optionally only lines after sequence number `since`
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn get_sidecar_output(state: State<'_, SyncthingState>, since: Option<u64>) -> Vec<OutputLine> {
+    state.sidecar.output(since)
+}
+
 /// Ping Syncthing to check if it's responding
 #[tauri::command]
 pub async fn ping_syncthing(
     state: State<'_, SyncthingState>,
 ) -> Result<serde_json::Value, SyncthingError> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let url = format!(
-        "http://{}:{}/rest/system/ping",
-        state.config.host, state.config.port
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let json: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    Ok(json)
+    system::ping_syncthing(&state.context()).await
 }
 
 /// Get Syncthing system status
@@ -188,49 +140,26 @@ pub async fn ping_syncthing(
 pub async fn get_system_status(
     state: State<'_, SyncthingState>,
 ) -> Result<serde_json::Value, SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/system/status",
-        state.config.host, state.config.port
-    );
-
-    let res = client
-        .get(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    let json: serde_json::Value = res
-        .json()
-        .await
-        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
-
-    Ok(json)
+    system::get_system_status(&state.context()).await
 }
 
 /// Restart Syncthing
 #[tauri::command]
 pub async fn restart_syncthing(state: State<'_, SyncthingState>) -> Result<(), SyncthingError> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "http://{}:{}/rest/system/restart",
-        state.config.host, state.config.port
-    );
-
-    client
-        .post(&url)
-        .header("X-API-Key", &state.config.api_key)
-        .send()
-        .await
-        .map_err(|e| SyncthingError::HttpError(e.to_string()))?;
-
-    Ok(())
+    system::restart_syncthing(&state.context()).await
 }
 
-/// Get the current API configuration (for debugging)
+/// Get the active profile's API address (for debugging)
 #[tauri::command]
 #[allow(clippy::needless_pass_by_value)]
 pub fn get_api_config(state: State<'_, SyncthingState>) -> (String, u16) {
-    (state.config.host.clone(), state.config.port)
+    let config = state.config();
+    (config.host, config.port)
+}
+
+/// Report which Syncthing `config.xml` was used for the local instance and what it contained
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn get_syncthing_discovery(state: State<'_, SyncthingState>) -> Discovery {
+    state.discovery.clone()
 }
@@ -0,0 +1,95 @@
+//! How far each device is with each folder.
+//!
+//! `/rest/db/completion` answers for one folder and device at a time, which
+//! adds up quickly for a folders × devices matrix. Answers are therefore kept
+//! in a [`CompletionCache`] that the event pump refreshes from
+//! `FolderCompletion` events, and that is cleared whenever events may have
+//! been missed (a profile switch or a lost event stream).
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Completion of one folder on one device (`/rest/db/completion`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Completion {
+    /// Percentage of the global state the device has, 0–100
+    pub completion: f64,
+    pub global_bytes: u64,
+    pub global_items: u64,
+    pub need_bytes: u64,
+    pub need_items: u64,
+    pub need_deletes: u64,
+    /// `valid`, `paused`, `notSharing` or `unknown`
+    pub remote_state: String,
+    pub sequence: i64,
+}
+
+/// A remote device, as a column of the matrix
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatrixDevice {
+    pub device_id: String,
+    pub name: String,
+}
+
+/// Completion of a folder on one device it is shared with
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceCompletion {
+    pub device_id: String,
+    #[serde(flatten)]
+    pub completion: Completion,
+}
+
+/// One folder, as a row of the matrix
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderCompletionRow {
+    pub folder_id: String,
+    pub label: String,
+    /// Paused folders report no completion
+    pub paused: bool,
+    /// Only the devices the folder is shared with
+    pub devices: Vec<DeviceCompletion>,
+}
+
+/// Completion of every folder on every remote device it is shared with
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionMatrix {
+    pub devices: Vec<MatrixDevice>,
+    pub folders: Vec<FolderCompletionRow>,
+}
+
+/// Last known completion per folder and device; cheap to clone and shared
+#[derive(Debug, Clone, Default)]
+pub struct CompletionCache {
+    entries: Arc<Mutex<HashMap<(String, String), Completion>>>,
+}
+
+impl CompletionCache {
+    pub fn get(&self, folder_id: &str, device_id: &str) -> Option<Completion> {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&(folder_id.to_string(), device_id.to_string()))
+            .cloned()
+    }
+
+    pub fn insert(&self, folder_id: &str, device_id: &str, completion: Completion) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((folder_id.to_string(), device_id.to_string()), completion);
+    }
+
+    /// Forget everything, e.g. after events may have been missed
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
@@ -0,0 +1,97 @@
+//! Applying bandwidth limits from the schedule.
+
+use super::config::get_config;
+use super::Context;
+use crate::bandwidth::{BandwidthLimits, BandwidthSchedule};
+use crate::client::path_segment;
+use crate::schedule::{epoch_millis_in, WeekMinute};
+use crate::SyncthingError;
+use serde::Serialize;
+
+/// What the schedule wants right now
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthStatus {
+    pub enabled: bool,
+    /// Name of the rule in effect; `None` while the default limits apply
+    pub rule: Option<String>,
+    /// Global limits and those of every device the schedule manages
+    pub limits: BandwidthLimits,
+    /// When a different rule takes over, in milliseconds since the Unix epoch
+    pub next_change: Option<u64>,
+}
+
+impl BandwidthStatus {
+    /// One line for the tray tooltip
+    pub fn summary(&self) -> String {
+        format!(
+            "Bandwidth: {} ({})",
+            self.rule.as_deref().unwrap_or("default"),
+            self.limits.global
+        )
+    }
+}
+
+/// Evaluate `schedule` for the current local time
+pub fn bandwidth_status(schedule: &BandwidthSchedule) -> BandwidthStatus {
+    let at = WeekMinute::now();
+    let rule = schedule.active_rule(at);
+    BandwidthStatus {
+        enabled: schedule.enabled,
+        rule: rule.map(|i| schedule.rules[i].name.clone()),
+        limits: schedule.resolve(rule),
+        next_change: schedule.minutes_until_change(at).map(epoch_millis_in),
+    }
+}
+
+/// Write `limits` to the options and device configs where they differ
+///
+/// Not snapshotted like user changes, since it runs at every rule boundary.
+/// Devices that are not configured are skipped. Returns whether anything
+/// was written.
+pub async fn apply_bandwidth_limits(
+    ctx: &Context,
+    limits: &BandwidthLimits,
+) -> Result<bool, SyncthingError> {
+    let config = get_config(ctx).await?;
+    let mut changed = false;
+
+    let options = &config.options;
+    if options.max_send_kbps != limits.global.max_send_kbps
+        || options.max_recv_kbps != limits.global.max_recv_kbps
+    {
+        ctx.client
+            .patch(
+                "/rest/config/options",
+                &serde_json::json!({
+                    "maxSendKbps": limits.global.max_send_kbps,
+                    "maxRecvKbps": limits.global.max_recv_kbps,
+                }),
+            )
+            .await?;
+        changed = true;
+    }
+
+    for (device_id, wanted) in &limits.devices {
+        let Some(device) = config.device(device_id) else {
+            continue;
+        };
+        if device.max_send_kbps == wanted.max_send_kbps
+            && device.max_recv_kbps == wanted.max_recv_kbps
+        {
+            continue;
+        }
+        ctx.client
+            .patch(
+                &format!("/rest/config/devices/{}", path_segment(device_id)),
+                &serde_json::json!({
+                    "maxSendKbps": wanted.max_send_kbps,
+                    "maxRecvKbps": wanted.max_recv_kbps,
+                }),
+            )
+            .await?;
+        changed = true;
+    }
+
+    Ok(changed)
+}
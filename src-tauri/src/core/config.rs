@@ -0,0 +1,59 @@
+//! Configuration, options and batched transactions.
+
+use super::Context;
+use crate::models::{apply_update, Configuration, Extra, Options};
+use crate::transaction::{self, ConfigDiff, ConfigOperation};
+use crate::SyncthingError;
+
+/// Get Syncthing configuration
+pub async fn get_config(ctx: &Context) -> Result<Configuration, SyncthingError> {
+    ctx.client.get("/rest/config", &[]).await
+}
+
+/// Get Syncthing connections info
+pub async fn get_connections(ctx: &Context) -> Result<serde_json::Value, SyncthingError> {
+    ctx.client.get("/rest/system/connections", &[]).await
+}
+
+/// Update global Syncthing options
+pub async fn update_options(ctx: &Context, options: &Extra) -> Result<(), SyncthingError> {
+    ctx.snapshot("update_options").await?;
+    let current: Options = ctx.client.get("/rest/config/options", &[]).await?;
+    apply_update(&current, options, "options")?;
+
+    // Only the validated keys are sent, so unrelated concurrent changes survive
+    ctx.client.patch("/rest/config/options", options).await
+}
+
+/// Compute what a batch of operations would change, without applying it
+pub async fn preview_config_transaction(
+    ctx: &Context,
+    operations: &[ConfigOperation],
+) -> Result<ConfigDiff, SyncthingError> {
+    let before = get_config(ctx).await?;
+    let mut after = before.clone();
+    transaction::apply(&mut after, operations)?;
+
+    Ok(transaction::diff(&before, &after))
+}
+
+/// Apply a batch of operations in a single validated config write
+///
+/// Either every operation takes effect or none does. Returns the applied diff.
+pub async fn apply_config_transaction(
+    ctx: &Context,
+    operations: &[ConfigOperation],
+) -> Result<ConfigDiff, SyncthingError> {
+    ctx.snapshot("apply_config_transaction").await?;
+    let mut applied = ConfigDiff::default();
+    ctx.client
+        .modify("/rest/config", |config: &mut Configuration| {
+            let before = config.clone();
+            transaction::apply(config, operations)?;
+            applied = transaction::diff(&before, config);
+            Ok(!applied.is_empty())
+        })
+        .await?;
+
+    Ok(applied)
+}
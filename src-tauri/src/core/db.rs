@@ -0,0 +1,339 @@
+//! Out-of-sync items and per-file details of a folder.
+//!
+//! Typed, paginated views of `/rest/db/need`, `/rest/db/remoteneed` and
+//! `/rest/db/localchanged`, plus `/rest/db/file`, for finding out what keeps
+//! a folder from reaching 100% and which devices hold the latest copy.
+
+use super::devices::list_devices;
+use super::Context;
+use crate::device_id::normalize;
+use crate::SyncthingError;
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Items per page when the caller does not say
+pub const DEFAULT_PER_PAGE: u32 = 100;
+/// Largest page accepted
+pub const MAX_PER_PAGE: u32 = 1000;
+/// Availability lookups in flight at once, one `/rest/db/file` request each
+const AVAILABILITY_LOOKUPS: usize = 8;
+
+/// Kind of a file entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileType {
+    #[default]
+    File,
+    Directory,
+    Symlink,
+}
+
+impl<'de> Deserialize<'de> for FileType {
+    /// Syncthing sends `FILE_INFO_TYPE_*` names, older releases plain numbers
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Value::deserialize(deserializer)? {
+            Value::Number(n) => match n.as_u64() {
+                Some(1) => Self::Directory,
+                Some(2..=4) => Self::Symlink,
+                _ => Self::File,
+            },
+            Value::String(s) if s.contains("DIRECTORY") => Self::Directory,
+            Value::String(s) if s.contains("SYMLINK") => Self::Symlink,
+            _ => Self::File,
+        })
+    }
+}
+
+/// A file as recorded in Syncthing's database
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct FileEntry {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub file_type: FileType,
+    pub size: i64,
+    /// RFC 3339 modification time
+    pub modified: String,
+    /// Short ID of the device that made the last change
+    pub modified_by: String,
+    pub deleted: bool,
+    pub invalid: bool,
+    pub ignored: bool,
+    pub no_permissions: bool,
+    pub sequence: i64,
+    /// Version vector as `SHORTID:counter` entries
+    pub version: Vec<String>,
+    /// Devices that have this version; only filled in for needed items when
+    /// asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub availability: Option<Vec<String>>,
+    /// Why the availability lookup for this item failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub availability_error: Option<String>,
+}
+
+/// One page of what the local device still needs (`/rest/db/need`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NeedPage {
+    /// Being downloaded right now
+    pub progress: Vec<FileEntry>,
+    /// Next in line
+    pub queued: Vec<FileEntry>,
+    /// Everything else
+    pub rest: Vec<FileEntry>,
+    pub page: u32,
+    #[serde(alias = "perpage")]
+    pub per_page: u32,
+}
+
+/// One page of file entries (`/rest/db/remoteneed`, `/rest/db/localchanged`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FilePage {
+    pub files: Vec<FileEntry>,
+    pub page: u32,
+    #[serde(alias = "perpage")]
+    pub per_page: u32,
+}
+
+/// Validate 1-based paging arguments, applying the defaults
+fn paging(page: Option<u32>, per_page: Option<u32>) -> Result<(String, String), SyncthingError> {
+    let page = page.unwrap_or(1);
+    let per_page = per_page.unwrap_or(DEFAULT_PER_PAGE);
+    if page == 0 {
+        return Err(SyncthingError::validation("page", "starts at 1"));
+    }
+    if per_page == 0 || per_page > MAX_PER_PAGE {
+        return Err(SyncthingError::validation(
+            "perPage",
+            format!("must be between 1 and {MAX_PER_PAGE}"),
+        ));
+    }
+    Ok((page.to_string(), per_page.to_string()))
+}
+
+/// Files the local device still needs in `folder_id`
+///
+/// With `availability`, each item is also looked up to see which devices
+/// have it. That is one request per item, so it is opt-in; a failed lookup
+/// is reported on the item rather than failing the page.
+pub async fn get_folder_need(
+    ctx: &Context,
+    folder_id: &str,
+    page: Option<u32>,
+    per_page: Option<u32>,
+    availability: bool,
+) -> Result<NeedPage, SyncthingError> {
+    let (page, per_page) = paging(page, per_page)?;
+    let mut need: NeedPage = ctx
+        .client
+        .get(
+            "/rest/db/need",
+            &[
+                ("folder", folder_id),
+                ("page", &page),
+                ("perpage", &per_page),
+            ],
+        )
+        .await?;
+
+    if availability {
+        let files = need
+            .progress
+            .iter_mut()
+            .chain(&mut need.queued)
+            .chain(&mut need.rest);
+        stream::iter(files)
+            .for_each_concurrent(AVAILABILITY_LOOKUPS, |file| async move {
+                match get_availability(ctx, folder_id, &file.name).await {
+                    Ok(devices) => file.availability = Some(devices),
+                    Err(e) => file.availability_error = Some(e.to_string()),
+                }
+            })
+            .await;
+    }
+    Ok(need)
+}
+
+/// Files `device_id` still needs from the local device in `folder_id`
+pub async fn get_remote_need(
+    ctx: &Context,
+    folder_id: &str,
+    device_id: &str,
+    page: Option<u32>,
+    per_page: Option<u32>,
+) -> Result<FilePage, SyncthingError> {
+    let device_id = normalize(device_id)?;
+    let (page, per_page) = paging(page, per_page)?;
+    ctx.client
+        .get(
+            "/rest/db/remoteneed",
+            &[
+                ("folder", folder_id),
+                ("device", &device_id),
+                ("page", &page),
+                ("perpage", &per_page),
+            ],
+        )
+        .await
+}
+
+/// Files changed locally in a receive-only folder, i.e. what a revert would undo
+pub async fn get_local_changed(
+    ctx: &Context,
+    folder_id: &str,
+    page: Option<u32>,
+    per_page: Option<u32>,
+) -> Result<FilePage, SyncthingError> {
+    let (page, per_page) = paging(page, per_page)?;
+    ctx.client
+        .get(
+            "/rest/db/localchanged",
+            &[
+                ("folder", folder_id),
+                ("page", &page),
+                ("perpage", &per_page),
+            ],
+        )
+        .await
+}
+
+/// A device holding the global version of a file
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileAvailability {
+    pub device_id: String,
+    /// Configured name, if the device is known
+    pub name: Option<String>,
+    pub connected: bool,
+    /// The device only has the file in a temporary, partly downloaded copy
+    pub from_temporary: bool,
+}
+
+/// Everything Syncthing knows about one file (`/rest/db/file`)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDetails {
+    /// The local copy; `None` if this device does not have the file at all
+    pub local: Option<FileEntry>,
+    /// The newest version in the cluster
+    pub global: FileEntry,
+    /// Whether the local copy is the global version
+    pub in_sync: bool,
+    pub availability: Vec<FileAvailability>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawFileDetails {
+    availability: Vec<RawAvailability>,
+    global: FileEntry,
+    local: FileEntry,
+}
+
+/// `{ "id": ..., "fromTemporary": ... }`; bare IDs are tolerated too
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawAvailability {
+    Device {
+        id: String,
+        #[serde(default, rename = "fromTemporary")]
+        from_temporary: bool,
+    },
+    Id(String),
+}
+
+impl RawAvailability {
+    fn into_parts(self) -> (String, bool) {
+        match self {
+            Self::Device { id, from_temporary } => (id, from_temporary),
+            Self::Id(id) => (id, false),
+        }
+    }
+}
+
+async fn get_raw_file(
+    ctx: &Context,
+    folder_id: &str,
+    file: &str,
+) -> Result<RawFileDetails, SyncthingError> {
+    ctx.client
+        .get("/rest/db/file", &[("folder", folder_id), ("file", file)])
+        .await
+}
+
+/// Local and global versions of `file` in `folder_id`, and who has it
+pub async fn get_file_info(
+    ctx: &Context,
+    folder_id: &str,
+    file: &str,
+) -> Result<FileDetails, SyncthingError> {
+    let raw = get_raw_file(ctx, folder_id, file).await?;
+    let names: HashMap<String, String> = list_devices(ctx)
+        .await?
+        .into_iter()
+        .map(|d| (d.device_id, d.name))
+        .collect();
+    let connections: Value = ctx.client.get("/rest/system/connections", &[]).await?;
+
+    let availability = raw
+        .availability
+        .into_iter()
+        .map(|entry| {
+            let (device_id, from_temporary) = entry.into_parts();
+            FileAvailability {
+                name: names.get(&device_id).filter(|n| !n.is_empty()).cloned(),
+                connected: connections["connections"][&device_id]["connected"]
+                    .as_bool()
+                    .unwrap_or(false),
+                from_temporary,
+                device_id,
+            }
+        })
+        .collect();
+
+    // Syncthing sends an empty entry when the file is not present locally
+    let local = Some(raw.local).filter(|local| !local.name.is_empty());
+    let in_sync = local
+        .as_ref()
+        .is_some_and(|local| same_version(&local.version, &raw.global.version));
+
+    Ok(FileDetails {
+        local,
+        global: raw.global,
+        in_sync,
+        availability,
+    })
+}
+
+/// Whether two version vectors are equal, regardless of entry order
+fn same_version(a: &[String], b: &[String]) -> bool {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort_unstable();
+    b.sort_unstable();
+    a == b
+}
+
+/// IDs of the devices holding the global version of `file`
+///
+/// A file that disappeared since the listing has no availability.
+async fn get_availability(
+    ctx: &Context,
+    folder_id: &str,
+    file: &str,
+) -> Result<Vec<String>, SyncthingError> {
+    match get_raw_file(ctx, folder_id, file).await {
+        Ok(raw) => Ok(raw
+            .availability
+            .into_iter()
+            .map(|entry| entry.into_parts().0)
+            .collect()),
+        Err(SyncthingError::NotFound { .. }) => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,105 @@
+//! Device management operations.
+
+use super::Context;
+use crate::client::path_segment;
+use crate::device_id::{normalize, DeviceId};
+use crate::models::{apply_update, DeviceConfiguration, Extra};
+use crate::SyncthingError;
+
+/// Config path of `device_id`, which is normalized first
+fn device_path(device_id: &str) -> Result<String, SyncthingError> {
+    Ok(format!(
+        "/rest/config/devices/{}",
+        path_segment(&normalize(device_id)?)
+    ))
+}
+
+/// A device started from Syncthing's device defaults, as the web UI does
+///
+/// `device_id` is validated and normalized. Falls back to built-in defaults
+/// on Syncthing versions without `/rest/config/defaults`.
+pub async fn new_device(
+    ctx: &Context,
+    device_id: &str,
+    name: String,
+) -> Result<DeviceConfiguration, SyncthingError> {
+    let device_id = normalize(device_id)?;
+    let defaults = match ctx.client.get("/rest/config/defaults/device", &[]).await {
+        Ok(defaults) => Some(defaults),
+        Err(SyncthingError::NotFound { .. }) => None,
+        Err(e) => return Err(e),
+    };
+    Ok(DeviceConfiguration::from_defaults(
+        defaults, device_id, name,
+    ))
+}
+
+/// All configured devices, including this one
+pub async fn list_devices(ctx: &Context) -> Result<Vec<DeviceConfiguration>, SyncthingError> {
+    ctx.client.get("/rest/config/devices", &[]).await
+}
+
+/// Get detailed device configuration
+pub async fn get_device_config(
+    ctx: &Context,
+    device_id: &str,
+) -> Result<DeviceConfiguration, SyncthingError> {
+    ctx.client.get(&device_path(device_id)?, &[]).await
+}
+
+/// Add `device`, refusing to replace an existing device with the same ID
+pub async fn add_device(ctx: &Context, device: &DeviceConfiguration) -> Result<(), SyncthingError> {
+    let device_id = DeviceId::parse(&device.device_id)?;
+    let device = DeviceConfiguration {
+        device_id: device_id.to_string(),
+        ..device.clone()
+    };
+    ctx.snapshot("add_device").await?;
+    let path = device_path(device_id.as_str())?;
+    if ctx.client.create(&path, &device).await? {
+        Ok(())
+    } else {
+        Err(SyncthingError::conflict(
+            path,
+            format!("Device `{device_id}` already exists"),
+        ))
+    }
+}
+
+/// Remove a device from Syncthing
+pub async fn remove_device(ctx: &Context, device_id: &str) -> Result<(), SyncthingError> {
+    let path = device_path(device_id)?;
+    ctx.snapshot("remove_device").await?;
+    ctx.client.delete(&path, &[]).await
+}
+
+/// Update device configuration
+pub async fn update_device_config(
+    ctx: &Context,
+    device_id: &str,
+    updates: &Extra,
+) -> Result<(), SyncthingError> {
+    let path = device_path(device_id)?;
+    ctx.snapshot("update_device_config").await?;
+    let current: DeviceConfiguration = ctx.client.get(&path, &[]).await?;
+    apply_update(&current, updates, "device")?;
+
+    // Only the validated keys are sent, so unrelated concurrent changes survive
+    ctx.client.patch(&path, updates).await
+}
+
+/// Pause a device (not snapshotted, like [`super::folders::pause_folder`])
+pub async fn pause_device(ctx: &Context, device_id: &str) -> Result<(), SyncthingError> {
+    let path = device_path(device_id)?;
+    ctx.client
+        .patch(&path, &serde_json::json!({ "paused": true }))
+        .await
+}
+
+/// Resume a device
+pub async fn resume_device(ctx: &Context, device_id: &str) -> Result<(), SyncthingError> {
+    let path = device_path(device_id)?;
+    ctx.client
+        .patch(&path, &serde_json::json!({ "paused": false }))
+        .await
+}
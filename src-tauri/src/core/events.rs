@@ -0,0 +1,52 @@
+//! Events and logs.
+
+use super::Context;
+use crate::SyncthingError;
+use reqwest::Method;
+
+/// Get events from Syncthing
+///
+/// One-shot long-poll for callers that track `since` themselves.
+pub async fn get_events(
+    ctx: &Context,
+    since: Option<u64>,
+    limit: Option<u32>,
+    timeout: Option<u32>,
+) -> Result<serde_json::Value, SyncthingError> {
+    let mut params = Vec::new();
+    if let Some(s) = since {
+        params.push(("since", s.to_string()));
+    }
+    if let Some(l) = limit {
+        params.push(("limit", l.to_string()));
+    }
+    if let Some(t) = timeout {
+        params.push(("timeout", t.to_string()));
+    }
+
+    // Long-poll: allow the request to outlive Syncthing's own timeout
+    let request = ctx
+        .client
+        .request(Method::GET, "/rest/events")
+        .query(&params)
+        .timeout(std::time::Duration::from_secs(
+            u64::from(timeout.unwrap_or(60)) + 5,
+        ));
+
+    ctx.client.execute_json(request).await
+}
+
+/// Get Syncthing logs
+pub async fn get_system_logs(
+    ctx: &Context,
+    since: Option<&str>,
+) -> Result<serde_json::Value, SyncthingError> {
+    match since {
+        Some(since_time) => {
+            ctx.client
+                .get("/rest/system/log", &[("since", since_time)])
+                .await
+        },
+        None => ctx.client.get("/rest/system/log", &[]).await,
+    }
+}
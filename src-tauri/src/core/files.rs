@@ -0,0 +1,328 @@
+//! File browser, conflicts, versions, and ignore patterns.
+
+use super::Context;
+use crate::SyncthingError;
+use std::path::Path;
+
+/// Browse files in a folder (list directory contents)
+/// This version returns immediate children only (for file browser UI)
+pub async fn browse_folder(
+    ctx: &Context,
+    folder_id: &str,
+    prefix: Option<&str>,
+) -> Result<serde_json::Value, SyncthingError> {
+    let mut query = vec![("folder", folder_id), ("levels", "0")];
+    if let Some(p) = prefix {
+        query.push(("prefix", p));
+    }
+
+    ctx.client.get("/rest/db/browse", &query).await
+}
+
+/// Browse all files in a folder recursively (for indexing)
+/// Returns a flat list of all files with their full paths
+pub async fn browse_folder_recursive(
+    ctx: &Context,
+    folder_id: &str,
+) -> Result<Vec<serde_json::Value>, SyncthingError> {
+    let json: serde_json::Value = ctx
+        .client
+        .get(
+            "/rest/db/browse",
+            &[("folder", folder_id), ("levels", "999")],
+        )
+        .await?;
+
+    let mut files = Vec::new();
+    if let Some(arr) = json.as_array() {
+        flatten_browse_response(arr, "", &mut files);
+    }
+
+    Ok(files)
+}
+
+/// Helper function to flatten the nested browse response
+fn flatten_browse_response(
+    items: &[serde_json::Value],
+    parent_path: &str,
+    result: &mut Vec<serde_json::Value>,
+) {
+    for item in items {
+        if let Some(obj) = item.as_object() {
+            let name = obj.get("name").and_then(|n| n.as_str()).unwrap_or("");
+            let full_path = if parent_path.is_empty() {
+                name.to_string()
+            } else {
+                format!("{parent_path}/{name}")
+            };
+
+            let item_type = obj.get("type").and_then(|t| t.as_str()).unwrap_or("");
+            let is_directory = item_type == "FILE_INFO_TYPE_DIRECTORY";
+
+            let flat_item = serde_json::json!({
+                "name": full_path,
+                "size": obj.get("size").and_then(serde_json::Value::as_i64).unwrap_or(0),
+                "modTime": obj.get("modTime").cloned().unwrap_or(serde_json::Value::Null),
+                "type": if is_directory { "directory" } else { "file" }
+            });
+
+            result.push(flat_item);
+
+            if let Some(children) = obj.get("children").and_then(|c| c.as_array()) {
+                flatten_browse_response(children, &full_path, result);
+            }
+        }
+    }
+}
+
+/// Get ignore patterns for a folder
+pub async fn get_folder_ignores(
+    ctx: &Context,
+    folder_id: &str,
+) -> Result<serde_json::Value, SyncthingError> {
+    ctx.client
+        .get("/rest/db/ignores", &[("folder", folder_id)])
+        .await
+}
+
+/// Set ignore patterns for a folder
+pub async fn set_folder_ignores(
+    ctx: &Context,
+    folder_id: &str,
+    ignore_patterns: &[String],
+) -> Result<(), SyncthingError> {
+    let body = serde_json::json!({
+        "ignore": ignore_patterns
+    });
+
+    ctx.client
+        .post_json("/rest/db/ignores", &[("folder", folder_id)], &body)
+        .await
+}
+
+/// Get list of conflict files for a folder by scanning the filesystem
+pub fn scan_for_conflicts(folder_path: &Path) -> Vec<serde_json::Value> {
+    fn scan_dir(
+        dir: &std::path::Path,
+        conflicts: &mut Vec<serde_json::Value>,
+        base: &std::path::Path,
+    ) {
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                        if !name.starts_with('.') && name != ".stversions" {
+                            scan_dir(&path, conflicts, base);
+                        }
+                    }
+                } else if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                    if name.contains(".sync-conflict-") {
+                        let relative_path = path.strip_prefix(base).unwrap_or(&path);
+                        if let Ok(metadata) = std::fs::metadata(&path) {
+                            let original = extract_original_filename(name);
+                            conflicts.push(serde_json::json!({
+                                "name": relative_path.to_string_lossy(),
+                                "original": original,
+                                "size": metadata.len(),
+                                "modTime": metadata.modified().ok().map(|t| {
+                                    let duration = t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+                                    duration.as_secs()
+                                }),
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn extract_original_filename(conflict_name: &str) -> String {
+        if let Some(pos) = conflict_name.find(".sync-conflict-") {
+            let before = &conflict_name[..pos];
+            let after = &conflict_name[pos..];
+            if let Some(ext_pos) = after.rfind('.') {
+                let ext = &after[ext_pos..];
+                return format!("{before}{ext}");
+            }
+            return before.to_string();
+        }
+        conflict_name.to_string()
+    }
+
+    let mut conflicts = Vec::new();
+    if folder_path.exists() {
+        scan_dir(folder_path, &mut conflicts, folder_path);
+    }
+
+    conflicts
+}
+
+/// Delete a conflict file (resolve by keeping the original)
+pub fn delete_conflict_file(folder_path: &Path, conflict_file: &str) -> Result<(), SyncthingError> {
+    let full_path = folder_path.join(conflict_file);
+
+    if full_path.exists() {
+        std::fs::remove_file(&full_path).map_err(|e| SyncthingError::filesystem(&full_path, e))?;
+    }
+
+    Ok(())
+}
+
+/// Resolve conflict by replacing original with conflict file
+pub fn resolve_conflict_keep_conflict(
+    folder_path: &Path,
+    original_file: &str,
+    conflict_file: &str,
+) -> Result<(), SyncthingError> {
+    let original_path = folder_path.join(original_file);
+    let conflict_path = folder_path.join(conflict_file);
+
+    if original_path.exists() {
+        std::fs::remove_file(&original_path)
+            .map_err(|e| SyncthingError::filesystem(&original_path, e))?;
+    }
+
+    if conflict_path.exists() {
+        std::fs::rename(&conflict_path, &original_path)
+            .map_err(|e| SyncthingError::filesystem(&conflict_path, e))?;
+    }
+
+    Ok(())
+}
+
+/// Browse the .stversions folder for old file versions
+pub fn browse_versions(folder_path: &Path, prefix: Option<&str>) -> Vec<serde_json::Value> {
+    use std::fs;
+
+    let versions_path = folder_path.join(".stversions");
+    let browse_path = if let Some(p) = prefix {
+        versions_path.join(p)
+    } else {
+        versions_path
+    };
+
+    if !browse_path.exists() {
+        return Vec::new();
+    }
+
+    let mut entries = Vec::new();
+
+    if let Ok(dir_entries) = fs::read_dir(&browse_path) {
+        for entry in dir_entries.flatten() {
+            let path = entry.path();
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_string();
+
+            if let Ok(metadata) = entry.metadata() {
+                let is_dir = metadata.is_dir();
+
+                let (original_name, version_time) = if is_dir {
+                    (name.clone(), None)
+                } else {
+                    parse_version_filename(&name)
+                };
+
+                entries.push(serde_json::json!({
+                    "name": name,
+                    "originalName": original_name,
+                    "type": if is_dir { "directory" } else { "file" },
+                    "size": if is_dir { None::<u64> } else { Some(metadata.len()) },
+                    "modTime": metadata.modified().ok().map(|t| {
+                        t.duration_since(std::time::UNIX_EPOCH)
+                            .map_or(0, |d| d.as_secs())
+                    }),
+                    "versionTime": version_time,
+                }));
+            }
+        }
+    }
+
+    entries.sort_by(|a, b| {
+        let a_is_dir = a["type"].as_str() == Some("directory");
+        let b_is_dir = b["type"].as_str() == Some("directory");
+
+        if a_is_dir != b_is_dir {
+            return if a_is_dir {
+                std::cmp::Ordering::Less
+            } else {
+                std::cmp::Ordering::Greater
+            };
+        }
+
+        let a_time = a["modTime"].as_u64().unwrap_or(0);
+        let b_time = b["modTime"].as_u64().unwrap_or(0);
+        b_time.cmp(&a_time)
+    });
+
+    entries
+}
+
+/// Parse versioned filename to extract original name and version timestamp
+pub fn parse_version_filename(name: &str) -> (String, Option<String>) {
+    if let Some(tilde_pos) = name.rfind('~') {
+        let before_tilde = &name[..tilde_pos];
+        let after_tilde = &name[tilde_pos + 1..];
+
+        let (version_part, extension) = after_tilde.find('.').map_or_else(
+            || (after_tilde.to_string(), ""),
+            |dot_pos| (after_tilde[..dot_pos].to_string(), &after_tilde[dot_pos..]),
+        );
+
+        if version_part.len() == 15 && version_part.chars().nth(8) == Some('-') {
+            let original = format!("{before_tilde}{extension}");
+
+            let formatted = format!(
+                "{}-{}-{} {}:{}:{}",
+                &version_part[0..4],
+                &version_part[4..6],
+                &version_part[6..8],
+                &version_part[9..11],
+                &version_part[11..13],
+                &version_part[13..15]
+            );
+
+            return (original, Some(formatted));
+        }
+    }
+
+    (name.to_string(), None)
+}
+
+/// Restore a versioned file to its original location
+pub fn restore_version(
+    folder_path: &Path,
+    version_path: &str,
+    original_name: &str,
+    overwrite: bool,
+) -> Result<(), SyncthingError> {
+    use std::fs;
+
+    let source = folder_path.join(".stversions").join(version_path);
+    let dest = folder_path.join(original_name);
+
+    if !source.exists() {
+        return Err(SyncthingError::validation(
+            "versionPath",
+            "Version file not found",
+        ));
+    }
+
+    if dest.exists() && !overwrite {
+        return Err(SyncthingError::validation(
+            "overwrite",
+            "Destination file exists. Set overwrite=true to replace.",
+        ));
+    }
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| SyncthingError::filesystem(parent, e))?;
+    }
+
+    fs::copy(&source, &dest).map_err(|e| SyncthingError::filesystem(&dest, e))?;
+
+    Ok(())
+}
@@ -0,0 +1,589 @@
+//! Folder management operations.
+
+use super::pending::get_pending_folders;
+use super::system::get_device_id;
+use super::Context;
+use crate::client::path_segment;
+use crate::completion::{
+    Completion, CompletionMatrix, DeviceCompletion, FolderCompletionRow, MatrixDevice,
+};
+use crate::device_id::normalize;
+use crate::models::{apply_update, Configuration, Extra, FolderConfiguration};
+use crate::preflight::{self, PathCheck};
+use crate::SyncthingError;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Characters of generated folder IDs, as in Syncthing's web UI
+const FOLDER_ID_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+/// Random IDs tried before giving up; a clash is already very unlikely
+const FOLDER_ID_ATTEMPTS: usize = 8;
+
+/// A folder that was just added
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddedFolder {
+    /// The folder's ID, which may have been generated
+    pub folder_id: String,
+    #[serde(flatten)]
+    pub check: PathCheck,
+}
+
+/// A device to share a new folder with
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderShare {
+    pub device_id: String,
+    /// Encrypt what is sent to the device with this password, for a peer
+    /// that keeps the folder as `receiveencrypted`
+    #[serde(default)]
+    pub encryption_password: Option<String>,
+}
+
+fn folder_path(folder_id: &str) -> String {
+    format!("/rest/config/folders/{}", path_segment(folder_id))
+}
+
+/// A random folder ID in Syncthing's `abcde-12345` style
+pub fn random_folder_id() -> String {
+    let mut rng = rand::thread_rng();
+    let mut part = || -> String {
+        (0..5)
+            .map(|_| char::from(FOLDER_ID_CHARS[rng.gen_range(0..FOLDER_ID_CHARS.len())]))
+            .collect()
+    };
+    format!("{}-{}", part(), part())
+}
+
+/// Reject folder IDs Syncthing would refuse or mangle
+///
+/// The ID ends up in the config XML, REST query strings and database keys, so
+/// it must be non-empty, free of control characters and not padded with
+/// whitespace. Syncthing decodes `/rest/config/folders/{id}` before routing
+/// it, so an ID containing `/`, or one that is `.` or `..`, cannot be
+/// addressed there even when percent-encoded. Anything else survives
+/// [`path_segment`] unchanged.
+pub fn validate_folder_id(folder_id: &str) -> Result<(), SyncthingError> {
+    let invalid = |message: &str| SyncthingError::validation("id", message);
+    if folder_id.is_empty() {
+        return Err(invalid("must not be empty"));
+    }
+    if folder_id.trim() != folder_id {
+        return Err(invalid("must not start or end with whitespace"));
+    }
+    if folder_id.chars().any(char::is_control) {
+        return Err(invalid("must not contain control characters"));
+    }
+    if folder_id.contains('/') {
+        return Err(invalid("must not contain `/`"));
+    }
+    if folder_id == "." || folder_id == ".." {
+        return Err(invalid("must not be `.` or `..`"));
+    }
+    Ok(())
+}
+
+/// A random folder ID that is neither configured nor offered by another device
+pub async fn generate_folder_id(ctx: &Context) -> Result<String, SyncthingError> {
+    let folders = list_folders(ctx).await?;
+    let pending = get_pending_folders(ctx).await?;
+    let taken =
+        |id: &str| folders.iter().any(|f| f.id == id) || pending.iter().any(|p| p.folder_id == id);
+    unused_folder_id(std::iter::repeat_with(random_folder_id), taken).ok_or_else(|| {
+        SyncthingError::conflict(
+            "/rest/config/folders",
+            format!("no unused folder ID found in {FOLDER_ID_ATTEMPTS} attempts"),
+        )
+    })
+}
+
+/// The first of up to [`FOLDER_ID_ATTEMPTS`] `candidates` that is not `taken`
+fn unused_folder_id(
+    candidates: impl Iterator<Item = String>,
+    taken: impl Fn(&str) -> bool,
+) -> Option<String> {
+    candidates.take(FOLDER_ID_ATTEMPTS).find(|id| !taken(id))
+}
+
+/// Refuse an ID another device is offering, so adding a folder cannot
+/// silently join someone else's share instead of accepting it
+async fn check_not_offered(ctx: &Context, folder_id: &str) -> Result<(), SyncthingError> {
+    let pending = get_pending_folders(ctx).await?;
+    if let Some(offer) = pending.iter().find(|p| p.folder_id == folder_id) {
+        return Err(SyncthingError::conflict(
+            folder_path(folder_id),
+            format!(
+                "Folder `{folder_id}` is offered by device {}; accept the offer instead",
+                offer.offered_by
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// A folder started from Syncthing's folder defaults, as the web UI does
+///
+/// Falls back to built-in defaults on Syncthing versions without
+/// `/rest/config/defaults`.
+pub async fn new_folder(
+    ctx: &Context,
+    id: String,
+    label: String,
+    path: String,
+) -> Result<FolderConfiguration, SyncthingError> {
+    let defaults = match ctx.client.get("/rest/config/defaults/folder", &[]).await {
+        Ok(defaults) => Some(defaults),
+        Err(SyncthingError::NotFound { .. }) => None,
+        Err(e) => return Err(e),
+    };
+    Ok(FolderConfiguration::from_defaults(
+        defaults, id, label, path,
+    ))
+}
+
+/// All configured folders
+pub async fn list_folders(ctx: &Context) -> Result<Vec<FolderConfiguration>, SyncthingError> {
+    ctx.client.get("/rest/config/folders", &[]).await
+}
+
+/// Get folder status
+pub async fn get_folder_status(
+    ctx: &Context,
+    folder_id: &str,
+) -> Result<serde_json::Value, SyncthingError> {
+    ctx.client
+        .get("/rest/db/status", &[("folder", folder_id)])
+        .await
+}
+
+/// How much of `folder_id` `device_id` still needs
+///
+/// Served from the completion cache when an event or earlier query filled it.
+pub async fn get_folder_completion(
+    ctx: &Context,
+    folder_id: &str,
+    device_id: &str,
+) -> Result<Completion, SyncthingError> {
+    if let Some(completion) = ctx.completion.get(folder_id, device_id) {
+        return Ok(completion);
+    }
+    let completion: Completion = ctx
+        .client
+        .get(
+            "/rest/db/completion",
+            &[("folder", folder_id), ("device", device_id)],
+        )
+        .await?;
+    ctx.completion
+        .insert(folder_id, device_id, completion.clone());
+    Ok(completion)
+}
+
+/// Completion of every folder on every remote device it is shared with
+pub async fn get_completion_matrix(ctx: &Context) -> Result<CompletionMatrix, SyncthingError> {
+    let config: Configuration = ctx.client.get("/rest/config", &[]).await?;
+    let local_device = get_device_id(ctx).await?;
+
+    let mut folders = Vec::with_capacity(config.folders.len());
+    for folder in &config.folders {
+        let mut devices = Vec::new();
+        if !folder.paused {
+            for device in folder
+                .devices
+                .iter()
+                .filter(|d| d.device_id != local_device)
+            {
+                let completion = get_folder_completion(ctx, &folder.id, &device.device_id).await?;
+                devices.push(DeviceCompletion {
+                    device_id: device.device_id.clone(),
+                    completion,
+                });
+            }
+        }
+        folders.push(FolderCompletionRow {
+            folder_id: folder.id.clone(),
+            label: folder.label.clone(),
+            paused: folder.paused,
+            devices,
+        });
+    }
+
+    Ok(CompletionMatrix {
+        devices: config
+            .devices
+            .iter()
+            .filter(|d| d.device_id != local_device)
+            .map(|d| MatrixDevice {
+                device_id: d.device_id.clone(),
+                name: d.name.clone(),
+            })
+            .collect(),
+        folders,
+    })
+}
+
+/// Get detailed folder configuration
+pub async fn get_folder_config(
+    ctx: &Context,
+    folder_id: &str,
+) -> Result<FolderConfiguration, SyncthingError> {
+    ctx.client.get(&folder_path(folder_id), &[]).await
+}
+
+/// Pause a folder
+///
+/// Not snapshotted: resuming undoes it, and sync windows pause and resume at
+/// every boundary, which would push real changes out of the retained
+/// snapshots.
+pub async fn pause_folder(ctx: &Context, folder_id: &str) -> Result<(), SyncthingError> {
+    ctx.client
+        .patch(
+            &folder_path(folder_id),
+            &serde_json::json!({ "paused": true }),
+        )
+        .await
+}
+
+/// Resume a folder
+pub async fn resume_folder(ctx: &Context, folder_id: &str) -> Result<(), SyncthingError> {
+    ctx.client
+        .patch(
+            &folder_path(folder_id),
+            &serde_json::json!({ "paused": false }),
+        )
+        .await
+}
+
+/// Force rescan of a folder
+pub async fn rescan_folder(ctx: &Context, folder_id: &str) -> Result<(), SyncthingError> {
+    ctx.client
+        .post("/rest/db/scan", &[("folder", folder_id)])
+        .await
+}
+
+fn already_exists(folder_id: &str) -> SyncthingError {
+    SyncthingError::conflict(
+        folder_path(folder_id),
+        format!("Folder `{folder_id}` already exists"),
+    )
+}
+
+/// Check the path of a folder about to be added, without changing anything
+///
+/// Fails with `Conflict` if the folder already exists. Only runs when
+/// Syncthing is on this machine; a remote instance's paths are left unchecked.
+pub async fn check_folder_path(
+    ctx: &Context,
+    folder: &FolderConfiguration,
+) -> Result<PathCheck, SyncthingError> {
+    if !ctx.client.is_local() {
+        return Ok(PathCheck::unchecked(&folder.path));
+    }
+    let others = list_folders(ctx).await?;
+    if others.iter().any(|f| f.id == folder.id) {
+        return Err(already_exists(&folder.id));
+    }
+    preflight::check(&folder.path, &others)
+}
+
+/// Create `folder` at the path `check` found, unless its ID is taken;
+/// returns whether it was created
+///
+/// The path is saved as checked, with `~` expanded. On this machine the
+/// directory and its marker are made just before the write, so Syncthing can
+/// scan straight away, and removed again if the folder is not created.
+pub async fn create_checked_folder(
+    ctx: &Context,
+    folder: &FolderConfiguration,
+    check: &mut PathCheck,
+) -> Result<bool, SyncthingError> {
+    let folder = FolderConfiguration {
+        path: check.path.clone(),
+        ..folder.clone()
+    };
+    let created = if ctx.client.is_local() {
+        Some(preflight::create(check, &folder.marker_name)?)
+    } else {
+        None
+    };
+    let result = ctx.client.create(&folder_path(&folder.id), &folder).await;
+    if !matches!(result, Ok(true)) {
+        if let Some(created) = created {
+            created.undo();
+        }
+        check.created = false;
+    }
+    result
+}
+
+/// Add `folder`, refusing to replace an existing folder with the same ID
+///
+/// Also refuses an ID a pending offer uses. The path is pre-flighted first;
+/// returns what the check found.
+pub async fn add_folder(
+    ctx: &Context,
+    folder: &FolderConfiguration,
+) -> Result<PathCheck, SyncthingError> {
+    validate_folder_id(&folder.id)?;
+    folder.validate()?;
+    check_not_offered(ctx, &folder.id).await?;
+    let mut check = check_folder_path(ctx, folder).await?;
+    ctx.snapshot("add_folder").await?;
+    if create_checked_folder(ctx, folder, &mut check).await? {
+        Ok(check)
+    } else {
+        Err(already_exists(&folder.id))
+    }
+}
+
+/// Share a folder that is about to be added with `devices`, so it is created
+/// with a single write
+pub fn share_with_devices(
+    folder: &mut FolderConfiguration,
+    devices: &[FolderShare],
+) -> Result<(), SyncthingError> {
+    for (i, share) in devices.iter().enumerate() {
+        let device_id = normalize(&share.device_id).map_err(|e| match e {
+            SyncthingError::Validation { message, .. } => {
+                SyncthingError::validation(format!("devices[{i}].deviceId"), message)
+            },
+            other => other,
+        })?;
+        match share.encryption_password.as_deref() {
+            Some("") => {
+                return Err(SyncthingError::validation(
+                    format!("devices[{i}].encryptionPassword"),
+                    "cannot be empty",
+                ))
+            },
+            Some(password) => folder.share_encrypted_with(&device_id, password),
+            None => folder.share_with(&device_id),
+        };
+    }
+    folder.validate()
+}
+
+/// Remove a folder from Syncthing
+pub async fn remove_folder(ctx: &Context, folder_id: &str) -> Result<(), SyncthingError> {
+    ctx.snapshot("remove_folder").await?;
+    ctx.client.delete(&folder_path(folder_id), &[]).await
+}
+
+/// Update folder configuration
+pub async fn update_folder_config(
+    ctx: &Context,
+    folder_id: &str,
+    updates: &Extra,
+) -> Result<(), SyncthingError> {
+    ctx.snapshot("update_folder_config").await?;
+    let path = folder_path(folder_id);
+    let current: FolderConfiguration = ctx.client.get(&path, &[]).await?;
+    apply_update(&current, updates, "folder")?.validate()?;
+
+    // Only the validated keys are sent, so unrelated concurrent changes survive
+    ctx.client.patch(&path, updates).await
+}
+
+/// Share a folder with a specific device
+///
+/// With `encryption_password`, the device is treated as untrusted: it only
+/// ever receives data encrypted with that password.
+pub async fn share_folder(
+    ctx: &Context,
+    folder_id: &str,
+    device_id: &str,
+    encryption_password: Option<&str>,
+) -> Result<(), SyncthingError> {
+    if encryption_password == Some("") {
+        return Err(SyncthingError::validation(
+            "encryptionPassword",
+            "cannot be empty",
+        ));
+    }
+    let device_id = &normalize(device_id)?;
+    ctx.snapshot("share_folder").await?;
+    ctx.client
+        .modify(
+            &folder_path(folder_id),
+            |folder: &mut FolderConfiguration| {
+                let changed = match encryption_password {
+                    Some(password) => folder.share_encrypted_with(device_id, password),
+                    None => folder.share_with(device_id),
+                };
+                folder.validate()?;
+                Ok(changed)
+            },
+        )
+        .await
+        .map(drop)
+}
+
+/// Unshare a folder from a device
+pub async fn unshare_folder(
+    ctx: &Context,
+    folder_id: &str,
+    device_id: &str,
+) -> Result<(), SyncthingError> {
+    let device_id = &normalize(device_id)?;
+    ctx.snapshot("unshare_folder").await?;
+    ctx.client
+        .modify(
+            &folder_path(folder_id),
+            |folder: &mut FolderConfiguration| Ok(folder.unshare_with(device_id)),
+        )
+        .await
+        .map(drop)
+}
+
+/// Require `folder_id` to be one of `types` before running a type-specific action
+async fn require_folder_type(
+    ctx: &Context,
+    folder_id: &str,
+    types: &[&str],
+    action: &str,
+) -> Result<(), SyncthingError> {
+    let folder = get_folder_config(ctx, folder_id).await?;
+    if types.contains(&folder.folder_type.as_str()) {
+        Ok(())
+    } else {
+        Err(SyncthingError::validation(
+            "type",
+            format!(
+                "{action} needs a {} folder, `{folder_id}` is {}",
+                types.join(" or "),
+                folder.folder_type
+            ),
+        ))
+    }
+}
+
+/// Override Changes: make the cluster adopt this send-only folder's state
+///
+/// Remote modifications are overwritten and files the folder does not have
+/// are deleted on the other devices.
+pub async fn override_folder_changes(ctx: &Context, folder_id: &str) -> Result<(), SyncthingError> {
+    require_folder_type(ctx, folder_id, &["sendonly"], "Override").await?;
+    ctx.client
+        .post("/rest/db/override", &[("folder", folder_id)])
+        .await
+}
+
+/// Revert Local Changes: discard what was changed locally in a receive-only folder
+///
+/// Locally modified files are restored to the cluster's version and locally
+/// added files are removed.
+pub async fn revert_folder_changes(ctx: &Context, folder_id: &str) -> Result<(), SyncthingError> {
+    require_folder_type(
+        ctx,
+        folder_id,
+        &["receiveonly", "receiveencrypted"],
+        "Revert",
+    )
+    .await?;
+    ctx.client
+        .post("/rest/db/revert", &[("folder", folder_id)])
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE: &str = "MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD";
+    const OTHER_DEVICE: &str = "P56IOI7-MZJNU2Y-IQGDREY-DM2MGTI-MGL3BXN-PQ6W5BM-TBBZ4TJ-XZWICQ2";
+
+    fn folder() -> FolderConfiguration {
+        FolderConfiguration::new("abcde-12345".into(), "Photos".into(), "/data/photos".into())
+    }
+
+    fn share(device_id: &str, encryption_password: Option<&str>) -> FolderShare {
+        FolderShare {
+            device_id: device_id.into(),
+            encryption_password: encryption_password.map(str::to_string),
+        }
+    }
+
+    fn invalid_field(result: Result<(), SyncthingError>) -> String {
+        match result {
+            Err(SyncthingError::Validation { field, .. }) => field,
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn random_folder_ids_look_like_syncthings() {
+        for _ in 0..100 {
+            let id = random_folder_id();
+            let (first, second) = id.split_once('-').unwrap();
+            assert_eq!((first.len(), second.len()), (5, 5), "{id}");
+            assert!(first
+                .bytes()
+                .chain(second.bytes())
+                .all(|b| FOLDER_ID_CHARS.contains(&b)));
+            assert!(validate_folder_id(&id).is_ok());
+        }
+    }
+
+    #[test]
+    fn unused_folder_id_skips_taken_ids_and_gives_up() {
+        let candidates = ["taken-00001", "taken-00002", "fresh-00003"].map(String::from);
+        let found = unused_folder_id(candidates.into_iter(), |id| id.starts_with("taken"));
+        assert_eq!(found.as_deref(), Some("fresh-00003"));
+
+        let attempts = std::cell::Cell::new(0);
+        let always_taken = |_: &str| {
+            attempts.set(attempts.get() + 1);
+            true
+        };
+        let endless = std::iter::repeat_with(random_folder_id);
+        assert_eq!(unused_folder_id(endless, always_taken), None);
+        assert_eq!(attempts.get(), FOLDER_ID_ATTEMPTS);
+    }
+
+    #[test]
+    fn folder_ids_must_be_addressable() {
+        for valid in ["abcde-12345", "Photos 2024", "100%", "a.b", "ü?#"] {
+            assert!(validate_folder_id(valid).is_ok(), "{valid}");
+        }
+        for invalid in ["", " photos", "photos\n", "tab\there", "a/b", ".", ".."] {
+            assert_eq!(
+                invalid_field(validate_folder_id(invalid)),
+                "id",
+                "{invalid:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn new_folders_are_shared_with_normalized_ids_and_passwords() {
+        let mut folder = folder();
+        let devices = [
+            share(&DEVICE.to_lowercase().replace('-', ""), None),
+            share(OTHER_DEVICE, Some("secret")),
+        ];
+        share_with_devices(&mut folder, &devices).unwrap();
+
+        assert_eq!(folder.devices.len(), 2);
+        assert_eq!(folder.devices[0].device_id, DEVICE);
+        assert!(folder.devices[0].encryption_password.is_empty());
+        assert_eq!(folder.devices[1].device_id, OTHER_DEVICE);
+        assert_eq!(folder.devices[1].encryption_password, "secret");
+    }
+
+    #[test]
+    fn sharing_errors_name_the_device_entry() {
+        let devices = [share(DEVICE, None), share("not-a-device", None)];
+        let field = invalid_field(share_with_devices(&mut folder(), &devices));
+        assert_eq!(field, "devices[1].deviceId");
+
+        let devices = [share(DEVICE, Some(""))];
+        let field = invalid_field(share_with_devices(&mut folder(), &devices));
+        assert_eq!(field, "devices[0].encryptionPassword");
+
+        let mut encrypted = folder();
+        encrypted.folder_type = "receiveencrypted".into();
+        let devices = [share(DEVICE, Some("secret"))];
+        let field = invalid_field(share_with_devices(&mut encrypted, &devices));
+        assert_eq!(field, "encryptionPassword");
+    }
+}
@@ -0,0 +1,263 @@
+//! Device invitations.
+//!
+//! An invitation is this device's ID, its name and optionally some folders
+//! to share, whether to accept it as an introducer and when it expires,
+//! packed into an `eigen://invite?...` URI short enough for a QR code.
+//! Decoding also accepts a bare device ID, which is what Syncthing's own QR
+//! codes contain, and `id` for `device` as older invite links wrote it.
+
+use super::config::get_config;
+use super::folders::validate_folder_id;
+use super::system::get_device_id;
+use super::Context;
+use crate::device_id::DeviceId;
+use crate::SyncthingError;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCHEME: &str = "eigen";
+const HOST: &str = "invite";
+
+/// A folder the inviting device suggests sharing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderOffer {
+    pub folder_id: String,
+    pub label: String,
+}
+
+/// What an invitation carries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Invite {
+    /// Canonical device ID, ready for `add_device`
+    pub device_id: String,
+    /// Suggested name for the device
+    pub name: Option<String>,
+    pub folders: Vec<FolderOffer>,
+    /// Suggest adding the device as an introducer
+    #[serde(default)]
+    pub introducer: bool,
+    /// Milliseconds since the Unix epoch after which the invite is refused
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+/// How an invitation should be created
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InviteOptions {
+    pub introducer: bool,
+    /// Hours until the invite expires; never without
+    pub expires_in_hours: Option<u32>,
+}
+
+impl Invite {
+    /// Encode as an `eigen://invite` URI
+    ///
+    /// Each `folder` parameter may be followed by a `label` for it, which is
+    /// left out when it equals the folder ID.
+    pub fn to_payload(&self) -> String {
+        let mut url = Url::parse(&format!("{SCHEME}://{HOST}")).expect("static URL is valid");
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("device", &self.device_id);
+            if let Some(name) = self.name.as_deref().filter(|n| !n.is_empty()) {
+                query.append_pair("name", name);
+            }
+            for folder in &self.folders {
+                query.append_pair("folder", &folder.folder_id);
+                if !folder.label.is_empty() && folder.label != folder.folder_id {
+                    query.append_pair("label", &folder.label);
+                }
+            }
+            if self.introducer {
+                query.append_pair("introducer", "1");
+            }
+            if let Some(expires_at) = self.expires_at {
+                query.append_pair("exp", &expires_at.to_string());
+            }
+        }
+        url.into()
+    }
+
+    /// Decode an invite URI or a bare device ID, validating every ID in it
+    /// and refusing expired invites
+    pub fn parse(payload: &str) -> Result<Self, SyncthingError> {
+        let invalid = |message: &str| SyncthingError::validation("payload", message);
+        let payload = payload.trim();
+
+        if !payload
+            .to_ascii_lowercase()
+            .starts_with(&format!("{SCHEME}:"))
+        {
+            return Ok(Self {
+                device_id: DeviceId::parse(payload)?.to_string(),
+                name: None,
+                folders: Vec::new(),
+                introducer: false,
+                expires_at: None,
+            });
+        }
+
+        let url = Url::parse(payload).map_err(|_| invalid("is not a valid invite URI"))?;
+        if url.host_str() != Some(HOST) {
+            return Err(invalid("is not an eigen://invite URI"));
+        }
+        let mut device_id = None;
+        let mut name = None;
+        let mut folders: Vec<FolderOffer> = Vec::new();
+        let mut introducer = false;
+        let mut expires_at = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "device" | "id" => device_id = Some(DeviceId::parse(&value)?.to_string()),
+                "name" => name = Some(value.into_owned()).filter(|n| !n.is_empty()),
+                "folder" => {
+                    validate_folder_id(&value)?;
+                    folders.push(FolderOffer {
+                        folder_id: value.to_string(),
+                        label: value.into_owned(),
+                    });
+                },
+                "label" => {
+                    let folder = folders
+                        .last_mut()
+                        .ok_or_else(|| invalid("has a label before any folder"))?;
+                    folder.label = value.into_owned();
+                },
+                "introducer" => introducer = matches!(value.as_ref(), "1" | "true"),
+                "exp" => {
+                    expires_at = Some(
+                        value
+                            .parse()
+                            .map_err(|_| invalid("has an invalid expiry time"))?,
+                    );
+                },
+                // Left for newer versions of the format
+                _ => {},
+            }
+        }
+
+        if expires_at.is_some_and(|at| at <= now_millis()) {
+            return Err(invalid("has expired"));
+        }
+        Ok(Self {
+            device_id: device_id.ok_or_else(|| invalid("has no device ID"))?,
+            name,
+            folders,
+            introducer,
+            expires_at,
+        })
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// An invitation for this device and its encoded form
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceInvite {
+    /// What goes into the QR code
+    pub payload: String,
+    #[serde(flatten)]
+    pub invite: Invite,
+}
+
+/// Invite other devices to this one, suggesting `folder_ids` to share
+pub async fn create_invite(
+    ctx: &Context,
+    folder_ids: &[String],
+    options: &InviteOptions,
+) -> Result<DeviceInvite, SyncthingError> {
+    let device_id = get_device_id(ctx).await?;
+    let config = get_config(ctx).await?;
+
+    let folders = folder_ids
+        .iter()
+        .map(|id| {
+            let folder = config
+                .folder(id)
+                .ok_or_else(|| SyncthingError::FolderNotFound(id.clone()))?;
+            Ok(FolderOffer {
+                folder_id: folder.id.clone(),
+                label: folder.label.clone(),
+            })
+        })
+        .collect::<Result<_, SyncthingError>>()?;
+    let invite = Invite {
+        name: config
+            .device(&device_id)
+            .map(|d| d.name.clone())
+            .filter(|n| !n.is_empty()),
+        device_id,
+        folders,
+        introducer: options.introducer,
+        expires_at: options
+            .expires_in_hours
+            .map(|hours| now_millis().saturating_add(u64::from(hours) * 60 * 60 * 1000)),
+    };
+
+    Ok(DeviceInvite {
+        payload: invite.to_payload(),
+        invite,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE: &str = "MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD";
+
+    #[test]
+    fn payload_round_trips() {
+        let invite = Invite {
+            device_id: DEVICE.into(),
+            name: Some("laptop & co".into()),
+            folders: vec![
+                FolderOffer {
+                    folder_id: "photos".into(),
+                    label: "Photos".into(),
+                },
+                FolderOffer {
+                    folder_id: "docs".into(),
+                    label: "docs".into(),
+                },
+            ],
+            introducer: true,
+            expires_at: Some(now_millis() + 60_000),
+        };
+        let parsed = Invite::parse(&invite.to_payload()).unwrap();
+
+        assert_eq!(parsed.device_id, DEVICE);
+        assert_eq!(parsed.name.as_deref(), Some("laptop & co"));
+        let labels: Vec<_> = parsed.folders.iter().map(|f| f.label.as_str()).collect();
+        assert_eq!(labels, ["Photos", "docs"]);
+        assert!(parsed.introducer);
+        assert_eq!(parsed.expires_at, invite.expires_at);
+    }
+
+    #[test]
+    fn accepts_id_alias_and_bare_ids() {
+        let compact = DEVICE.replace('-', "").to_lowercase();
+        let parsed = Invite::parse(&format!("eigen://invite?id={compact}&name=nas")).unwrap();
+        assert_eq!(parsed.device_id, DEVICE);
+        assert!(!parsed.introducer);
+        assert_eq!(Invite::parse(&compact).unwrap().device_id, DEVICE);
+    }
+
+    #[test]
+    fn refuses_expired_invites() {
+        let result = Invite::parse(&format!("eigen://invite?device={DEVICE}&exp=1"));
+        assert!(matches!(
+            result,
+            Err(SyncthingError::Validation { message, .. }) if message == "has expired"
+        ));
+    }
+}
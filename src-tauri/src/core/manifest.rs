@@ -0,0 +1,59 @@
+//! Declarative manifest export and apply.
+
+use super::config::get_config;
+use super::system::get_device_id;
+use super::Context;
+use crate::manifest::{Manifest, ManifestFormat};
+use crate::models::Configuration;
+use crate::transaction::{self, ConfigDiff};
+use crate::SyncthingError;
+
+/// Export the folders, devices and sharing of the live config as a manifest document
+pub async fn export_config_manifest(
+    ctx: &Context,
+    format: ManifestFormat,
+) -> Result<String, SyncthingError> {
+    let local = get_device_id(ctx).await?;
+    let config = get_config(ctx).await?;
+
+    Manifest::export(&config, &local).to_document(format)
+}
+
+/// Compute what applying a manifest would change, without applying it
+pub async fn preview_config_manifest(
+    ctx: &Context,
+    manifest: &Manifest,
+    prune: bool,
+) -> Result<ConfigDiff, SyncthingError> {
+    let local = get_device_id(ctx).await?;
+    let before = get_config(ctx).await?;
+
+    let mut after = before.clone();
+    transaction::apply(&mut after, &manifest.plan(&before, &local, prune))?;
+    Ok(transaction::diff(&before, &after))
+}
+
+/// Converge the live config on a manifest in a single validated write
+///
+/// With `prune`, folders and devices the manifest does not list are removed.
+/// Returns the applied diff.
+pub async fn apply_config_manifest(
+    ctx: &Context,
+    manifest: &Manifest,
+    prune: bool,
+) -> Result<ConfigDiff, SyncthingError> {
+    let local = get_device_id(ctx).await?;
+    ctx.snapshot("apply_config_manifest").await?;
+
+    let mut applied = ConfigDiff::default();
+    ctx.client
+        .modify("/rest/config", |config: &mut Configuration| {
+            let before = config.clone();
+            transaction::apply(config, &manifest.plan(&before, &local, prune))?;
+            applied = transaction::diff(&before, config);
+            Ok(!applied.is_empty())
+        })
+        .await?;
+
+    Ok(applied)
+}
@@ -0,0 +1,77 @@
+//! Tauri-independent implementation of the commands.
+//!
+//! Every operation takes a [`Context`] instead of Tauri's managed state, so the
+//! same logic backs both the desktop app's `commands/*` and the `eigen-cli`
+//! binary. Nothing in here may depend on Tauri.
+//!
+//! This module is organized like `commands`:
+//! - `system`: Ping, status, restart
+//! - `config`: Configuration, options, connections, transactions
+//! - `bandwidth`: Applying the bandwidth schedule
+//! - `folders`: Folder management operations
+//! - `db`: Out-of-sync items and per-file details
+//! - `devices`: Device management operations
+//! - `files`: File browser, conflicts, versions, ignores
+//! - `events`: Events and logs
+//! - `invite`: Device invitations for QR codes
+//! - `pending`: Pending device/folder requests
+//! - `manifest`: Declarative TOML/YAML export and apply
+//! - `snapshots`: Config snapshot list, diff and restore
+//! - `stats`: Device and folder statistics, device overview
+//! - `sync_windows`: Applying sync windows and reporting their state
+
+pub mod bandwidth;
+pub mod config;
+pub mod db;
+pub mod devices;
+pub mod events;
+pub mod files;
+pub mod folders;
+pub mod invite;
+pub mod manifest;
+pub mod pending;
+pub mod snapshots;
+pub mod stats;
+pub mod sync_windows;
+pub mod system;
+
+use crate::client::SyncthingClient;
+use crate::completion::CompletionCache;
+use crate::models::Configuration;
+use crate::snapshots::SnapshotStore;
+use crate::{SyncthingConfig, SyncthingError};
+
+/// The Syncthing instance an operation runs against
+#[derive(Debug, Clone)]
+pub struct Context {
+    pub client: SyncthingClient,
+    /// Where config snapshots are kept
+    pub snapshots: SnapshotStore,
+    /// Profile the snapshots are filed under
+    pub profile: String,
+    /// Last known folder completion per device
+    pub completion: CompletionCache,
+}
+
+impl Context {
+    /// Context for the instance described by `config`
+    pub fn new(
+        config: &SyncthingConfig,
+        snapshots: SnapshotStore,
+        profile: impl Into<String>,
+    ) -> Result<Self, SyncthingError> {
+        Ok(Self {
+            client: SyncthingClient::new(config)?,
+            snapshots,
+            profile: profile.into(),
+            completion: CompletionCache::default(),
+        })
+    }
+
+    /// Snapshot the live configuration before changing it
+    pub async fn snapshot(&self, reason: &str) -> Result<(), SyncthingError> {
+        let config: Configuration = self.client.get("/rest/config", &[]).await?;
+        self.snapshots.save(&self.profile, reason, config)?;
+        Ok(())
+    }
+}
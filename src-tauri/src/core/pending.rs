@@ -0,0 +1,241 @@
+//! Pending device and folder requests.
+//!
+//! Incoming connection requests from other devices and folder share requests
+//! that haven't been accepted yet.
+
+use super::devices::new_device;
+use super::folders::{check_folder_path, create_checked_folder, new_folder, validate_folder_id};
+use super::Context;
+use crate::client::path_segment;
+use crate::device_id::{normalize, DeviceId};
+use crate::models::FolderConfiguration;
+use crate::preflight::PathCheck;
+use crate::SyncthingError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Information about a pending device connection request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingDevice {
+    /// The device ID requesting connection
+    pub device_id: String,
+    /// Device name (if known)
+    pub name: Option<String>,
+    /// Address the device connected from
+    pub address: Option<String>,
+    /// When the request was received
+    pub time: Option<String>,
+}
+
+/// Information about a pending folder share request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingFolder {
+    /// The folder ID being shared
+    pub folder_id: String,
+    /// Folder label (if provided)
+    pub folder_label: Option<String>,
+    /// Device ID that wants to share this folder
+    pub offered_by: String,
+    /// Device name (if known)
+    pub offered_by_name: Option<String>,
+    /// When the request was received
+    pub time: Option<String>,
+    /// Whether encryption is required
+    pub receive_encrypted: bool,
+    /// Remote encryption password if set
+    pub remote_encrypted: bool,
+}
+
+/// Response containing all pending requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingRequests {
+    pub devices: Vec<PendingDevice>,
+    pub folders: Vec<PendingFolder>,
+}
+
+/// Get all pending device connection requests
+pub async fn get_pending_devices(ctx: &Context) -> Result<Vec<PendingDevice>, SyncthingError> {
+    // The API returns a map of deviceID -> device info
+    let json: HashMap<String, serde_json::Value> =
+        ctx.client.get("/rest/cluster/pending/devices", &[]).await?;
+
+    let devices: Vec<PendingDevice> = json
+        .into_iter()
+        .map(|(device_id, info)| PendingDevice {
+            device_id,
+            name: info.get("name").and_then(|v| v.as_str()).map(String::from),
+            address: info
+                .get("address")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            time: info.get("time").and_then(|v| v.as_str()).map(String::from),
+        })
+        .collect();
+
+    Ok(devices)
+}
+
+/// Get all pending folder share requests
+pub async fn get_pending_folders(ctx: &Context) -> Result<Vec<PendingFolder>, SyncthingError> {
+    // The API returns: { folderID: { offeredBy: { deviceID: { time, label, ... } } } }
+    let json: HashMap<String, serde_json::Value> =
+        ctx.client.get("/rest/cluster/pending/folders", &[]).await?;
+
+    let mut folders: Vec<PendingFolder> = Vec::new();
+
+    for (folder_id, folder_info) in json {
+        // Get the offeredBy map which contains device_id -> offer info
+        if let Some(offered_by) = folder_info.get("offeredBy").and_then(|v| v.as_object()) {
+            for (device_id, offer_info) in offered_by {
+                folders.push(PendingFolder {
+                    folder_id: folder_id.clone(),
+                    folder_label: offer_info
+                        .get("label")
+                        .and_then(|v| v.as_str())
+                        .map(String::from),
+                    offered_by: device_id.clone(),
+                    offered_by_name: None, // We'll need to look this up from config if needed
+                    time: offer_info
+                        .get("time")
+                        .and_then(|v| v.as_str())
+                        .map(String::from),
+                    receive_encrypted: offer_info
+                        .get("receiveEncrypted")
+                        .and_then(serde_json::Value::as_bool)
+                        .unwrap_or(false),
+                    remote_encrypted: offer_info
+                        .get("remoteEncrypted")
+                        .and_then(serde_json::Value::as_bool)
+                        .unwrap_or(false),
+                });
+            }
+        }
+    }
+
+    Ok(folders)
+}
+
+/// Get all pending requests (devices and folders) in one call
+pub async fn get_pending_requests(ctx: &Context) -> Result<PendingRequests, SyncthingError> {
+    let devices = get_pending_devices(ctx).await?;
+    let folders = get_pending_folders(ctx).await?;
+
+    Ok(PendingRequests { devices, folders })
+}
+
+/// Accept a pending device connection request
+/// This adds the device to our config
+pub async fn accept_pending_device(
+    ctx: &Context,
+    device_id: &str,
+    name: Option<String>,
+) -> Result<(), SyncthingError> {
+    let device_id = DeviceId::parse(device_id)?;
+    ctx.snapshot("accept_pending_device").await?;
+    let device_name = name.unwrap_or_else(|| format!("Device {}", device_id.short()));
+    let device = new_device(ctx, device_id.as_str(), device_name).await?;
+    let path = format!("/rest/config/devices/{}", path_segment(device_id.as_str()));
+
+    if !ctx.client.create(&path, &device).await? {
+        // Device already exists, just remove from pending
+        return dismiss_pending_device(ctx, device_id.as_str()).await;
+    }
+    Ok(())
+}
+
+/// Dismiss/reject a pending device request
+/// This removes the device from the pending list without adding it
+pub async fn dismiss_pending_device(ctx: &Context, device_id: &str) -> Result<(), SyncthingError> {
+    let device_id = normalize(device_id)?;
+    ctx.client
+        .delete("/rest/cluster/pending/devices", &[("device", &device_id)])
+        .await
+}
+
+/// Accept a pending folder share request
+/// This adds the folder to our config with the specified path
+///
+/// `folder_type` only applies when the folder is new. With
+/// `encryption_password`, the offering device is treated as untrusted.
+/// Returns the pre-flight check of the path, or `None` if the folder already
+/// existed and was only shared with the device.
+pub async fn accept_pending_folder(
+    ctx: &Context,
+    folder_id: &str,
+    device_id: &str,
+    folder_path: String,
+    folder_label: Option<String>,
+    folder_type: Option<String>,
+    encryption_password: Option<&str>,
+) -> Result<Option<PathCheck>, SyncthingError> {
+    validate_folder_id(folder_id)?;
+    let device_id = &normalize(device_id)?;
+    if encryption_password == Some("") {
+        return Err(SyncthingError::validation(
+            "encryptionPassword",
+            "cannot be empty",
+        ));
+    }
+    ctx.snapshot("accept_pending_folder").await?;
+    let client = &ctx.client;
+    let path = format!("/rest/config/folders/{}", path_segment(folder_id));
+    let share = |folder: &mut FolderConfiguration| match encryption_password {
+        Some(password) => folder.share_encrypted_with(device_id, password),
+        None => folder.share_with(device_id),
+    };
+
+    // Create a new folder shared with this device...
+    let label = folder_label.unwrap_or_else(|| folder_id.to_string());
+    let mut folder = new_folder(ctx, folder_id.to_string(), label, folder_path).await?;
+    if let Some(kind) = folder_type {
+        folder.folder_type = kind;
+    }
+    share(&mut folder);
+    folder.validate()?;
+    let mut check = match check_folder_path(ctx, &folder).await {
+        Ok(check) => Some(check),
+        Err(SyncthingError::Conflict { .. }) => None,
+        Err(e) => return Err(e),
+    };
+    let created = match check.as_mut() {
+        Some(check) => create_checked_folder(ctx, &folder, check).await?,
+        None => false,
+    };
+
+    // ...or, if it already exists, just add the device to it
+    let check = if created {
+        check
+    } else {
+        client
+            .modify(&path, |folder: &mut FolderConfiguration| {
+                let changed = share(folder);
+                folder.validate()?;
+                Ok(changed)
+            })
+            .await?;
+        None
+    };
+
+    // Remove from pending
+    dismiss_pending_folder(ctx, folder_id, device_id).await?;
+
+    Ok(check)
+}
+
+/// Dismiss/reject a pending folder share request
+pub async fn dismiss_pending_folder(
+    ctx: &Context,
+    folder_id: &str,
+    device_id: &str,
+) -> Result<(), SyncthingError> {
+    let device_id = normalize(device_id)?;
+    ctx.client
+        .delete(
+            "/rest/cluster/pending/folders",
+            &[("folder", folder_id), ("device", &device_id)],
+        )
+        .await
+}
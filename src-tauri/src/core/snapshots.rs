@@ -0,0 +1,50 @@
+//! Config snapshot list, diff and restore.
+
+use super::config::get_config;
+use super::Context;
+use crate::models::Configuration;
+use crate::snapshots::SnapshotInfo;
+use crate::transaction::{self, ConfigDiff};
+use crate::SyncthingError;
+
+/// Config snapshots of the context's profile, newest first
+pub fn list_config_snapshots(ctx: &Context) -> Vec<SnapshotInfo> {
+    ctx.snapshots.list(&ctx.profile)
+}
+
+/// What changed in the live config since snapshot `id` was taken
+pub async fn diff_config_snapshot(ctx: &Context, id: &str) -> Result<ConfigDiff, SyncthingError> {
+    let snapshot = ctx.snapshots.load(&ctx.profile, id)?;
+    let live = get_config(ctx).await?;
+
+    Ok(transaction::diff(&snapshot, &live))
+}
+
+/// Put the folders, devices and options of snapshot `id` back in place
+///
+/// Only what [`transaction::diff`] compares is restored; GUI settings and
+/// defaults stay as they are, and snapshots do not keep the GUI credentials
+/// anyway. The current config is snapshotted first, so a restore can itself
+/// be undone. Returns the changes the restore made.
+pub async fn restore_config_snapshot(
+    ctx: &Context,
+    id: &str,
+) -> Result<ConfigDiff, SyncthingError> {
+    let snapshot = ctx.snapshots.load(&ctx.profile, id)?;
+    ctx.snapshot("restore_config_snapshot").await?;
+
+    let mut restored = ConfigDiff::default();
+    ctx.client
+        .modify("/rest/config", |config: &mut Configuration| {
+            restored = transaction::diff(config, &snapshot);
+            if !restored.is_empty() {
+                config.folders.clone_from(&snapshot.folders);
+                config.devices.clone_from(&snapshot.devices);
+                config.options = snapshot.options.clone();
+            }
+            Ok(!restored.is_empty())
+        })
+        .await?;
+
+    Ok(restored)
+}
@@ -0,0 +1,241 @@
+//! Device and folder statistics.
+//!
+//! `/rest/stats/device` and `/rest/stats/folder` only know IDs and timestamps;
+//! these views merge them with the configured names, and the device overview
+//! adds what `/rest/system/connections` says about each device right now.
+
+use super::config::get_config;
+use super::system::get_device_id;
+use super::Context;
+use crate::SyncthingError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// When a device was last connected
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceStats {
+    pub device_id: String,
+    pub name: String,
+    /// RFC 3339; `None` if the device has never connected
+    pub last_seen: Option<String>,
+    /// How long the last connection lasted, in seconds
+    pub last_connection_duration_s: f64,
+}
+
+/// The file a folder last synced
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LastFile {
+    /// RFC 3339
+    pub at: String,
+    pub filename: String,
+    pub deleted: bool,
+}
+
+/// When a folder was last scanned and what it last synced
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderStats {
+    pub folder_id: String,
+    pub label: String,
+    /// RFC 3339; `None` if the folder was never scanned
+    pub last_scan: Option<String>,
+    /// `None` if nothing was synced yet
+    pub last_file: Option<LastFile>,
+}
+
+/// How a device is connected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionType {
+    Tcp,
+    Quic,
+    Relay,
+    /// A transport this version does not know about
+    Other,
+}
+
+impl ConnectionType {
+    /// Classify Syncthing's `tcp-client`, `quic-server`, `relay-client`, ...
+    fn from_syncthing(kind: &str) -> Option<Self> {
+        match kind.split('-').next()? {
+            "" => None,
+            "tcp" => Some(Self::Tcp),
+            "quic" => Some(Self::Quic),
+            "relay" => Some(Self::Relay),
+            _ => Some(Self::Other),
+        }
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp",
+            Self::Quic => "quic",
+            Self::Relay => "relay",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// A folder, as listed in the device overview
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedFolder {
+    pub folder_id: String,
+    pub label: String,
+}
+
+/// Everything about a remote device at a glance
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceOverview {
+    pub device_id: String,
+    pub name: String,
+    pub paused: bool,
+    pub connected: bool,
+    /// Remote address of the current connection
+    pub address: Option<String>,
+    /// `None` while disconnected
+    pub connection_type: Option<ConnectionType>,
+    /// e.g. `v1.27.0`; `None` while disconnected
+    pub client_version: Option<String>,
+    /// RFC 3339; `None` if the device has never connected
+    pub last_seen: Option<String>,
+    pub last_connection_duration_s: f64,
+    /// Folders shared with the device
+    pub folders: Vec<SharedFolder>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct RawDeviceStats {
+    last_seen: String,
+    last_connection_duration_s: f64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct RawFolderStats {
+    last_scan: String,
+    last_file: Option<LastFile>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RawConnections {
+    connections: HashMap<String, RawConnection>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct RawConnection {
+    connected: bool,
+    address: String,
+    #[serde(rename = "type")]
+    kind: String,
+    client_version: String,
+}
+
+/// `None` for Syncthing's zero timestamps, which mean "never"
+fn timestamp(at: String) -> Option<String> {
+    let never = at.is_empty() || at.starts_with("0001-") || at.starts_with("1970-");
+    (!never).then_some(at)
+}
+
+async fn get_raw_device_stats(
+    ctx: &Context,
+) -> Result<HashMap<String, RawDeviceStats>, SyncthingError> {
+    ctx.client.get("/rest/stats/device", &[]).await
+}
+
+/// Last-seen statistics of every configured device, including this one
+pub async fn get_device_stats(ctx: &Context) -> Result<Vec<DeviceStats>, SyncthingError> {
+    let config = get_config(ctx).await?;
+    let mut stats = get_raw_device_stats(ctx).await?;
+
+    Ok(config
+        .devices
+        .into_iter()
+        .map(|device| {
+            let raw = stats.remove(&device.device_id).unwrap_or_default();
+            DeviceStats {
+                device_id: device.device_id,
+                name: device.name,
+                last_seen: timestamp(raw.last_seen),
+                last_connection_duration_s: raw.last_connection_duration_s,
+            }
+        })
+        .collect())
+}
+
+/// Last scan and last synced file of every configured folder
+pub async fn get_folder_stats(ctx: &Context) -> Result<Vec<FolderStats>, SyncthingError> {
+    let config = get_config(ctx).await?;
+    let mut stats: HashMap<String, RawFolderStats> =
+        ctx.client.get("/rest/stats/folder", &[]).await?;
+
+    Ok(config
+        .folders
+        .into_iter()
+        .map(|folder| {
+            let raw = stats.remove(&folder.id).unwrap_or_default();
+            FolderStats {
+                folder_id: folder.id,
+                label: folder.label,
+                last_scan: timestamp(raw.last_scan),
+                last_file: raw.last_file.filter(|file| !file.filename.is_empty()),
+            }
+        })
+        .collect())
+}
+
+/// Connection state, statistics and shared folders of every remote device
+pub async fn get_device_overview(ctx: &Context) -> Result<Vec<DeviceOverview>, SyncthingError> {
+    let local_device = get_device_id(ctx).await?;
+    let config = get_config(ctx).await?;
+    let mut stats = get_raw_device_stats(ctx).await?;
+    let mut connections: RawConnections = ctx.client.get("/rest/system/connections", &[]).await?;
+
+    Ok(config
+        .devices
+        .iter()
+        .filter(|device| device.device_id != local_device)
+        .map(|device| {
+            let raw = stats.remove(&device.device_id).unwrap_or_default();
+            let connection = connections
+                .connections
+                .remove(&device.device_id)
+                .filter(|c| c.connected);
+            let folders = config
+                .folders
+                .iter()
+                .filter(|f| f.devices.iter().any(|d| d.device_id == device.device_id))
+                .map(|f| SharedFolder {
+                    folder_id: f.id.clone(),
+                    label: f.label.clone(),
+                })
+                .collect();
+
+            DeviceOverview {
+                device_id: device.device_id.clone(),
+                name: device.name.clone(),
+                paused: device.paused,
+                connected: connection.is_some(),
+                address: connection
+                    .as_ref()
+                    .map(|c| c.address.clone())
+                    .filter(|a| !a.is_empty()),
+                connection_type: connection
+                    .as_ref()
+                    .and_then(|c| ConnectionType::from_syncthing(&c.kind)),
+                client_version: connection
+                    .map(|c| c.client_version)
+                    .filter(|v| !v.is_empty()),
+                last_seen: timestamp(raw.last_seen),
+                last_connection_duration_s: raw.last_connection_duration_s,
+                folders,
+            }
+        })
+        .collect())
+}
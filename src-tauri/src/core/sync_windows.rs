@@ -0,0 +1,385 @@
+//! Applying sync windows and reporting their state.
+
+use super::config::get_config;
+use super::devices::{pause_device, resume_device};
+use super::folders::{pause_folder, resume_folder};
+use super::Context;
+use crate::models::Configuration;
+use crate::schedule::{epoch_millis_in, WeekMinute};
+use crate::sync_windows::{PauseReason, SyncWindows, Target, TargetKind};
+use crate::SyncthingError;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A folder or device under sync windows, right now
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncWindowState {
+    #[serde(flatten)]
+    pub target: Target,
+    /// Folder label or device name; `None` if it is not configured
+    pub name: Option<String>,
+    /// Whether one of its windows is open
+    pub open: bool,
+    pub paused: bool,
+    /// `None` while not paused
+    pub pause_reason: Option<PauseReason>,
+    /// When a window next opens or closes, in milliseconds since the Unix epoch
+    pub next_change: Option<u64>,
+}
+
+/// Name and paused state of `target` in `config`
+fn lookup(config: &Configuration, target: &Target) -> Option<(String, bool)> {
+    match target.kind {
+        TargetKind::Folder => config
+            .folder(&target.id)
+            .map(|f| (f.label.clone(), f.paused)),
+        TargetKind::Device => config
+            .device(&target.id)
+            .map(|d| (d.name.clone(), d.paused)),
+    }
+}
+
+async fn set_paused(ctx: &Context, target: &Target, paused: bool) -> Result<(), SyncthingError> {
+    match (target.kind, paused) {
+        (TargetKind::Folder, true) => pause_folder(ctx, &target.id).await,
+        (TargetKind::Folder, false) => resume_folder(ctx, &target.id).await,
+        (TargetKind::Device, true) => pause_device(ctx, &target.id).await,
+        (TargetKind::Device, false) => resume_device(ctx, &target.id).await,
+    }
+}
+
+/// Pause and resume folders and devices as their windows close and open
+///
+/// `scheduled` holds the targets paused by the scheduler and is updated in
+/// place; only those are ever resumed. See [`plan_pause_changes`] for when
+/// each target is paused or resumed. Keeps going past failures and returns
+/// the first one.
+pub async fn apply_sync_windows(
+    ctx: &Context,
+    windows: &SyncWindows,
+    at: WeekMinute,
+    scheduled: &mut BTreeSet<Target>,
+    last_open: &mut BTreeMap<Target, bool>,
+) -> Result<(), SyncthingError> {
+    let config = get_config(ctx).await?;
+    let mut result = Ok(());
+
+    for change in plan_pause_changes(&config, windows, at, scheduled, last_open) {
+        let outcome = set_paused(ctx, &change.target, change.pause).await;
+        record_pause_change(&change, outcome.is_ok(), scheduled, last_open);
+        if let (Err(e), Ok(())) = (outcome, &result) {
+            result = Err(e);
+        }
+    }
+    result
+}
+
+/// A pause or resume the scheduler decided on
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PauseChange {
+    target: Target,
+    /// Pause, or else resume
+    pause: bool,
+}
+
+/// Decide which targets to pause and resume at `at`
+///
+/// A target is paused when its window closes, which is whenever `last_open`
+/// has not seen it closed yet, so one resumed by hand while closed stays
+/// running until the window next closes. Only targets in `scheduled` are
+/// resumed; one the user paused or resumed meanwhile is dropped from it.
+/// Targets no longer configured are forgotten.
+fn plan_pause_changes(
+    config: &Configuration,
+    windows: &SyncWindows,
+    at: WeekMinute,
+    scheduled: &mut BTreeSet<Target>,
+    last_open: &mut BTreeMap<Target, bool>,
+) -> Vec<PauseChange> {
+    let targets: BTreeSet<Target> = windows.targets().chain(scheduled.iter().cloned()).collect();
+    let mut changes = Vec::new();
+
+    for target in targets {
+        let Some((_, paused)) = lookup(config, &target) else {
+            // Removed from the config; nothing left to resume
+            scheduled.remove(&target);
+            last_open.remove(&target);
+            continue;
+        };
+        let open = windows.is_open(&target, at);
+        let was_open = last_open.insert(target.clone(), open);
+
+        if scheduled.contains(&target) {
+            if open && paused {
+                changes.push(PauseChange {
+                    target,
+                    pause: false,
+                });
+            } else if !paused {
+                // Resumed by hand while closed
+                scheduled.remove(&target);
+            }
+        } else if !open && !paused && was_open != Some(false) {
+            changes.push(PauseChange {
+                target,
+                pause: true,
+            });
+        }
+        // Otherwise running inside its window, or paused by hand
+    }
+    changes
+}
+
+/// Update `scheduled` after `change` was tried; a failed one is tried again
+/// next time
+fn record_pause_change(
+    change: &PauseChange,
+    succeeded: bool,
+    scheduled: &mut BTreeSet<Target>,
+    last_open: &mut BTreeMap<Target, bool>,
+) {
+    match (succeeded, change.pause) {
+        (true, true) => {
+            scheduled.insert(change.target.clone());
+        },
+        (true, false) => {
+            scheduled.remove(&change.target);
+        },
+        (false, _) => {
+            last_open.remove(&change.target);
+        },
+    }
+}
+
+/// Every folder and device under sync windows, plus any the scheduler still
+/// holds paused (`scheduled`)
+pub async fn get_sync_window_state(
+    ctx: &Context,
+    windows: &SyncWindows,
+    scheduled: &BTreeSet<Target>,
+) -> Result<Vec<SyncWindowState>, SyncthingError> {
+    let config = get_config(ctx).await?;
+    let at = WeekMinute::now();
+    let targets: BTreeSet<Target> = windows.targets().chain(scheduled.iter().cloned()).collect();
+
+    Ok(targets
+        .into_iter()
+        .map(|target| {
+            let (name, paused) = lookup(&config, &target).unzip();
+            let paused = paused.unwrap_or_default();
+            SyncWindowState {
+                name,
+                open: windows.is_open(&target, at),
+                paused,
+                pause_reason: paused.then(|| {
+                    if scheduled.contains(&target) {
+                        PauseReason::Scheduled
+                    } else {
+                        PauseReason::Manual
+                    }
+                }),
+                next_change: windows
+                    .minutes_until_change(&target, at)
+                    .map(epoch_millis_in),
+                target,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FolderConfiguration;
+    use crate::schedule::{TimeOfDay, Weekday, WeeklyRange};
+
+    fn at(hhmm: &str) -> WeekMinute {
+        WeekMinute::new(Weekday::Mon, TimeOfDay::try_from(hhmm.to_string()).unwrap())
+    }
+
+    /// `photos` may only sync from 09:00 to 17:00
+    fn office_hours() -> SyncWindows {
+        let range = WeeklyRange {
+            days: BTreeSet::new(),
+            start: TimeOfDay::try_from("09:00".to_string()).unwrap(),
+            end: TimeOfDay::try_from("17:00".to_string()).unwrap(),
+        };
+        SyncWindows {
+            enabled: true,
+            folders: BTreeMap::from([("photos".to_string(), vec![range])]),
+            devices: BTreeMap::new(),
+        }
+    }
+
+    fn config(paused: bool) -> Configuration {
+        let mut folder = FolderConfiguration::new(
+            "photos".to_string(),
+            "Photos".to_string(),
+            "/photos".to_string(),
+        );
+        folder.paused = paused;
+        Configuration {
+            folders: vec![folder],
+            ..Configuration::default()
+        }
+    }
+
+    fn change(pause: bool) -> PauseChange {
+        PauseChange {
+            target: Target::folder("photos"),
+            pause,
+        }
+    }
+
+    #[test]
+    fn paused_by_hand_is_never_resumed() {
+        let windows = office_hours();
+        let (mut scheduled, mut last_open) = (BTreeSet::new(), BTreeMap::new());
+
+        for time in ["18:00", "10:00", "18:00", "10:00"] {
+            let changes = plan_pause_changes(
+                &config(true),
+                &windows,
+                at(time),
+                &mut scheduled,
+                &mut last_open,
+            );
+            assert_eq!(changes, vec![], "at {time}");
+        }
+        assert!(scheduled.is_empty());
+    }
+
+    #[test]
+    fn resumed_by_hand_while_closed_is_unscheduled() {
+        let windows = office_hours();
+        let mut scheduled = BTreeSet::from([Target::folder("photos")]);
+        let mut last_open = BTreeMap::from([(Target::folder("photos"), false)]);
+
+        let changes = plan_pause_changes(
+            &config(false),
+            &windows,
+            at("18:00"),
+            &mut scheduled,
+            &mut last_open,
+        );
+        assert_eq!(changes, vec![]);
+        assert!(scheduled.is_empty());
+
+        // Stays running until the window next closes
+        let changes = plan_pause_changes(
+            &config(false),
+            &windows,
+            at("19:00"),
+            &mut scheduled,
+            &mut last_open,
+        );
+        assert_eq!(changes, vec![]);
+        let changes = plan_pause_changes(
+            &config(false),
+            &windows,
+            at("10:00"),
+            &mut scheduled,
+            &mut last_open,
+        );
+        assert_eq!(changes, vec![]);
+        let changes = plan_pause_changes(
+            &config(false),
+            &windows,
+            at("18:00"),
+            &mut scheduled,
+            &mut last_open,
+        );
+        assert_eq!(changes, vec![change(true)]);
+    }
+
+    #[test]
+    fn window_open_close_open_pauses_then_resumes() {
+        let windows = office_hours();
+        let (mut scheduled, mut last_open) = (BTreeSet::new(), BTreeMap::new());
+
+        let changes = plan_pause_changes(
+            &config(false),
+            &windows,
+            at("10:00"),
+            &mut scheduled,
+            &mut last_open,
+        );
+        assert_eq!(changes, vec![]);
+
+        let changes = plan_pause_changes(
+            &config(false),
+            &windows,
+            at("17:00"),
+            &mut scheduled,
+            &mut last_open,
+        );
+        assert_eq!(changes, vec![change(true)]);
+        record_pause_change(&changes[0], true, &mut scheduled, &mut last_open);
+        assert!(scheduled.contains(&Target::folder("photos")));
+
+        let changes = plan_pause_changes(
+            &config(true),
+            &windows,
+            at("18:00"),
+            &mut scheduled,
+            &mut last_open,
+        );
+        assert_eq!(changes, vec![]);
+
+        let changes = plan_pause_changes(
+            &config(true),
+            &windows,
+            at("09:00"),
+            &mut scheduled,
+            &mut last_open,
+        );
+        assert_eq!(changes, vec![change(false)]);
+        record_pause_change(&changes[0], true, &mut scheduled, &mut last_open);
+        assert!(scheduled.is_empty());
+    }
+
+    #[test]
+    fn failed_pause_is_tried_again() {
+        let windows = office_hours();
+        let (mut scheduled, mut last_open) = (BTreeSet::new(), BTreeMap::new());
+
+        let changes = plan_pause_changes(
+            &config(false),
+            &windows,
+            at("18:00"),
+            &mut scheduled,
+            &mut last_open,
+        );
+        assert_eq!(changes, vec![change(true)]);
+        record_pause_change(&changes[0], false, &mut scheduled, &mut last_open);
+        assert!(scheduled.is_empty());
+
+        let changes = plan_pause_changes(
+            &config(false),
+            &windows,
+            at("18:01"),
+            &mut scheduled,
+            &mut last_open,
+        );
+        assert_eq!(changes, vec![change(true)]);
+    }
+
+    #[test]
+    fn removed_targets_are_forgotten() {
+        let windows = office_hours();
+        let mut scheduled = BTreeSet::from([Target::folder("photos")]);
+        let mut last_open = BTreeMap::from([(Target::folder("photos"), false)]);
+
+        let changes = plan_pause_changes(
+            &Configuration::default(),
+            &windows,
+            at("10:00"),
+            &mut scheduled,
+            &mut last_open,
+        );
+        assert_eq!(changes, vec![]);
+        assert!(scheduled.is_empty());
+        assert!(last_open.is_empty());
+    }
+}
@@ -0,0 +1,35 @@
+//! Ping, status and restart.
+
+use super::Context;
+use crate::SyncthingError;
+use reqwest::Method;
+
+/// Ping Syncthing to check if it's responding
+pub async fn ping_syncthing(ctx: &Context) -> Result<serde_json::Value, SyncthingError> {
+    let request = ctx
+        .client
+        .request(Method::GET, "/rest/system/ping")
+        .timeout(std::time::Duration::from_secs(5));
+
+    ctx.client.execute_json(request).await
+}
+
+/// Get Syncthing system status
+pub async fn get_system_status(ctx: &Context) -> Result<serde_json::Value, SyncthingError> {
+    ctx.client.get("/rest/system/status", &[]).await
+}
+
+/// ID of the device `ctx` talks to
+pub async fn get_device_id(ctx: &Context) -> Result<String, SyncthingError> {
+    let json = get_system_status(ctx).await?;
+
+    json["myID"]
+        .as_str()
+        .map(std::string::ToString::to_string)
+        .ok_or_else(|| SyncthingError::ParseError("No device ID found".into()))
+}
+
+/// Restart Syncthing
+pub async fn restart_syncthing(ctx: &Context) -> Result<(), SyncthingError> {
+    ctx.client.post("/rest/system/restart", &[]).await
+}
@@ -0,0 +1,229 @@
+//! Syncthing device IDs.
+//!
+//! A device ID is the SHA-256 of the device certificate in base32, split into
+//! four 13-character groups that each get a Luhn mod-32 check character, and
+//! written as eight dash-separated groups of seven
+//! (`MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD`).
+//!
+//! Input is accepted in any form Syncthing itself accepts: with or without
+//! dashes and spaces, in lowercase, with `0`/`1`/`8` typed for `O`/`I`/`B`,
+//! and in the old 52-character form without check characters (optionally
+//! padded to 56 with `=`). Parsing always yields the canonical form.
+
+use crate::SyncthingError;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+/// Base32 characters of the certificate hash
+const BARE_LEN: usize = 52;
+/// Characters per group that gets a check character
+const GROUP_LEN: usize = 13;
+/// Base32 characters plus one check character per group
+const CHECKED_LEN: usize = BARE_LEN + BARE_LEN / GROUP_LEN;
+/// Characters per dash-separated chunk of the canonical form
+const CHUNK_LEN: usize = 7;
+
+/// A validated device ID in canonical form
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DeviceId(String);
+
+impl DeviceId {
+    /// Normalize and validate `input`
+    pub fn parse(input: &str) -> Result<Self, SyncthingError> {
+        let invalid = |message: String| SyncthingError::validation("deviceID", message);
+
+        let cleaned: String = input
+            .trim()
+            .trim_matches('=')
+            .chars()
+            .filter(|c| !matches!(c, '-' | ' '))
+            .map(|c| match c.to_ascii_uppercase() {
+                '0' => 'O',
+                '1' => 'I',
+                '8' => 'B',
+                c => c,
+            })
+            .collect();
+        if cleaned.is_empty() {
+            return Err(invalid("must not be empty".into()));
+        }
+        if let Some(bad) = cleaned
+            .chars()
+            .find(|c| !ALPHABET.iter().any(|&a| char::from(a) == *c))
+        {
+            return Err(invalid(format!(
+                "`{bad}` is not a base32 character (A-Z, 2-7)"
+            )));
+        }
+
+        let bare = match cleaned.len() {
+            CHECKED_LEN => strip_check_characters(&cleaned).map_err(invalid)?,
+            BARE_LEN => cleaned,
+            len => {
+                return Err(invalid(format!(
+                    "must be {CHECKED_LEN} characters ({BARE_LEN} without check \
+                     characters), not {len}"
+                )))
+            },
+        };
+        Ok(Self(canonical(&bare)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The first group, which Syncthing shows as the short ID
+    pub fn short(&self) -> &str {
+        &self.0[..CHUNK_LEN]
+    }
+}
+
+impl FromStr for DeviceId {
+    type Err = SyncthingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for DeviceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for DeviceId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// Normalize `input` to its canonical form
+pub fn normalize(input: &str) -> Result<String, SyncthingError> {
+    DeviceId::parse(input).map(|id| id.0)
+}
+
+/// Verify and drop the check character at the end of each group
+fn strip_check_characters(checked: &str) -> Result<String, String> {
+    let mut bare = String::with_capacity(BARE_LEN);
+    for (i, group) in checked.as_bytes().chunks(GROUP_LEN + 1).enumerate() {
+        let (data, check) = group.split_at(GROUP_LEN);
+        let expected = luhn_base32(data);
+        if check[0] != expected {
+            return Err(format!(
+                "check character {} is `{}` but should be `{}`; the ID has a typo",
+                i + 1,
+                char::from(check[0]),
+                char::from(expected)
+            ));
+        }
+        bare.push_str(std::str::from_utf8(data).unwrap_or_default());
+    }
+    Ok(bare)
+}
+
+/// Insert the check characters and dashes into a bare 52-character ID
+fn canonical(bare: &str) -> String {
+    let mut checked = Vec::with_capacity(CHECKED_LEN);
+    for group in bare.as_bytes().chunks(GROUP_LEN) {
+        checked.extend_from_slice(group);
+        checked.push(luhn_base32(group));
+    }
+    checked
+        .chunks(CHUNK_LEN)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Syncthing's Luhn mod-32 check character over base32 `data`
+fn luhn_base32(data: &[u8]) -> u8 {
+    const N: usize = ALPHABET.len();
+    let mut factor = 1;
+    let mut sum = 0;
+    for c in data {
+        let codepoint = ALPHABET.iter().position(|a| a == c).unwrap_or(0);
+        let addend = factor * codepoint;
+        factor = if factor == 2 { 1 } else { 2 };
+        sum += addend / N + addend % N;
+    }
+    ALPHABET[(N - sum % N) % N]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// From Syncthing's documentation
+    const CANONICAL: &str = "MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD";
+
+    fn error_message(input: &str) -> String {
+        match DeviceId::parse(input) {
+            Err(SyncthingError::Validation { field, message }) => {
+                assert_eq!(field, "deviceID");
+                message
+            },
+            other => panic!("`{input}` should be rejected, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_canonical_id() {
+        let id = DeviceId::parse(CANONICAL).unwrap();
+        assert_eq!(id.as_str(), CANONICAL);
+        assert_eq!(id.short(), "MFZWI3D");
+    }
+
+    #[test]
+    fn expands_56_characters_to_canonical_63() {
+        let compact = CANONICAL.replace('-', "");
+        assert_eq!(compact.len(), 56);
+        assert_eq!(normalize(&compact).unwrap().len(), 63);
+        assert_eq!(normalize(&compact).unwrap(), CANONICAL);
+        assert_eq!(normalize(&compact.to_lowercase()).unwrap(), CANONICAL);
+    }
+
+    #[test]
+    fn accepts_legacy_and_mistyped_forms() {
+        // 52 characters without check characters, padded like old releases did
+        let bare = "MFZWI3DBONSGYYLTMRWGC43ENRQXGZDMMFZWI3DBONSGYYLTMRWA====";
+        assert_eq!(normalize(bare).unwrap(), CANONICAL);
+        let mistyped = CANONICAL
+            .replace('O', "0")
+            .replace('I', "1")
+            .replace('B', "8");
+        assert_eq!(normalize(&mistyped).unwrap(), CANONICAL);
+        assert_eq!(
+            normalize(&format!(" {} ", CANONICAL.replace('-', " "))).unwrap(),
+            CANONICAL
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_check_character() {
+        // The 14th character checks the first group; `C` is correct
+        let typo = CANONICAL.replacen("BONSGYC", "BONSGYD", 1);
+        let message = error_message(&typo);
+        assert!(message.contains("check character 1"), "{message}");
+        assert!(message.contains("should be `C`"), "{message}");
+    }
+
+    #[test]
+    fn rejects_characters_outside_base32() {
+        let message = error_message(&CANONICAL.replacen('M', "9", 1));
+        assert!(
+            message.contains("`9` is not a base32 character"),
+            "{message}"
+        );
+        error_message(&CANONICAL.replacen('M', "!", 1));
+    }
+
+    #[test]
+    fn rejects_wrong_length_and_empty_input() {
+        assert!(error_message(&CANONICAL[..CANONICAL.len() - 1]).contains("not 55"));
+        assert!(error_message("  ").contains("must not be empty"));
+    }
+}
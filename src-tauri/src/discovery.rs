@@ -0,0 +1,376 @@
+//! Discovery of the local Syncthing instance from its `config.xml`.
+//!
+//! Syncthing's home directory is resolved the same way Syncthing itself does
+//! it (`--home`/`STHOMEDIR`, `STCONFDIR`, then the platform defaults including
+//! `$XDG_STATE_HOME`), and the `<gui>` element is parsed for the listen
+//! address, TLS flag, API key and authentication settings. The result records
+//! which file was used and why anything is missing, so a GUI moved to another
+//! port or a unix socket is explained instead of silently failing.
+
+use crate::tls;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Syncthing's built-in GUI address
+pub const DEFAULT_GUI_ADDRESS: &str = "127.0.0.1:8384";
+
+const CONFIG_FILE: &str = "config.xml";
+
+/// Settings read from the `<gui>` element of `config.xml`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_excessive_bools)]
+pub struct GuiSettings {
+    pub enabled: bool,
+    /// Raw `<address>` value
+    pub address: String,
+    /// Host to connect to (wildcard binds are mapped to loopback)
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    /// Path of the unix socket, if the GUI listens on one instead of TCP
+    pub unix_socket: Option<String>,
+    #[serde(skip)]
+    pub api_key: Option<String>,
+    pub has_api_key: bool,
+    pub has_user: bool,
+    pub has_password: bool,
+}
+
+/// Outcome of looking for the local Syncthing configuration
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Discovery {
+    /// Home directory set with `--home` or `STHOMEDIR`, which a launched
+    /// Syncthing must be given too
+    pub home: Option<PathBuf>,
+    /// The `config.xml` that was parsed, if any
+    pub config_path: Option<PathBuf>,
+    /// SHA-256 fingerprint of the GUI certificate (`https-cert.pem`) next to it
+    pub cert_fingerprint: Option<String>,
+    /// Every location that was checked, in order
+    pub searched: Vec<PathBuf>,
+    pub gui: Option<GuiSettings>,
+    /// Problems a user may need to act on
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct XmlConfiguration {
+    gui: Option<XmlGui>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct XmlGui {
+    #[serde(rename = "@enabled")]
+    enabled: Option<bool>,
+    #[serde(rename = "@tls")]
+    tls: bool,
+    address: Option<String>,
+    user: Option<String>,
+    password: Option<String>,
+    apikey: Option<String>,
+}
+
+/// Find and parse the local Syncthing `config.xml`
+///
+/// `home` overrides Syncthing's home directory like its own `--home` flag;
+/// without it `STHOMEDIR` is honoured.
+pub fn discover(home: Option<&Path>) -> Discovery {
+    let mut discovery = Discovery {
+        home: home_override(home),
+        ..Discovery::default()
+    };
+
+    for dir in candidate_dirs(discovery.home.as_deref()) {
+        let path = dir.join(CONFIG_FILE);
+        discovery.searched.push(path.clone());
+
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        match parse_gui(&content) {
+            Ok(gui) => {
+                if gui.unix_socket.is_some() {
+                    discovery.warnings.push(format!(
+                        "The Syncthing GUI listens on unix socket `{}`, which Eigen cannot \
+                         connect to; set a TCP <address> in {}",
+                        gui.address,
+                        path.display()
+                    ));
+                }
+                if !gui.enabled {
+                    discovery.warnings.push(format!(
+                        "The Syncthing GUI/REST API is disabled in {}",
+                        path.display()
+                    ));
+                }
+                if !gui.has_api_key {
+                    discovery
+                        .warnings
+                        .push(format!("No <apikey> is set in {}", path.display()));
+                }
+                discovery.gui = Some(gui);
+            },
+            Err(e) => discovery
+                .warnings
+                .push(format!("Could not parse {}: {e}", path.display())),
+        }
+        read_cert_fingerprint(&dir, &mut discovery);
+        discovery.config_path = Some(path);
+        break;
+    }
+
+    if discovery.config_path.is_none() {
+        discovery.warnings.push(format!(
+            "No Syncthing config.xml found; assuming the default GUI address {DEFAULT_GUI_ADDRESS}"
+        ));
+    }
+
+    discovery
+}
+
+/// Record the fingerprint of the GUI certificate in `dir`, needed to pin it when TLS is on
+fn read_cert_fingerprint(dir: &Path, discovery: &mut Discovery) {
+    match tls::fingerprint_from_pem_file(&dir.join(tls::GUI_CERT_FILE)) {
+        Ok(fingerprint) => discovery.cert_fingerprint = Some(fingerprint),
+        Err(e) => {
+            if discovery.gui.as_ref().is_some_and(|gui| gui.tls) {
+                discovery.warnings.push(format!(
+                    "GUI TLS is enabled but its certificate could not be pinned: {e}"
+                ));
+            }
+        },
+    }
+}
+
+/// Parse the `<gui>` element out of a `config.xml` document
+pub fn parse_gui(xml: &str) -> Result<GuiSettings, String> {
+    let config: XmlConfiguration = quick_xml::de::from_str(xml).map_err(|e| e.to_string())?;
+    let gui = config.gui.unwrap_or_default();
+
+    let address = gui
+        .address
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .unwrap_or_else(|| DEFAULT_GUI_ADDRESS.to_string());
+    let api_key = gui
+        .apikey
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty());
+
+    let unix_socket = unix_socket_path(&address);
+    let (host, port) = if unix_socket.is_some() {
+        (String::new(), 0)
+    } else {
+        split_address(&address)?
+    };
+
+    Ok(GuiSettings {
+        enabled: gui.enabled.unwrap_or(true),
+        host,
+        port,
+        tls: gui.tls,
+        unix_socket,
+        has_api_key: api_key.is_some(),
+        api_key,
+        has_user: gui.user.is_some_and(|u| !u.trim().is_empty()),
+        has_password: gui.password.is_some_and(|p| !p.trim().is_empty()),
+        address,
+    })
+}
+
+/// Syncthing treats `unix://` URLs and absolute paths as unix socket addresses
+fn unix_socket_path(address: &str) -> Option<String> {
+    address
+        .strip_prefix("unix://")
+        .or_else(|| address.strip_prefix("unixs://"))
+        .map(str::to_string)
+        .or_else(|| address.starts_with('/').then(|| address.to_string()))
+}
+
+/// Split `host:port` (with optional scheme and `[ipv6]`) into a connectable host and port
+fn split_address(address: &str) -> Result<(String, u16), String> {
+    let without_scheme = address
+        .split_once("://")
+        .map_or(address, |(_, rest)| rest)
+        .trim_end_matches('/');
+
+    let (host, port) = without_scheme
+        .rsplit_once(':')
+        .ok_or_else(|| format!("GUI address `{address}` has no port"))?;
+    let port: u16 = port
+        .parse()
+        .map_err(|_| format!("GUI address `{address}` has an invalid port"))?;
+
+    // A wildcard bind is reachable on loopback
+    let host = match host {
+        "" | "0.0.0.0" => "127.0.0.1".to_string(),
+        "[::]" => "[::1]".to_string(),
+        other => other.to_string(),
+    };
+    Ok((host, port))
+}
+
+/// `home`, or else `STHOMEDIR`
+fn home_override(home: Option<&Path>) -> Option<PathBuf> {
+    home.map(Path::to_path_buf)
+        .or_else(|| non_empty_env("STHOMEDIR").map(PathBuf::from))
+}
+
+/// Directories that may hold `config.xml`, most specific first, given the
+/// resolved [`home_override`]
+fn candidate_dirs(home: Option<&Path>) -> Vec<PathBuf> {
+    // Explicit overrides win and are not combined with the defaults
+    if let Some(home) = home {
+        return vec![home.to_path_buf()];
+    }
+    if let Some(dir) = non_empty_env("STCONFDIR") {
+        return vec![PathBuf::from(dir)];
+    }
+
+    let mut dirs = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(local_app_data) = non_empty_env("LOCALAPPDATA") {
+            dirs.push(Path::new(&local_app_data).join("Syncthing"));
+        }
+        if let Some(user_profile) = non_empty_env("USERPROFILE") {
+            dirs.push(Path::new(&user_profile).join("AppData\\Local\\Syncthing"));
+        }
+        // Some older installations use the roaming profile
+        if let Some(app_data) = non_empty_env("APPDATA") {
+            dirs.push(Path::new(&app_data).join("Syncthing"));
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        if let Some(home) = non_empty_env("HOME") {
+            dirs.push(Path::new(&home).join("Library/Application Support/Syncthing"));
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        let home = non_empty_env("HOME");
+        if let Some(state_home) = non_empty_env("XDG_STATE_HOME") {
+            dirs.push(Path::new(&state_home).join("syncthing"));
+        } else if let Some(home) = &home {
+            dirs.push(Path::new(home).join(".local/state/syncthing"));
+        }
+        // Pre-1.27 installs keep everything in the config dir
+        if let Some(config_home) = non_empty_env("XDG_CONFIG_HOME") {
+            dirs.push(Path::new(&config_home).join("syncthing"));
+        } else if let Some(home) = &home {
+            dirs.push(Path::new(home).join(".config/syncthing"));
+        }
+    }
+
+    dirs
+}
+
+/// The value of a `--home <dir>` / `--home=<dir>` option (or the single-dash
+/// spelling Syncthing also accepts) among `args`
+pub fn home_arg(args: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--home" || arg == "-home" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(dir) = arg
+            .strip_prefix("--home=")
+            .or_else(|| arg.strip_prefix("-home="))
+        {
+            return Some(PathBuf::from(dir));
+        }
+    }
+    None
+}
+
+fn non_empty_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    fn gui(gui: &str) -> GuiSettings {
+        parse_gui(&format!("<configuration>{gui}</configuration>")).unwrap()
+    }
+
+    #[test]
+    fn wildcard_binds_are_reached_on_loopback() {
+        let ipv4 = gui("<gui><address>0.0.0.0:8384</address></gui>");
+        assert_eq!((ipv4.host.as_str(), ipv4.port), ("127.0.0.1", 8384));
+
+        let ipv6 = gui("<gui><address>[::]:8385</address></gui>");
+        assert_eq!((ipv6.host.as_str(), ipv6.port), ("[::1]", 8385));
+
+        let ipv6_host = gui("<gui><address>[fd00::2]:8384</address></gui>");
+        assert_eq!(ipv6_host.host, "[fd00::2]");
+    }
+
+    #[test]
+    fn unix_socket_addresses_have_no_host() {
+        let url = gui("<gui><address>unix:///run/syncthing.sock</address></gui>");
+        assert_eq!(url.unix_socket.as_deref(), Some("/run/syncthing.sock"));
+        assert_eq!((url.host.as_str(), url.port), ("", 0));
+
+        let path = gui("<gui><address>/run/syncthing.sock</address></gui>");
+        assert_eq!(path.unix_socket.as_deref(), Some("/run/syncthing.sock"));
+    }
+
+    #[test]
+    fn address_without_port_is_an_error() {
+        let xml = "<configuration><gui><address>nas.lan</address></gui></configuration>";
+        assert!(parse_gui(xml).unwrap_err().contains("no port"));
+    }
+
+    #[test]
+    fn gui_settings_are_read_from_attributes_and_elements() {
+        let settings = gui(r#"<gui enabled="true" tls="true">
+                <address>127.0.0.1:8384</address>
+                <user>admin</user>
+                <apikey> abc123 </apikey>
+            </gui>"#);
+        assert!(settings.enabled && settings.tls);
+        assert_eq!(settings.api_key.as_deref(), Some("abc123"));
+        assert!(settings.has_user && !settings.has_password);
+    }
+
+    #[test]
+    fn missing_gui_means_syncthing_defaults() {
+        let settings = gui("");
+        assert!(settings.enabled && !settings.tls && !settings.has_api_key);
+        assert_eq!(settings.address, DEFAULT_GUI_ADDRESS);
+        assert_eq!((settings.host.as_str(), settings.port), ("127.0.0.1", 8384));
+    }
+
+    #[test]
+    fn home_arg_accepts_syncthing_spellings() {
+        for given in [
+            args(&["--home", "/srv/st"]),
+            args(&["--home=/srv/st"]),
+            args(&["-home", "/srv/st"]),
+            args(&["-home=/srv/st"]),
+        ] {
+            assert_eq!(home_arg(given), Some(PathBuf::from("/srv/st")));
+        }
+    }
+
+    #[test]
+    fn home_arg_ignores_positional_home() {
+        let given = args(&["folder", "add", "--id", "home", "/srv/data"]);
+        assert_eq!(home_arg(given), None);
+        assert_eq!(home_arg(args(&["home=/srv/data"])), None);
+    }
+}
@@ -0,0 +1,343 @@
+//! Errors returned by every command.
+//!
+//! Each variant serializes to an object with a stable `code` that matches the
+//! frontend's `AppError` codes in `src/lib/errors.ts`, plus the HTTP status,
+//! endpoint, field or path where one applies, so the UI can react to what
+//! actually happened instead of parsing messages.
+
+use crate::client::decode_path_segment;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum SyncthingError {
+    /// Nothing is listening at the configured GUI address
+    NotRunning {
+        endpoint: String,
+    },
+    /// The API key was rejected (401/403)
+    Unauthorized {
+        status: u16,
+        endpoint: String,
+    },
+    /// The endpoint or resource does not exist (404)
+    NotFound {
+        endpoint: String,
+        message: String,
+    },
+    FolderNotFound(String),
+    DeviceNotFound(String),
+    /// The change clashes with existing state, e.g. a duplicate folder ID
+    /// (409, or found before anything was sent)
+    Conflict {
+        endpoint: String,
+        message: String,
+    },
+    /// Any other non-success response from Syncthing
+    Api {
+        status: u16,
+        endpoint: String,
+        message: String,
+    },
+    /// A command argument was rejected before anything was sent
+    Validation {
+        field: String,
+        message: String,
+    },
+    Timeout {
+        endpoint: String,
+    },
+    Filesystem {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// Transport-level failure talking to Syncthing
+    HttpError(String),
+    ParseError(String),
+    ProcessError(String),
+}
+
+impl SyncthingError {
+    pub fn validation(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Validation {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn conflict(endpoint: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Conflict {
+            endpoint: endpoint.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn filesystem(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        Self::Filesystem {
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Classify a non-success response from `endpoint`
+    pub fn from_status(status: u16, endpoint: &str, message: String) -> Self {
+        let endpoint = endpoint.to_string();
+        match status {
+            401 | 403 => Self::Unauthorized { status, endpoint },
+            404 => {
+                let id = |prefix: &str| {
+                    endpoint
+                        .strip_prefix(prefix)
+                        .filter(|id| !id.is_empty() && !id.contains('/'))
+                        .map(decode_path_segment)
+                };
+                let folder = id("/rest/config/folders/").map(Self::FolderNotFound);
+                let device = id("/rest/config/devices/").map(Self::DeviceNotFound);
+                folder
+                    .or(device)
+                    .unwrap_or(Self::NotFound { endpoint, message })
+            },
+            409 => Self::Conflict { endpoint, message },
+            _ => Self::Api {
+                status,
+                endpoint,
+                message,
+            },
+        }
+    }
+
+    /// Stable machine-readable code, shared with `src/lib/errors.ts`
+    pub fn code(&self) -> String {
+        match self {
+            Self::NotRunning { .. } => "SYNCTHING_NOT_RUNNING".into(),
+            Self::Unauthorized { .. } => "SYNCTHING_API_KEY_INVALID".into(),
+            Self::NotFound { .. } => "API_ERROR_404".into(),
+            Self::FolderNotFound(_) => "SYNCTHING_FOLDER_NOT_FOUND".into(),
+            Self::DeviceNotFound(_) => "SYNCTHING_DEVICE_NOT_FOUND".into(),
+            Self::Conflict { .. } => "CONFLICT".into(),
+            Self::Api { status, .. } => format!("API_ERROR_{status}"),
+            Self::Validation { .. } => "VALIDATION_ERROR".into(),
+            Self::Timeout { .. } => "SYNCTHING_TIMEOUT".into(),
+            Self::Filesystem { .. } => "FILESYSTEM_ERROR".into(),
+            Self::HttpError(_) => "NETWORK_ERROR".into(),
+            Self::ParseError(_) => "PARSE_ERROR".into(),
+            Self::ProcessError(_) => "PROCESS_ERROR".into(),
+        }
+    }
+
+    /// HTTP status Syncthing answered with, if the request got that far
+    pub const fn status(&self) -> Option<u16> {
+        match self {
+            Self::Unauthorized { status, .. } | Self::Api { status, .. } => Some(*status),
+            Self::NotFound { .. } | Self::FolderNotFound(_) | Self::DeviceNotFound(_) => Some(404),
+            Self::Conflict { .. } => Some(409),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SyncthingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotRunning { endpoint } => {
+                write!(f, "Syncthing is not running (no answer from {endpoint})")
+            },
+            Self::Unauthorized { status, endpoint } => {
+                write!(f, "API key rejected by {endpoint} ({status})")
+            },
+            Self::NotFound { endpoint, message } if message.is_empty() => {
+                write!(f, "{endpoint} not found")
+            },
+            Self::NotFound { endpoint, message } => write!(f, "{endpoint} not found: {message}"),
+            Self::FolderNotFound(id) => write!(f, "Folder `{id}` not found"),
+            Self::DeviceNotFound(id) => write!(f, "Device `{id}` not found"),
+            Self::Conflict { message, .. } => write!(f, "Conflict: {message}"),
+            Self::Api {
+                status,
+                endpoint,
+                message,
+            } if message.is_empty() => write!(f, "{endpoint} returned {status}"),
+            Self::Api {
+                status,
+                endpoint,
+                message,
+            } => write!(f, "{endpoint} returned {status}: {message}"),
+            Self::Validation { field, message } => write!(f, "Invalid {field}: {message}"),
+            Self::Timeout { endpoint } => write!(f, "Request to {endpoint} timed out"),
+            Self::Filesystem { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::HttpError(e) => write!(f, "HTTP error: {e}"),
+            Self::ParseError(e) => write!(f, "Parse error: {e}"),
+            Self::ProcessError(e) => write!(f, "Process error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SyncthingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Filesystem { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for SyncthingError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("SyncthingError", 6)?;
+        s.serialize_field("code", &self.code())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("status", &self.status())?;
+
+        let endpoint = match self {
+            Self::NotRunning { endpoint }
+            | Self::Unauthorized { endpoint, .. }
+            | Self::NotFound { endpoint, .. }
+            | Self::Conflict { endpoint, .. }
+            | Self::Api { endpoint, .. }
+            | Self::Timeout { endpoint } => Some(endpoint.as_str()),
+            _ => None,
+        };
+        s.serialize_field("endpoint", &endpoint)?;
+
+        let field = match self {
+            Self::Validation { field, .. } => Some(field.as_str()),
+            _ => None,
+        };
+        s.serialize_field("field", &field)?;
+
+        let path = match self {
+            Self::Filesystem { path, .. } => Some(path),
+            _ => None,
+        };
+        s.serialize_field("path", &path)?;
+        s.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The frontend's side of the contract
+    const ERRORS_TS: &str = include_str!("../../src/lib/errors.ts");
+
+    /// The TypeScript declaration starting with `start`, up to `end`
+    fn declaration(start: &str, end: &str) -> &'static str {
+        let from = ERRORS_TS.find(start).expect("declaration not found");
+        let rest = &ERRORS_TS[from + start.len()..];
+        &rest[..rest.find(end).expect("declaration not closed")]
+    }
+
+    /// String literals of the `BackendErrorCode` union
+    fn backend_codes() -> Vec<&'static str> {
+        declaration("export type BackendErrorCode =", ";")
+            .split('|')
+            .filter_map(|member| member.trim().strip_prefix('\'')?.strip_suffix('\''))
+            .collect()
+    }
+
+    /// One error of every kind
+    fn every_variant() -> Vec<SyncthingError> {
+        let errors = vec![
+            SyncthingError::NotRunning {
+                endpoint: "http://127.0.0.1:8384".into(),
+            },
+            SyncthingError::from_status(403, "/rest/config", String::new()),
+            SyncthingError::from_status(404, "/rest/db/file", "no such file".into()),
+            SyncthingError::from_status(404, "/rest/config/folders/abcd-1234", String::new()),
+            SyncthingError::from_status(404, "/rest/config/devices/MFZWI3D", String::new()),
+            SyncthingError::from_status(409, "/rest/config/folders/abcd-1234", String::new()),
+            SyncthingError::from_status(500, "/rest/system/status", "boom".into()),
+            SyncthingError::validation("path", "must not be empty"),
+            SyncthingError::Timeout {
+                endpoint: "/rest/events".into(),
+            },
+            SyncthingError::filesystem("/data", std::io::ErrorKind::NotFound.into()),
+            SyncthingError::HttpError("connection reset".into()),
+            SyncthingError::ParseError("expected value".into()),
+            SyncthingError::ProcessError("sidecar exited".into()),
+        ];
+        // Fails to compile when a variant is added, so it gets a sample here
+        for error in &errors {
+            match error {
+                SyncthingError::NotRunning { .. }
+                | SyncthingError::Unauthorized { .. }
+                | SyncthingError::NotFound { .. }
+                | SyncthingError::FolderNotFound(_)
+                | SyncthingError::DeviceNotFound(_)
+                | SyncthingError::Conflict { .. }
+                | SyncthingError::Api { .. }
+                | SyncthingError::Validation { .. }
+                | SyncthingError::Timeout { .. }
+                | SyncthingError::Filesystem { .. }
+                | SyncthingError::HttpError(_)
+                | SyncthingError::ParseError(_)
+                | SyncthingError::ProcessError(_) => {},
+            }
+        }
+        errors
+    }
+
+    #[test]
+    fn codes_match_the_frontend_union() {
+        let known = backend_codes();
+        assert!(
+            declaration("export type BackendErrorCode =", ";").contains("`API_ERROR_${number}`")
+        );
+
+        let produced: Vec<String> = every_variant().iter().map(SyncthingError::code).collect();
+        for code in &produced {
+            let by_status = code
+                .strip_prefix("API_ERROR_")
+                .is_some_and(|status| status.parse::<u16>().is_ok());
+            assert!(
+                by_status || known.contains(&code.as_str()),
+                "`{code}` is missing from BackendErrorCode"
+            );
+        }
+        for code in known {
+            assert!(
+                produced.iter().any(|c| c == code),
+                "`{code}` is never produced"
+            );
+        }
+    }
+
+    #[test]
+    fn serialized_fields_match_the_frontend_interface() {
+        let mut fields: Vec<&str> = declaration("export interface BackendError {", "\n}")
+            .lines()
+            .filter_map(|line| line.trim().split_once(':').map(|(name, _)| name))
+            .collect();
+        fields.sort_unstable();
+
+        for error in every_variant() {
+            let serde_json::Value::Object(object) = serde_json::to_value(&error).unwrap() else {
+                panic!("{error:?} did not serialize to an object");
+            };
+            let keys: Vec<&str> = object.keys().map(String::as_str).collect();
+            assert_eq!(keys, fields, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn conflicts_carry_status_and_endpoint() {
+        let error = SyncthingError::from_status(409, "/rest/config/devices/X", "exists".into());
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "CONFLICT");
+        assert_eq!(json["status"], 409);
+        assert_eq!(json["endpoint"], "/rest/config/devices/X");
+        assert_eq!(json["message"], "Conflict: exists");
+    }
+
+    #[test]
+    fn not_found_ids_are_decoded() {
+        let error =
+            SyncthingError::from_status(404, "/rest/config/folders/my%20photos", String::new());
+        assert!(matches!(error, SyncthingError::FolderNotFound(id) if id == "my photos"));
+
+        let nested = SyncthingError::from_status(404, "/rest/config/folders/a%2Fb", String::new());
+        assert!(matches!(nested, SyncthingError::FolderNotFound(id) if id == "a/b"));
+    }
+}
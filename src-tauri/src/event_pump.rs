@@ -0,0 +1,319 @@
+//! Background pump that streams Syncthing events to the frontend.
+//!
+//! One task per event endpoint long-polls Syncthing, remembers the last event
+//! ID it has seen and re-emits every event as a Tauri event on all windows, so
+//! the frontend never has to run its own poller. Failures are retried with
+//! exponential backoff from the same ID, so nothing is missed over a timeout.
+//! Only a lost connection or a Syncthing restart, which numbers events from 1
+//! again, starts over from the newest event.
+
+use crate::completion::Completion;
+use crate::{SyncthingError, SyncthingState};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Every Syncthing event is forwarded under this name, whatever its type
+pub const SYNCTHING_EVENT: &str = "syncthing-event";
+/// Emitted when a stream connects to or loses Syncthing
+pub const EVENT_STREAM_STATUS: &str = "event-stream-status";
+
+/// Seconds Syncthing holds a long-poll open before returning an empty batch
+const LONG_POLL_TIMEOUT_S: u64 = 60;
+/// Extra time allowed on top of the long-poll for the response to arrive
+const LONG_POLL_GRACE: Duration = Duration::from_secs(5);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A raw event as returned by `/rest/events`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncthingEvent {
+    pub id: u64,
+    #[serde(rename = "globalID", default)]
+    pub global_id: u64,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub time: String,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+/// Payload of `folder-state-changed`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FolderStateChanged {
+    pub folder: String,
+    pub from: String,
+    pub to: String,
+    pub error: Option<String>,
+}
+
+/// Payload of `device-connected`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeviceConnected {
+    pub id: String,
+    pub device_name: String,
+    pub client_name: String,
+    pub client_version: String,
+    pub addr: String,
+    #[serde(rename = "type")]
+    pub connection_type: String,
+}
+
+/// Payload of `device-disconnected`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeviceDisconnected {
+    pub id: String,
+    pub error: Option<String>,
+}
+
+/// Payload of `item-started` and `item-finished`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ItemEvent {
+    pub folder: String,
+    pub item: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub action: String,
+    pub error: Option<String>,
+}
+
+/// Payload of `folder-completion`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FolderCompletion {
+    pub folder: String,
+    pub device: String,
+    #[serde(flatten)]
+    pub completion: Completion,
+}
+
+/// Payload of `event-stream-status`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamStatus {
+    /// `events` or `disk`
+    pub stream: &'static str,
+    pub connected: bool,
+    pub error: Option<String>,
+}
+
+/// One Syncthing event endpoint and the name it is reported under
+#[derive(Debug, Clone, Copy)]
+struct EventSource {
+    name: &'static str,
+    path: &'static str,
+}
+
+const SOURCES: [EventSource; 2] = [
+    EventSource {
+        name: "events",
+        path: "/rest/events",
+    },
+    EventSource {
+        name: "disk",
+        path: "/rest/events/disk",
+    },
+];
+
+/// Start one pump task per event endpoint
+pub fn spawn(app: &AppHandle) {
+    for source in SOURCES {
+        tauri::async_runtime::spawn(run(app.clone(), source));
+    }
+}
+
+async fn run(app: AppHandle, source: EventSource) {
+    let mut since: Option<u64> = None;
+    let mut backoff = INITIAL_BACKOFF;
+    let mut connected: Option<bool> = None;
+    let mut profile_changes = app.state::<SyncthingState>().subscribe_profile_changes();
+
+    loop {
+        let result = tokio::select! {
+            result = poll(&app, source, since) => result,
+            // Abandon the long-poll against the old instance and resync
+            _ = profile_changes.changed() => {
+                since = None;
+                connected = None;
+                backoff = INITIAL_BACKOFF;
+                continue;
+            },
+        };
+
+        match result {
+            Ok(events) => {
+                if connected != Some(true) {
+                    connected = Some(true);
+                    emit_status(&app, source, None);
+                }
+                backoff = INITIAL_BACKOFF;
+
+                if restarted(since, &events) {
+                    since = None;
+                    app.state::<SyncthingState>().completion.clear();
+                    continue;
+                }
+
+                // After (re)connecting only the newest event is requested to
+                // learn the current ID; replaying the backlog would be stale.
+                let resync = since.is_none();
+                if let Some(last) = events.last() {
+                    since = Some(last.id);
+                } else if resync {
+                    since = Some(0);
+                }
+                if !resync {
+                    for event in &events {
+                        dispatch(&app, event);
+                    }
+                }
+            },
+            Err(e) => {
+                if connected != Some(false) {
+                    connected = Some(false);
+                    emit_status(&app, source, Some(e.to_string()));
+                }
+                // Syncthing may come back restarted, with event IDs from 1, so
+                // start over; completion updates may be lost meanwhile
+                if matches!(e, SyncthingError::NotRunning { .. }) {
+                    since = None;
+                    app.state::<SyncthingState>().completion.clear();
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            },
+        }
+    }
+}
+
+async fn poll(
+    app: &AppHandle,
+    source: EventSource,
+    since: Option<u64>,
+) -> Result<Vec<SyncthingEvent>, SyncthingError> {
+    let client = app.state::<SyncthingState>().client();
+
+    // A resync (no known ID) asks only for the newest event, without waiting
+    let resync = since.is_none();
+    let (since, timeout) = since.map_or((0, 0), |since| (since, LONG_POLL_TIMEOUT_S));
+    let mut query = vec![
+        ("since", since.to_string()),
+        ("timeout", timeout.to_string()),
+    ];
+    if resync {
+        query.push(("limit", "1".to_string()));
+    }
+
+    let request = client
+        .request(Method::GET, source.path)
+        .query(&query)
+        .timeout(Duration::from_secs(timeout) + LONG_POLL_GRACE);
+
+    client.execute_json(request).await
+}
+
+/// Whether `events`, polled for after `since`, come from a restarted Syncthing
+fn restarted(since: Option<u64>, events: &[SyncthingEvent]) -> bool {
+    since.is_some_and(|since| events.first().is_some_and(|event| event.id <= since))
+}
+
+fn dispatch(app: &AppHandle, event: &SyncthingEvent) {
+    let _ = app.emit(SYNCTHING_EVENT, event);
+
+    match event.kind.as_str() {
+        "StateChanged" => emit_typed::<FolderStateChanged>(app, "folder-state-changed", event),
+        "DeviceConnected" => emit_typed::<DeviceConnected>(app, "device-connected", event),
+        "DeviceDisconnected" => {
+            emit_typed::<DeviceDisconnected>(app, "device-disconnected", event);
+        },
+        "ItemStarted" => emit_typed::<ItemEvent>(app, "item-started", event),
+        "ItemFinished" => emit_typed::<ItemEvent>(app, "item-finished", event),
+        "FolderCompletion" => {
+            record_completion(app, event);
+            emit_typed::<FolderCompletion>(app, "folder-completion", event);
+        },
+        "FolderSummary" => emit_data(app, "folder-summary", event),
+        "FolderErrors" => emit_data(app, "folder-errors", event),
+        "FolderPaused" | "FolderResumed" => emit_data(app, "folder-paused-changed", event),
+        "DevicePaused" | "DeviceResumed" => emit_data(app, "device-paused-changed", event),
+        "ConfigSaved" => emit_data(app, "config-saved", event),
+        "PendingDevicesChanged" => emit_data(app, "pending-devices-changed", event),
+        "PendingFoldersChanged" => emit_data(app, "pending-folders-changed", event),
+        "LocalChangeDetected" => emit_data(app, "local-change-detected", event),
+        "RemoteChangeDetected" => emit_data(app, "remote-change-detected", event),
+        _ => {},
+    }
+}
+
+/// Emit the event's `data` decoded as `T`; malformed payloads are skipped
+fn emit_typed<T>(app: &AppHandle, name: &str, event: &SyncthingEvent)
+where
+    T: DeserializeOwned + Serialize + Clone,
+{
+    match serde_json::from_value::<T>(event.data.clone()) {
+        Ok(payload) => {
+            let _ = app.emit(name, payload);
+        },
+        Err(e) => log::warn!("Skipping malformed {} event {}: {e}", event.kind, event.id),
+    }
+}
+
+/// Keep the completion cache in step with `FolderCompletion` events
+fn record_completion(app: &AppHandle, event: &SyncthingEvent) {
+    if let Ok(update) = serde_json::from_value::<FolderCompletion>(event.data.clone()) {
+        app.state::<SyncthingState>().completion.insert(
+            &update.folder,
+            &update.device,
+            update.completion,
+        );
+    }
+}
+
+/// Emit the event's `data` as-is
+fn emit_data(app: &AppHandle, name: &str, event: &SyncthingEvent) {
+    let _ = app.emit(name, &event.data);
+}
+
+fn emit_status(app: &AppHandle, source: EventSource, error: Option<String>) {
+    let _ = app.emit(
+        EVENT_STREAM_STATUS,
+        StreamStatus {
+            stream: source.name,
+            connected: error.is_none(),
+            error,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(ids: &[u64]) -> Vec<SyncthingEvent> {
+        ids.iter()
+            .map(|&id| SyncthingEvent {
+                id,
+                global_id: id,
+                kind: "Ping".into(),
+                time: String::new(),
+                data: serde_json::Value::Null,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lower_ids_than_requested_mean_a_restart() {
+        assert!(!restarted(Some(10), &events(&[11, 12])));
+        assert!(!restarted(Some(10), &events(&[])));
+        assert!(!restarted(None, &events(&[1])));
+        assert!(restarted(Some(10), &events(&[1, 2])));
+        assert!(restarted(Some(10), &events(&[10])));
+    }
+}
@@ -1,113 +1,256 @@
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::sync::Mutex;
+#[cfg(feature = "desktop")]
+use std::sync::{Mutex, PoisonError, RwLock};
+#[cfg(feature = "desktop")]
 use tauri::Manager;
-use tauri_plugin_shell::process::CommandChild;
+#[cfg(feature = "desktop")]
+use tokio::sync::watch;
 
+pub mod bandwidth;
+pub mod client;
+#[cfg(feature = "desktop")]
 pub mod commands;
+pub mod completion;
+pub mod core;
+pub mod device_id;
+pub mod discovery;
+pub mod error;
+#[cfg(feature = "desktop")]
+pub mod event_pump;
+pub mod manifest;
+pub mod models;
+pub mod preflight;
+pub mod profiles;
+pub mod qr;
+pub mod schedule;
+#[cfg(feature = "desktop")]
+pub mod scheduler;
+#[cfg(feature = "desktop")]
+pub mod sidecar;
+pub mod snapshots;
+pub mod sync_windows;
+pub mod tls;
+pub mod transaction;
+#[cfg(feature = "desktop")]
+pub mod tray;
+
+#[cfg(feature = "desktop")]
+use bandwidth::ScheduleStore;
+#[cfg(feature = "desktop")]
+use client::SyncthingClient;
+use discovery::Discovery;
+pub use error::SyncthingError;
+#[cfg(feature = "desktop")]
+use profiles::{ConnectionProfile, ProfileStore};
+#[cfg(feature = "desktop")]
+use sync_windows::SyncWindowStore;
 
 #[derive(Debug, Clone)]
 pub struct SyncthingConfig {
     pub api_key: String,
     pub port: u16,
     pub host: String,
+    pub use_tls: bool,
+    /// SHA-256 fingerprint of the GUI certificate to pin when using TLS
+    pub cert_fingerprint: Option<String>,
 }
 
 impl SyncthingConfig {
-    /// Try to read API key from Syncthing's config file
-    /// Supports both Linux and Windows config paths
-    fn read_api_key() -> Option<String> {
-        let mut paths = Vec::new();
-
-        // Linux/macOS config paths
-        if let Ok(home) = std::env::var("HOME") {
-            paths.push(format!("{home}/.local/state/syncthing/config.xml"));
-            paths.push(format!("{home}/.config/syncthing/config.xml"));
-        }
-
-        // Windows config paths
-        #[cfg(target_os = "windows")]
-        {
-            if let Ok(local_app_data) = std::env::var("LOCALAPPDATA") {
-                paths.push(format!("{local_app_data}\\Syncthing\\config.xml"));
-            }
-            if let Ok(user_profile) = std::env::var("USERPROFILE") {
-                paths.push(format!(
-                    "{user_profile}\\AppData\\Local\\Syncthing\\config.xml"
-                ));
-            }
-        }
-
-        // Also try APPDATA on Windows (some installations use this)
-        #[cfg(target_os = "windows")]
-        {
-            if let Ok(app_data) = std::env::var("APPDATA") {
-                paths.push(format!("{app_data}\\Syncthing\\config.xml"));
-            }
-        }
+    /// Connection settings for the local instance described by `discovery`
+    ///
+    /// Falls back to Syncthing's default address when no usable TCP address was
+    /// found, and to a fresh API key (handed to the sidecar on launch) when
+    /// `config.xml` has none.
+    pub fn from_discovery(discovery: &Discovery) -> Self {
+        let gui = discovery
+            .gui
+            .as_ref()
+            .filter(|gui| gui.unix_socket.is_none());
 
-        for path in &paths {
-            if let Ok(content) = fs::read_to_string(path) {
-                // Simple XML parsing for apikey
-                if let Some(start) = content.find("<apikey>") {
-                    if let Some(end) = content[start..].find("</apikey>") {
-                        let key = &content[start + 8..start + end];
-                        if !key.is_empty() {
-                            return Some(key.to_string());
-                        }
-                    }
-                }
-            }
+        Self {
+            api_key: gui
+                .and_then(|gui| gui.api_key.clone())
+                .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string()),
+            port: gui.map_or(8384, |gui| gui.port),
+            host: gui.map_or_else(|| "127.0.0.1".to_string(), |gui| gui.host.clone()),
+            use_tls: gui.is_some_and(|gui| gui.tls),
+            cert_fingerprint: discovery.cert_fingerprint.clone(),
         }
-        None
     }
 }
 
 impl Default for SyncthingConfig {
     fn default() -> Self {
-        Self {
-            api_key: Self::read_api_key().unwrap_or_else(|| "no-api-key".to_string()),
-            port: 8384,
-            host: "127.0.0.1".to_string(),
-        }
+        Self::from_discovery(&discovery::discover(None))
     }
 }
 
+/// The profile every command is currently routed through
+#[cfg(feature = "desktop")]
+struct ActiveConnection {
+    config: SyncthingConfig,
+    client: SyncthingClient,
+}
+
+#[cfg(feature = "desktop")]
 pub struct SyncthingState {
-    pub config: SyncthingConfig,
-    pub sidecar_child: Mutex<Option<CommandChild>>,
+    /// Settings of the local instance, used to launch the sidecar
+    pub local_config: SyncthingConfig,
+    /// Where `local_config` came from
+    pub discovery: Discovery,
+    pub profiles: Mutex<ProfileStore>,
+    /// Config snapshots taken before every change
+    pub snapshots: snapshots::SnapshotStore,
+    /// Folder completion per device, kept fresh by the event pump
+    pub completion: completion::CompletionCache,
+    connection: RwLock<ActiveConnection>,
+    /// Bumped every time the active profile changes
+    profile_generation: watch::Sender<u64>,
+    /// Bandwidth schedule, applied by the scheduler
+    pub bandwidth: Mutex<ScheduleStore>,
+    /// Folder and device sync windows, applied by the scheduler
+    pub sync_windows: Mutex<SyncWindowStore>,
+    /// Bumped every time a schedule or the sync windows are saved
+    schedule_generation: watch::Sender<u64>,
+    /// Lines shown in the tray tooltip
+    pub tray: Mutex<tray::TrayTooltip>,
+    /// Supervised Syncthing sidecar process
+    pub sidecar: sidecar::Sidecar,
 }
 
-impl Default for SyncthingState {
-    fn default() -> Self {
+#[cfg(feature = "desktop")]
+impl SyncthingState {
+    /// Build the state with persisted profiles loaded from `config_dir` and
+    /// config snapshots kept in `data_dir`, discovering the local instance in
+    /// `syncthing_home` if given
+    pub fn new(
+        config_dir: Option<&std::path::Path>,
+        data_dir: Option<&std::path::Path>,
+        syncthing_home: Option<&std::path::Path>,
+    ) -> Self {
+        let discovery = discovery::discover(syncthing_home);
+        for warning in &discovery.warnings {
+            log::warn!("{warning}");
+        }
+        let local_config = SyncthingConfig::from_discovery(&discovery);
+        let mut store = ProfileStore::load(config_dir, ConnectionProfile::local(&local_config));
+        let mut config = store.active().to_config();
+        let client = SyncthingClient::new(&config)
+            .or_else(|e| {
+                log::warn!(
+                    "Cannot use profile `{}`, falling back to local: {e}",
+                    store.active().name
+                );
+                config = store.fall_back_to_local().to_config();
+                SyncthingClient::new(&config)
+            })
+            .unwrap_or_else(|e| {
+                // E.g. GUI TLS without a readable certificate; every request
+                // fails until the user picks a usable profile
+                log::error!("Cannot use the local instance: {e}");
+                config.use_tls = false;
+                SyncthingClient::new(&config).expect("Failed to initialise Syncthing HTTP client")
+            });
+
         Self {
-            config: SyncthingConfig::default(),
-            sidecar_child: Mutex::new(None),
+            local_config,
+            discovery,
+            profiles: Mutex::new(store),
+            snapshots: snapshots::SnapshotStore::new(data_dir),
+            completion: completion::CompletionCache::default(),
+            connection: RwLock::new(ActiveConnection { config, client }),
+            profile_generation: watch::channel(0).0,
+            bandwidth: Mutex::new(ScheduleStore::load(config_dir)),
+            sync_windows: Mutex::new(SyncWindowStore::load(config_dir)),
+            schedule_generation: watch::channel(0).0,
+            tray: Mutex::default(),
+            sidecar: sidecar::Sidecar::default(),
         }
     }
-}
 
-#[derive(Debug, Serialize, Deserialize)]
-pub enum SyncthingError {
-    NotRunning,
-    HttpError(String),
-    ParseError(String),
-    ProcessError(String),
-}
+    /// Client for the active profile
+    pub fn client(&self) -> SyncthingClient {
+        self.connection
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .client
+            .clone()
+    }
 
-impl std::fmt::Display for SyncthingError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::NotRunning => write!(f, "Syncthing is not running"),
-            Self::HttpError(e) => write!(f, "HTTP error: {e}"),
-            Self::ParseError(e) => write!(f, "Parse error: {e}"),
-            Self::ProcessError(e) => write!(f, "Process error: {e}"),
+    /// Context for running `core` operations against the active profile
+    pub fn context(&self) -> core::Context {
+        core::Context {
+            client: self.client(),
+            snapshots: self.snapshots.clone(),
+            profile: self
+                .profiles
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .active()
+                .name
+                .clone(),
+            completion: self.completion.clone(),
         }
     }
-}
 
-impl std::error::Error for SyncthingError {}
+    /// Connection settings of the active profile
+    pub fn config(&self) -> SyncthingConfig {
+        self.connection
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .config
+            .clone()
+    }
+
+    /// Route all further requests through `profile`
+    pub fn activate(&self, profile: &ConnectionProfile) -> Result<(), SyncthingError> {
+        let config = profile.to_config();
+        let client = SyncthingClient::new(&config)?;
+        *self
+            .connection
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = ActiveConnection { config, client };
+        self.completion.clear();
+        self.profile_generation.send_modify(|g| *g += 1);
+        Ok(())
+    }
+
+    /// Watch for active profile switches (used by long-running background tasks)
+    pub fn subscribe_profile_changes(&self) -> watch::Receiver<u64> {
+        self.profile_generation.subscribe()
+    }
+
+    /// Record that the user paused or resumed `target` by hand, so the
+    /// scheduler leaves it alone
+    pub fn forget_scheduled_pause(&self, target: &sync_windows::Target) {
+        let profile = self
+            .profiles
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .active()
+            .name
+            .clone();
+        let result = self
+            .sync_windows
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .forget_pause(&profile, target);
+        if let Err(e) = result {
+            log::warn!("Failed to record manual pause: {e}");
+        }
+    }
+
+    /// Tell the scheduler a schedule or the sync windows were saved
+    pub fn schedule_changed(&self) {
+        self.schedule_generation.send_modify(|g| *g += 1);
+    }
 
+    /// Watch for saved schedules and sync windows (used by the scheduler)
+    pub fn subscribe_schedule_changes(&self) -> watch::Receiver<u64> {
+        self.schedule_generation.subscribe()
+    }
+}
+
+#[cfg(feature = "desktop")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -115,12 +258,22 @@ pub fn run() {
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
-        .manage(SyncthingState::default())
         .setup(|app| {
-            // Set up tray menu
             use tauri::menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem};
             use tauri::tray::TrayIconBuilder;
 
+            // Load connection profiles before anything can issue a command
+            let config_dir = app.path().app_config_dir().ok();
+            let data_dir = app.path().app_data_dir().ok();
+            let syncthing_home = discovery::home_arg(std::env::args().skip(1));
+            app.manage(SyncthingState::new(
+                config_dir.as_deref(),
+                data_dir.as_deref(),
+                syncthing_home.as_deref(),
+            ));
+
+            // Set up tray menu
+
             let status = MenuItemBuilder::with_id("status", "Status: Checking...")
                 .enabled(false)
                 .build(app)?;
@@ -152,10 +305,10 @@ pub fn run() {
             let (width, height) = rgba.dimensions();
             let tray_icon = tauri::image::Image::new_owned(rgba.into_raw(), width, height);
 
-            let _tray = TrayIconBuilder::with_id("main")
+            let _tray = TrayIconBuilder::with_id(tray::TRAY_ID)
                 .icon(tray_icon)
                 .menu(&menu)
-                .tooltip("Eigen - Syncthing Manager")
+                .tooltip(tray::DEFAULT_TOOLTIP)
                 .on_menu_event(|app, event| {
                     match event.id().as_ref() {
                         "show" => {
@@ -171,17 +324,13 @@ pub fn run() {
                             }
                         },
                         "open_syncthing" => {
-                            // Open Syncthing web UI in browser
-                            let _ = open::that("http://127.0.0.1:8384");
+                            // Open the active profile's Syncthing web UI in browser
+                            let state = app.state::<SyncthingState>();
+                            let _ = open::that(state.client().base_url());
                         },
                         "quit" => {
                             // Stop syncthing sidecar before quitting
-                            let state = app.state::<SyncthingState>();
-                            if let Ok(mut child_guard) = state.sidecar_child.lock() {
-                                if let Some(child) = child_guard.take() {
-                                    let _ = child.kill();
-                                }
-                            }
+                            let _ = app.state::<SyncthingState>().sidecar.stop();
                             app.exit(0);
                         },
                         _ => {},
@@ -222,6 +371,11 @@ pub fn run() {
                 });
             }
 
+            // Stream Syncthing events to all windows from a single background task
+            event_pump::spawn(app.handle());
+            // Apply the bandwidth schedule and sync windows at every boundary
+            scheduler::spawn(app.handle());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -229,28 +383,69 @@ pub fn run() {
             commands::system::check_syncthing_installation,
             commands::system::start_syncthing_sidecar,
             commands::system::stop_syncthing_sidecar,
+            commands::system::get_sidecar_output,
             commands::system::ping_syncthing,
             commands::system::get_system_status,
             commands::system::restart_syncthing,
             commands::system::get_api_config,
+            commands::system::get_syncthing_discovery,
             // Config commands
             commands::config::get_connections,
             commands::config::get_config,
             commands::config::update_options,
+            commands::config::preview_config_transaction,
+            commands::config::apply_config_transaction,
+            // Connection profile commands
+            commands::profiles::list_profiles,
+            commands::profiles::save_profile,
+            commands::profiles::remove_profile,
+            commands::profiles::switch_profile,
+            commands::profiles::fetch_certificate_fingerprint,
+            // Declarative manifest commands
+            commands::manifest::export_config_manifest,
+            commands::manifest::preview_config_manifest,
+            commands::manifest::apply_config_manifest,
+            // Config snapshot commands
+            commands::snapshots::list_config_snapshots,
+            commands::snapshots::diff_config_snapshot,
+            commands::snapshots::restore_config_snapshot,
             // Folder commands
             commands::folders::get_folder_status,
+            commands::folders::get_folder_completion,
+            commands::folders::get_completion_matrix,
             commands::folders::pause_folder,
             commands::folders::resume_folder,
             commands::folders::rescan_folder,
             commands::folders::add_folder,
             commands::folders::add_folder_advanced,
+            commands::folders::generate_folder_id,
             commands::folders::remove_folder,
             commands::folders::update_folder_config,
             commands::folders::get_folder_config,
             commands::folders::share_folder,
             commands::folders::unshare_folder,
+            commands::folders::override_folder_changes,
+            commands::folders::revert_folder_changes,
+            // Out-of-sync item and file detail commands
+            commands::db::get_folder_need,
+            commands::db::get_remote_need,
+            commands::db::get_local_changed,
+            commands::db::get_file_info,
+            // Statistics commands
+            commands::stats::get_device_stats,
+            commands::stats::get_folder_stats,
+            commands::stats::get_device_overview,
+            // Bandwidth schedule commands
+            commands::bandwidth::get_bandwidth_schedule,
+            commands::bandwidth::set_bandwidth_schedule,
+            commands::bandwidth::get_bandwidth_status,
+            // Sync window commands
+            commands::sync_windows::get_sync_windows,
+            commands::sync_windows::set_sync_windows,
+            commands::sync_windows::get_sync_window_state,
             // Device commands
             commands::devices::get_device_id,
+            commands::devices::validate_device_id,
             commands::devices::add_device,
             commands::devices::add_device_advanced,
             commands::devices::remove_device,
@@ -258,6 +453,9 @@ pub fn run() {
             commands::devices::get_device_config,
             commands::devices::pause_device,
             commands::devices::resume_device,
+            commands::devices::get_device_invite,
+            commands::devices::render_device_invite_qr,
+            commands::devices::decode_device_invite,
             // File commands (browser, ignores, conflicts, versions)
             commands::files::open_folder_in_explorer,
             commands::files::browse_folder,
@@ -0,0 +1,346 @@
+//! Declarative folder/device manifests.
+//!
+//! A manifest describes the folders, devices and sharing of a machine in a
+//! portable TOML or YAML document. Exporting turns the live configuration into
+//! one; applying plans the [`ConfigOperation`]s that converge the live
+//! configuration on it, which then run as a normal config transaction.
+//!
+//! Settings left out of a manifest are not touched, and items missing from it
+//! are only removed when pruning is requested. The local device is never
+//! exported or removed, since its ID differs on every machine.
+
+use crate::device_id;
+use crate::models::{Configuration, DeviceConfiguration, Extra, FolderConfiguration};
+use crate::transaction::ConfigOperation;
+use crate::SyncthingError;
+use serde::{Deserialize, Serialize};
+
+/// Document formats a manifest can be read from and written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestFormat {
+    Toml,
+    Yaml,
+}
+
+/// The desired folders and devices of a machine
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default)]
+    pub devices: Vec<ManifestDevice>,
+    #[serde(default)]
+    pub folders: Vec<ManifestFolder>,
+}
+
+/// A remote device; unset settings keep their current value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ManifestDevice {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addresses: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub introducer: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_accept_folders: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused: Option<bool>,
+}
+
+/// A folder and the devices it is shared with; unset settings keep their current value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ManifestFolder {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub path: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub folder_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rescan_interval_s: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fs_watcher_enabled: Option<bool>,
+    /// Device IDs; when set, sharing is converged on exactly this list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub devices: Option<Vec<String>>,
+}
+
+impl Manifest {
+    /// Parse a manifest document
+    pub fn parse(document: &str, format: ManifestFormat) -> Result<Self, SyncthingError> {
+        let invalid = |e: &dyn std::fmt::Display| {
+            SyncthingError::ParseError(format!("Invalid manifest: {e}"))
+        };
+        let mut manifest: Self = match format {
+            ManifestFormat::Toml => toml::from_str(document).map_err(|e| invalid(&e))?,
+            ManifestFormat::Yaml => serde_yaml::from_str(document).map_err(|e| invalid(&e))?,
+        };
+        manifest.normalize_device_ids()?;
+        Ok(manifest)
+    }
+
+    /// Bring every device ID into the canonical form the live config uses,
+    /// so IDs written by hand compare equal to it
+    fn normalize_device_ids(&mut self) -> Result<(), SyncthingError> {
+        let normalize = |id: &mut String, field: String| {
+            *id = device_id::normalize(id).map_err(|e| match e {
+                SyncthingError::Validation { message, .. } => {
+                    SyncthingError::validation(field, message)
+                },
+                other => other,
+            })?;
+            Ok::<_, SyncthingError>(())
+        };
+        for (i, device) in self.devices.iter_mut().enumerate() {
+            normalize(&mut device.id, format!("devices[{i}].id"))?;
+        }
+        for (i, folder) in self.folders.iter_mut().enumerate() {
+            for (j, id) in folder.devices.iter_mut().flatten().enumerate() {
+                normalize(id, format!("folders[{i}].devices[{j}]"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize the manifest as a document
+    pub fn to_document(&self, format: ManifestFormat) -> Result<String, SyncthingError> {
+        let failed =
+            |e: &dyn std::fmt::Display| SyncthingError::ParseError(format!("Cannot export: {e}"));
+        match format {
+            ManifestFormat::Toml => toml::to_string_pretty(self).map_err(|e| failed(&e)),
+            ManifestFormat::Yaml => serde_yaml::to_string(self).map_err(|e| failed(&e)),
+        }
+    }
+
+    /// Describe `config`, leaving out the local device
+    pub fn export(config: &Configuration, local_device: &str) -> Self {
+        Self {
+            devices: config
+                .devices
+                .iter()
+                .filter(|d| d.device_id != local_device)
+                .map(ManifestDevice::from)
+                .collect(),
+            folders: config
+                .folders
+                .iter()
+                .map(|f| ManifestFolder::from_config(f, local_device))
+                .collect(),
+        }
+    }
+
+    /// Operations that converge `config` on this manifest
+    ///
+    /// Devices are added or updated first so folders can be shared with them.
+    /// With `prune`, folders and devices the manifest does not list are removed.
+    pub fn plan(
+        &self,
+        config: &Configuration,
+        local_device: &str,
+        prune: bool,
+    ) -> Vec<ConfigOperation> {
+        let mut operations = Vec::new();
+
+        for device in &self.devices {
+            let settings = settings(device, &["id", "name"]);
+            if config.device(&device.id).is_some() {
+                let mut updates = settings;
+                updates.insert("name".into(), device.name.clone().into());
+                operations.push(ConfigOperation::UpdateDevice {
+                    device_id: device.id.clone(),
+                    updates,
+                });
+            } else {
+                operations.push(ConfigOperation::AddDevice {
+                    device_id: device.id.clone(),
+                    name: device.name.clone(),
+                    settings,
+                });
+            }
+        }
+
+        for folder in &self.folders {
+            let settings = settings(folder, &["id", "devices"]);
+            let current = config.folder(&folder.id);
+            if current.is_some() {
+                operations.push(ConfigOperation::UpdateFolder {
+                    folder_id: folder.id.clone(),
+                    updates: settings,
+                });
+            } else {
+                operations.push(ConfigOperation::AddFolder {
+                    folder_id: folder.id.clone(),
+                    label: folder.label.clone().unwrap_or_else(|| folder.id.clone()),
+                    path: folder.path.clone(),
+                    settings,
+                });
+            }
+
+            let Some(devices) = &folder.devices else {
+                continue;
+            };
+            for device_id in devices {
+                if !current.is_some_and(|f| f.is_shared_with(device_id)) {
+                    operations.push(ConfigOperation::ShareFolder {
+                        folder_id: folder.id.clone(),
+                        device_id: device_id.clone(),
+                        encryption_password: None,
+                    });
+                }
+            }
+            for shared in current.iter().flat_map(|f| &f.devices) {
+                if shared.device_id != local_device && !devices.contains(&shared.device_id) {
+                    operations.push(ConfigOperation::UnshareFolder {
+                        folder_id: folder.id.clone(),
+                        device_id: shared.device_id.clone(),
+                    });
+                }
+            }
+        }
+
+        if prune {
+            for folder in &config.folders {
+                if !self.folders.iter().any(|f| f.id == folder.id) {
+                    operations.push(ConfigOperation::RemoveFolder {
+                        folder_id: folder.id.clone(),
+                    });
+                }
+            }
+            for device in &config.devices {
+                if device.device_id != local_device
+                    && !self.devices.iter().any(|d| d.id == device.device_id)
+                {
+                    operations.push(ConfigOperation::RemoveDevice {
+                        device_id: device.device_id.clone(),
+                    });
+                }
+            }
+        }
+
+        operations
+    }
+}
+
+impl From<&DeviceConfiguration> for ManifestDevice {
+    fn from(device: &DeviceConfiguration) -> Self {
+        Self {
+            id: canonical(&device.device_id),
+            name: device.name.clone(),
+            addresses: Some(device.addresses.clone()),
+            introducer: Some(device.introducer),
+            auto_accept_folders: Some(device.auto_accept_folders),
+            paused: Some(device.paused),
+        }
+    }
+}
+
+impl ManifestFolder {
+    fn from_config(folder: &FolderConfiguration, local_device: &str) -> Self {
+        Self {
+            id: folder.id.clone(),
+            label: Some(folder.label.clone()),
+            path: folder.path.clone(),
+            folder_type: Some(folder.folder_type.clone()),
+            paused: Some(folder.paused),
+            rescan_interval_s: Some(folder.rescan_interval_s),
+            fs_watcher_enabled: Some(folder.fs_watcher_enabled),
+            devices: Some(
+                folder
+                    .devices
+                    .iter()
+                    .filter(|d| d.device_id != local_device)
+                    .map(|d| canonical(&d.device_id))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// `id` in canonical form, or as it is if Syncthing holds an ID that does
+/// not parse
+fn canonical(id: &str) -> String {
+    device_id::normalize(id).unwrap_or_else(|_| id.to_string())
+}
+
+/// The settings an item sets, keyed as in Syncthing's config, minus `skip`
+///
+/// Manifest keys are camelCase like Syncthing's, so the serialized item doubles
+/// as an update for `apply_update`.
+fn settings<T: Serialize>(item: &T, skip: &[&str]) -> Extra {
+    let mut settings = match serde_json::to_value(item) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => Extra::new(),
+    };
+    settings.retain(|key, _| !skip.contains(&key.as_str()));
+    settings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const LOCAL: &str = "MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD";
+    const NAS: &str = "P56IOI7-MZJNU2Y-IQGDREY-DM2MGTI-MGL3BXN-PQ6W5BM-TBBZ4TJ-XZWICQ2";
+
+    #[test]
+    fn plan_matches_hand_written_device_ids() {
+        let config: Configuration = serde_json::from_value(json!({
+            "devices": [
+                { "deviceID": LOCAL, "name": "laptop" },
+                { "deviceID": NAS, "name": "nas" },
+            ],
+            "folders": [{
+                "id": "docs",
+                "path": "/srv/docs",
+                "devices": [{ "deviceID": LOCAL }, { "deviceID": NAS }],
+            }],
+        }))
+        .unwrap();
+        // Lowercase, without dashes and with `0`/`1` typed for `O`/`I`
+        let nas = NAS.to_lowercase().replace('-', "").replace('o', "0");
+        let manifest = Manifest::parse(
+            &format!(
+                r#"
+                [[devices]]
+                id = "{nas}"
+                name = "nas"
+
+                [[folders]]
+                id = "docs"
+                path = "/srv/docs"
+                devices = ["{nas}"]
+                "#
+            ),
+            ManifestFormat::Toml,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.devices[0].id, NAS);
+        let operations = manifest.plan(&config, LOCAL, true);
+        assert_eq!(operations.len(), 2, "{operations:?}");
+        assert!(
+            matches!(&operations[0], ConfigOperation::UpdateDevice { device_id, .. } if device_id == NAS)
+        );
+        assert!(
+            matches!(&operations[1], ConfigOperation::UpdateFolder { folder_id, .. } if folder_id == "docs")
+        );
+    }
+
+    #[test]
+    fn parse_names_the_invalid_device_id() {
+        let result = Manifest::parse(
+            "folders:\n  - id: docs\n    path: /srv/docs\n    devices: [NOT-A-DEVICE]\n",
+            ManifestFormat::Yaml,
+        );
+        assert!(matches!(
+            result,
+            Err(SyncthingError::Validation { field, .. }) if field == "folders[0].devices[0]"
+        ));
+    }
+}
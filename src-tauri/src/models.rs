@@ -0,0 +1,559 @@
+//! Typed models for Syncthing's configuration objects.
+//!
+//! These mirror the JSON shapes served under `/rest/config`. Every struct
+//! keeps a `#[serde(flatten)]` catch-all so fields added by newer Syncthing
+//! releases survive a read-modify-write round trip untouched.
+
+use crate::SyncthingError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Unknown keys preserved verbatim
+pub type Extra = serde_json::Map<String, serde_json::Value>;
+
+/// Folder types Syncthing accepts for a folder's `type`
+pub const FOLDER_TYPES: &[&str] = &["sendreceive", "sendonly", "receiveonly", "receiveencrypted"];
+
+/// The complete Syncthing configuration (`/rest/config`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Configuration {
+    pub version: i32,
+    pub folders: Vec<FolderConfiguration>,
+    pub devices: Vec<DeviceConfiguration>,
+    pub gui: GuiConfiguration,
+    pub options: Options,
+    /// Templates for new folders and devices (absent before Syncthing 1.12)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<Defaults>,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Configuration {
+    /// A new folder built from the configured folder defaults
+    pub fn new_folder(&self, id: String, label: String, path: String) -> FolderConfiguration {
+        let defaults = self.defaults.as_ref().map(|d| d.folder.clone());
+        FolderConfiguration::from_defaults(defaults, id, label, path)
+    }
+
+    /// A new device built from the configured device defaults
+    pub fn new_device(&self, device_id: String, name: String) -> DeviceConfiguration {
+        let defaults = self.defaults.as_ref().map(|d| d.device.clone());
+        DeviceConfiguration::from_defaults(defaults, device_id, name)
+    }
+
+    pub fn folder(&self, folder_id: &str) -> Option<&FolderConfiguration> {
+        self.folders.iter().find(|f| f.id == folder_id)
+    }
+
+    pub fn folder_mut(&mut self, folder_id: &str) -> Option<&mut FolderConfiguration> {
+        self.folders.iter_mut().find(|f| f.id == folder_id)
+    }
+
+    pub fn device(&self, device_id: &str) -> Option<&DeviceConfiguration> {
+        self.devices.iter().find(|d| d.device_id == device_id)
+    }
+}
+
+/// Templates Syncthing and its web UI start new objects from (`/rest/config/defaults`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Defaults {
+    pub folder: FolderConfiguration,
+    pub device: DeviceConfiguration,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// A shared folder (`/rest/config/folders/{id}`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct FolderConfiguration {
+    pub id: String,
+    pub label: String,
+    pub filesystem_type: String,
+    pub path: String,
+    #[serde(rename = "type")]
+    pub folder_type: String,
+    pub devices: Vec<FolderDeviceConfiguration>,
+    pub rescan_interval_s: i32,
+    pub fs_watcher_enabled: bool,
+    pub fs_watcher_delay_s: f64,
+    pub ignore_perms: bool,
+    pub auto_normalize: bool,
+    pub min_disk_free: Size,
+    pub versioning: Versioning,
+    pub copiers: i32,
+    #[serde(rename = "pullerMaxPendingKiB")]
+    pub puller_max_pending_kib: i32,
+    pub hashers: i32,
+    pub order: String,
+    pub ignore_delete: bool,
+    pub scan_progress_interval_s: i32,
+    pub puller_pause_s: i32,
+    pub max_conflicts: i32,
+    pub disable_sparse_files: bool,
+    pub disable_temp_indexes: bool,
+    pub paused: bool,
+    pub weak_hash_threshold_pct: i32,
+    pub marker_name: String,
+    pub copy_ownership_from_parent: bool,
+    pub mod_time_window_s: i32,
+    pub max_concurrent_writes: i32,
+    pub disable_fsync: bool,
+    pub block_pull_order: String,
+    pub copy_range_method: String,
+    #[serde(rename = "caseSensitiveFS")]
+    pub case_sensitive_fs: bool,
+    pub junctions_as_dirs: bool,
+    pub sync_ownership: bool,
+    pub send_ownership: bool,
+    pub sync_xattrs: bool,
+    pub send_xattrs: bool,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl FolderConfiguration {
+    /// `defaults` with the given identity, or [`Self::new`] when Syncthing has no defaults
+    pub fn from_defaults(defaults: Option<Self>, id: String, label: String, path: String) -> Self {
+        match defaults {
+            Some(defaults) => Self {
+                id,
+                label,
+                path,
+                ..defaults
+            },
+            None => Self::new(id, label, path),
+        }
+    }
+
+    /// A send-receive folder with the same defaults the Syncthing web UI uses
+    pub fn new(id: String, label: String, path: String) -> Self {
+        Self {
+            id,
+            label,
+            filesystem_type: "basic".into(),
+            path,
+            folder_type: "sendreceive".into(),
+            devices: Vec::new(),
+            rescan_interval_s: 3600,
+            fs_watcher_enabled: true,
+            fs_watcher_delay_s: 10.0,
+            ignore_perms: false,
+            auto_normalize: true,
+            min_disk_free: Size {
+                value: 1.0,
+                unit: "%".into(),
+            },
+            versioning: Versioning::default(),
+            copiers: 0,
+            puller_max_pending_kib: 0,
+            hashers: 0,
+            order: "random".into(),
+            ignore_delete: false,
+            scan_progress_interval_s: 0,
+            puller_pause_s: 0,
+            max_conflicts: 10,
+            disable_sparse_files: false,
+            disable_temp_indexes: false,
+            paused: false,
+            weak_hash_threshold_pct: 25,
+            marker_name: ".stfolder".into(),
+            copy_ownership_from_parent: false,
+            mod_time_window_s: 0,
+            max_concurrent_writes: 2,
+            disable_fsync: false,
+            block_pull_order: "standard".into(),
+            copy_range_method: "standard".into(),
+            case_sensitive_fs: false,
+            junctions_as_dirs: false,
+            sync_ownership: false,
+            send_ownership: false,
+            sync_xattrs: false,
+            send_xattrs: false,
+            extra: Extra::new(),
+        }
+    }
+
+    pub fn is_shared_with(&self, device_id: &str) -> bool {
+        self.devices.iter().any(|d| d.device_id == device_id)
+    }
+
+    /// Add `device_id` to the folder's device list; returns `false` if already present
+    pub fn share_with(&mut self, device_id: &str) -> bool {
+        if self.is_shared_with(device_id) {
+            return false;
+        }
+        self.devices
+            .push(FolderDeviceConfiguration::new(device_id.to_string()));
+        true
+    }
+
+    /// Share with `device_id`, encrypting what is sent to it with `password`
+    ///
+    /// An existing entry gets its password replaced. Returns `false` if
+    /// nothing changed.
+    pub fn share_encrypted_with(&mut self, device_id: &str, password: &str) -> bool {
+        let added = self.share_with(device_id);
+        let Some(device) = self.devices.iter_mut().find(|d| d.device_id == device_id) else {
+            return added;
+        };
+        if device.encryption_password == password {
+            return added;
+        }
+        device.encryption_password = password.to_string();
+        true
+    }
+
+    /// Reject settings Syncthing would refuse or misapply
+    pub fn validate(&self) -> Result<(), SyncthingError> {
+        if !FOLDER_TYPES.contains(&self.folder_type.as_str()) {
+            return Err(SyncthingError::validation(
+                "type",
+                format!("must be one of {}", FOLDER_TYPES.join(", ")),
+            ));
+        }
+        // A receive-encrypted folder only stores what its peers already encrypted
+        if self.folder_type == "receiveencrypted"
+            && self
+                .devices
+                .iter()
+                .any(|d| !d.encryption_password.is_empty())
+        {
+            return Err(SyncthingError::validation(
+                "encryptionPassword",
+                "cannot be set on a receive-encrypted folder",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Remove `device_id` from the folder's device list; returns `false` if it was not present
+    pub fn unshare_with(&mut self, device_id: &str) -> bool {
+        let before = self.devices.len();
+        self.devices.retain(|d| d.device_id != device_id);
+        self.devices.len() != before
+    }
+}
+
+/// A device entry inside a folder's `devices` list
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FolderDeviceConfiguration {
+    #[serde(rename = "deviceID")]
+    pub device_id: String,
+    pub introduced_by: String,
+    pub encryption_password: String,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl FolderDeviceConfiguration {
+    pub fn new(device_id: String) -> Self {
+        Self {
+            device_id,
+            ..Self::default()
+        }
+    }
+}
+
+/// File versioning settings of a folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Versioning {
+    /// `""` (disabled), `simple`, `staggered`, `trashcan` or `external`
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub params: BTreeMap<String, String>,
+    pub cleanup_interval_s: i32,
+    pub fs_path: String,
+    pub fs_type: String,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Default for Versioning {
+    fn default() -> Self {
+        Self {
+            kind: String::new(),
+            params: BTreeMap::new(),
+            cleanup_interval_s: 3600,
+            fs_path: String::new(),
+            fs_type: "basic".into(),
+            extra: Extra::new(),
+        }
+    }
+}
+
+impl Versioning {
+    /// Build versioning settings of the given type, filling in Syncthing's
+    /// default parameters when none are supplied
+    ///
+    /// An empty type turns versioning off; unknown types are rejected rather
+    /// than silently doing the same.
+    pub fn with_type(
+        kind: &str,
+        params: Option<BTreeMap<String, String>>,
+    ) -> Result<Self, SyncthingError> {
+        let defaults: &[(&str, &str)] = match kind {
+            "" => return Ok(Self::default()),
+            "simple" => &[("keep", "5")],
+            "staggered" => &[("cleanInterval", "3600"), ("maxAge", "31536000")],
+            "trashcan" => &[("cleanoutDays", "0")],
+            "external" => &[("command", "")],
+            _ => {
+                return Err(SyncthingError::validation(
+                    "versioning.type",
+                    format!(
+                        "unknown versioning type `{kind}` (expected trashcan, simple, \
+                         staggered or external)"
+                    ),
+                ))
+            },
+        };
+
+        Ok(Self {
+            kind: kind.to_string(),
+            params: params.unwrap_or_else(|| {
+                defaults
+                    .iter()
+                    .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+                    .collect()
+            }),
+            ..Self::default()
+        })
+    }
+}
+
+/// A size with unit, e.g. `{ "value": 1, "unit": "%" }`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Size {
+    pub value: f64,
+    pub unit: String,
+}
+
+/// A remote device (`/rest/config/devices/{id}`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct DeviceConfiguration {
+    #[serde(rename = "deviceID")]
+    pub device_id: String,
+    pub name: String,
+    pub addresses: Vec<String>,
+    pub compression: String,
+    pub cert_name: String,
+    pub introducer: bool,
+    pub skip_introduction_removals: bool,
+    pub introduced_by: String,
+    pub paused: bool,
+    pub allowed_networks: Vec<String>,
+    pub auto_accept_folders: bool,
+    pub max_send_kbps: i32,
+    pub max_recv_kbps: i32,
+    #[serde(rename = "maxRequestKiB")]
+    pub max_request_kib: i32,
+    pub untrusted: bool,
+    #[serde(rename = "remoteGUIPort")]
+    pub remote_gui_port: i32,
+    pub num_connections: i32,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl DeviceConfiguration {
+    /// `defaults` with the given identity, or [`Self::new`] when Syncthing has no defaults
+    pub fn from_defaults(defaults: Option<Self>, device_id: String, name: String) -> Self {
+        match defaults {
+            Some(defaults) => Self {
+                device_id,
+                name,
+                ..defaults
+            },
+            None => Self::new(device_id, name),
+        }
+    }
+
+    /// A device with dynamic addresses and metadata compression, as the web UI creates it
+    pub fn new(device_id: String, name: String) -> Self {
+        Self {
+            device_id,
+            name,
+            addresses: vec!["dynamic".into()],
+            compression: "metadata".into(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Global options (`/rest/config/options`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Options {
+    pub listen_addresses: Vec<String>,
+    pub global_announce_servers: Vec<String>,
+    pub global_announce_enabled: bool,
+    pub local_announce_enabled: bool,
+    pub local_announce_port: i32,
+    #[serde(rename = "localAnnounceMCAddr")]
+    pub local_announce_mc_addr: String,
+    pub max_send_kbps: i32,
+    pub max_recv_kbps: i32,
+    pub reconnection_interval_s: i32,
+    pub relays_enabled: bool,
+    pub relay_reconnect_interval_m: i32,
+    pub start_browser: bool,
+    pub nat_enabled: bool,
+    pub ur_accepted: i32,
+    pub ur_seen: i32,
+    pub auto_upgrade_interval_h: i32,
+    pub upgrade_to_pre_releases: bool,
+    pub keep_temporaries_h: i32,
+    pub limit_bandwidth_in_lan: bool,
+    pub min_home_disk_free: Size,
+    pub always_local_nets: Vec<String>,
+    pub overwrite_remote_device_names_on_connect: bool,
+    pub max_folder_concurrency: i32,
+    pub crash_reporting_enabled: bool,
+    #[serde(rename = "announceLANAddresses")]
+    pub announce_lan_addresses: bool,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// GUI/REST listener settings (`/rest/config/gui`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GuiConfiguration {
+    pub enabled: bool,
+    pub address: String,
+    pub unix_socket_permissions: String,
+    pub user: String,
+    pub password: String,
+    pub auth_mode: String,
+    #[serde(rename = "useTLS")]
+    pub use_tls: bool,
+    pub api_key: String,
+    pub insecure_admin_access: bool,
+    pub theme: String,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Apply a partial JSON update to a typed config object.
+///
+/// Keys must already exist on the serialized object, so a misspelled
+/// camelCase key is rejected instead of being written into config.xml, and
+/// the merged result must still deserialize, which catches wrongly typed values.
+pub fn apply_update<T>(current: &T, updates: &Extra, kind: &str) -> Result<T, SyncthingError>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let serde_json::Value::Object(mut object) =
+        serde_json::to_value(current).map_err(|e| SyncthingError::ParseError(e.to_string()))?
+    else {
+        return Err(SyncthingError::ParseError(format!(
+            "{kind} configuration is not an object"
+        )));
+    };
+
+    for (key, value) in updates {
+        if !object.contains_key(key) {
+            return Err(SyncthingError::validation(
+                key.as_str(),
+                format!("Unknown {kind} setting"),
+            ));
+        }
+        object.insert(key.clone(), value.clone());
+    }
+
+    serde_json::from_value(serde_json::Value::Object(object))
+        .map_err(|e| SyncthingError::validation(kind, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn folder() -> FolderConfiguration {
+        FolderConfiguration::new("abcd-1234".into(), "Photos".into(), "/data/photos".into())
+    }
+
+    fn updates(value: serde_json::Value) -> Extra {
+        match value {
+            serde_json::Value::Object(map) => map,
+            _ => unreachable!("updates must be an object"),
+        }
+    }
+
+    fn invalid_field<T: std::fmt::Debug>(result: Result<T, SyncthingError>) -> String {
+        match result {
+            Err(SyncthingError::Validation { field, .. }) => field,
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn apply_update_changes_known_settings() {
+        let update = updates(json!({ "label": "Pictures", "rescanIntervalS": 60 }));
+        let updated = apply_update(&folder(), &update, "folder").unwrap();
+        assert_eq!(updated.label, "Pictures");
+        assert_eq!(updated.rescan_interval_s, 60);
+        assert_eq!(updated.path, "/data/photos");
+    }
+
+    #[test]
+    fn apply_update_rejects_unknown_settings() {
+        let update = updates(json!({ "label": "Pictures", "rescanIntervalSecs": 60 }));
+        let field = invalid_field(apply_update(&folder(), &update, "folder"));
+        assert_eq!(field, "rescanIntervalSecs");
+    }
+
+    #[test]
+    fn apply_update_accepts_settings_from_newer_syncthing_releases() {
+        let mut current = folder();
+        current.extra.insert("newSetting".into(), json!(false));
+        let update = updates(json!({ "newSetting": true }));
+        let updated = apply_update(&current, &update, "folder").unwrap();
+        assert_eq!(updated.extra["newSetting"], json!(true));
+    }
+
+    #[test]
+    fn apply_update_rejects_wrongly_typed_values() {
+        let update = updates(json!({ "fsWatcherEnabled": "yes" }));
+        let field = invalid_field(apply_update(&folder(), &update, "folder"));
+        assert_eq!(field, "folder");
+    }
+
+    #[test]
+    fn validate_checks_the_folder_type() {
+        let mut folder = folder();
+        for folder_type in FOLDER_TYPES {
+            folder.folder_type = (*folder_type).to_string();
+            assert!(folder.validate().is_ok(), "{folder_type}");
+        }
+        folder.folder_type = "sendrecieve".into();
+        assert_eq!(invalid_field(folder.validate()), "type");
+    }
+
+    #[test]
+    fn validate_rejects_passwords_on_a_receive_encrypted_folder() {
+        let mut folder = folder();
+        folder.share_encrypted_with("MFZWI3D", "secret");
+        assert!(folder.validate().is_ok());
+
+        folder.folder_type = "receiveencrypted".into();
+        let field = invalid_field(folder.validate());
+        assert_eq!(field, "encryptionPassword");
+
+        folder.share_encrypted_with("MFZWI3D", "");
+        assert!(folder.validate().is_ok());
+    }
+}
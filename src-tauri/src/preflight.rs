@@ -0,0 +1,460 @@
+//! Pre-flight checks for folder paths.
+//!
+//! Syncthing accepts any folder path and only reports "folder path missing"
+//! once it tries to scan it. These checks run before a folder is saved: hard
+//! problems fail with a validation error, softer ones come back as
+//! [`PathWarning`]s. They inspect the local filesystem, so they only mean
+//! something when Syncthing runs on this machine. Checking changes nothing;
+//! the directory and its marker are only created right before the folder is
+//! saved, and removed again if that fails.
+
+use crate::models::FolderConfiguration;
+use crate::SyncthingError;
+use serde::Serialize;
+use std::fs;
+use std::path::{Component, Path, PathBuf, Prefix};
+
+/// Filesystem types whose change notifications and locking are unreliable
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "afpfs",
+    "webdav",
+    "9p",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "fuse.sshfs",
+    "fuse.rclone",
+    "fuse.glusterfs",
+    "fuse.davfs2",
+];
+
+/// What a [`PathWarning`] is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PathWarningKind {
+    /// The path is on a network share
+    NetworkFilesystem,
+    /// Names differing only in case refer to the same file
+    CaseInsensitive,
+    /// Syncthing runs on another machine, so nothing was checked
+    NotChecked,
+}
+
+/// A problem with a folder path that does not stop the folder from being added
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathWarning {
+    pub kind: PathWarningKind,
+    pub message: String,
+}
+
+/// Outcome of preparing a folder path
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathCheck {
+    /// The path as checked and saved, with `~` expanded
+    pub path: String,
+    /// Whether the directory had to be created
+    pub created: bool,
+    pub warnings: Vec<PathWarning>,
+}
+
+impl PathCheck {
+    /// Report for a path on a remote Syncthing instance
+    pub fn unchecked(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            created: false,
+            warnings: vec![PathWarning {
+                kind: PathWarningKind::NotChecked,
+                message: "Syncthing runs on another machine, so the path was not checked".into(),
+            }],
+        }
+    }
+}
+
+/// Expand a leading `~` to the home directory, as Syncthing does
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            home.join(rest.trim_start_matches(['/', '\\']))
+        },
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Check `path` for a new folder without changing anything
+///
+/// `others` are the folders already configured. A missing directory is fine;
+/// [`create`] makes it once the folder is about to be saved. Writability is
+/// probed with a file that is removed straight away, in the nearest directory
+/// that exists.
+pub fn check(path: &str, others: &[FolderConfiguration]) -> Result<PathCheck, SyncthingError> {
+    let invalid = |message: String| SyncthingError::validation("path", message);
+
+    if path.trim().is_empty() {
+        return Err(invalid("must not be empty".into()));
+    }
+    let expanded = normalize(&expand_home(path));
+    if !expanded.is_absolute() {
+        return Err(invalid(format!("`{path}` is not an absolute path")));
+    }
+
+    let resolved = resolve(&expanded);
+    for other in others.iter().filter(|f| !f.path.is_empty()) {
+        let other_path = resolve(&normalize(&expand_home(&other.path)));
+        let name = if other.label.is_empty() {
+            &other.id
+        } else {
+            &other.label
+        };
+        if resolved == other_path {
+            return Err(invalid(format!("is already used by folder `{name}`")));
+        }
+        if resolved.starts_with(&other_path) {
+            return Err(invalid(format!(
+                "is inside folder `{name}` ({})",
+                other.path
+            )));
+        }
+        if other_path.starts_with(&resolved) {
+            return Err(invalid(format!(
+                "contains folder `{name}` ({})",
+                other.path
+            )));
+        }
+    }
+
+    if expanded.exists() && !expanded.is_dir() {
+        return Err(invalid(format!("`{path}` is not a directory")));
+    }
+    let Some(existing) = expanded.ancestors().find(|dir| dir.exists()) else {
+        return Err(invalid(format!("`{path}` is on no existing filesystem")));
+    };
+    if !existing.is_dir() {
+        return Err(invalid(format!(
+            "`{path}` cannot be created inside the file {}",
+            existing.display()
+        )));
+    }
+
+    let mut warnings = Vec::new();
+    if probe_case_insensitive(existing).map_err(|e| invalid(format!("is not writable: {e}")))? {
+        warnings.push(PathWarning {
+            kind: PathWarningKind::CaseInsensitive,
+            message: "The filesystem ignores case; files whose names differ only in case \
+                      on other devices will conflict"
+                .into(),
+        });
+    }
+    if let Some(fs_type) = network_filesystem(&resolved) {
+        warnings.push(PathWarning {
+            kind: PathWarningKind::NetworkFilesystem,
+            message: format!(
+                "The path is on a network filesystem ({fs_type}); change detection and \
+                 file locking may be unreliable"
+            ),
+        });
+    }
+
+    Ok(PathCheck {
+        path: expanded.to_string_lossy().into_owned(),
+        created: false,
+        warnings,
+    })
+}
+
+/// Directories [`create`] made, so they can be removed again
+#[derive(Debug, Default)]
+#[must_use = "what was created has to be removed if the folder is not saved"]
+pub struct Created {
+    /// Deepest first
+    dirs: Vec<PathBuf>,
+    marker: Option<PathBuf>,
+}
+
+impl Created {
+    /// Remove what was created; directories that are no longer empty stay
+    pub fn undo(self) {
+        for dir in self.marker.iter().chain(&self.dirs) {
+            if let Err(e) = fs::remove_dir(dir) {
+                log::warn!("Failed to remove {}: {e}", dir.display());
+            }
+        }
+    }
+}
+
+/// Create the directory of a checked path if missing, and the `marker`
+/// Syncthing looks for inside it
+///
+/// Records in `check` whether the directory had to be created. Nothing is
+/// left behind on failure.
+pub fn create(check: &mut PathCheck, marker: &str) -> Result<Created, SyncthingError> {
+    let invalid = |message: String| SyncthingError::validation("path", message);
+    let dir = Path::new(&check.path);
+
+    let mut created = Created {
+        dirs: dir
+            .ancestors()
+            .take_while(|dir| !dir.exists())
+            .map(Path::to_path_buf)
+            .collect(),
+        marker: None,
+    };
+    if let Err(e) = fs::create_dir_all(dir) {
+        created.undo();
+        return Err(invalid(format!("`{}` cannot be created: {e}", check.path)));
+    }
+
+    let marker_path = dir.join(marker);
+    if !marker_path.exists() {
+        if let Err(e) = fs::create_dir(&marker_path) {
+            created.undo();
+            return Err(invalid(format!("cannot create the `{marker}` marker: {e}")));
+        }
+        created.marker = Some(marker_path);
+    }
+
+    check.created = !created.dirs.is_empty();
+    Ok(created)
+}
+
+/// Remove `.` and `..` components without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                normalized.pop();
+            },
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Resolve symlinks in the longest existing prefix of `path`
+fn resolve(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+            return path.to_path_buf();
+        };
+        rest.push(name);
+        existing = parent;
+    }
+    let mut resolved = fs::canonicalize(existing).unwrap_or_else(|_| existing.to_path_buf());
+    resolved.extend(rest.iter().rev());
+    resolved
+}
+
+/// Write a lowercase probe file into `dir` and look for it under an uppercase name
+///
+/// Doubles as the writability check.
+fn probe_case_insensitive(dir: &Path) -> std::io::Result<bool> {
+    let name = format!(".eigen-probe-{}", uuid::Uuid::new_v4().simple());
+    let probe = dir.join(&name);
+    fs::write(&probe, b"")?;
+    let insensitive = dir.join(name.to_uppercase()).exists();
+    fs::remove_file(&probe)?;
+    Ok(insensitive)
+}
+
+/// Type of the network filesystem `path` is on, if any
+fn network_filesystem(path: &Path) -> Option<String> {
+    if let Some(Component::Prefix(prefix)) = path.components().next() {
+        if matches!(prefix.kind(), Prefix::UNC(..) | Prefix::VerbatimUNC(..)) {
+            return Some("SMB share".into());
+        }
+    }
+
+    // Mounts listed later shadow earlier ones at the same point
+    let (_, fs_type) = mount_table()
+        .into_iter()
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())?;
+    NETWORK_FILESYSTEMS
+        .contains(&fs_type.as_str())
+        .then_some(fs_type)
+}
+
+/// Mount points and their filesystem types
+#[cfg(target_os = "linux")]
+fn mount_table() -> Vec<(PathBuf, String)> {
+    let Ok(mounts) = fs::read_to_string("/proc/self/mounts") else {
+        return Vec::new();
+    };
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?;
+            let fs_type = fields.next()?;
+            Some((
+                PathBuf::from(unescape_mount(mount_point)),
+                fs_type.to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Mount points and their filesystem types
+#[cfg(target_os = "macos")]
+fn mount_table() -> Vec<(PathBuf, String)> {
+    let Ok(output) = std::process::Command::new("mount").output() else {
+        return Vec::new();
+    };
+    // e.g. `//user@nas/share on /Volumes/share (smbfs, nodev, nosuid)`
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (device, options) = line.rsplit_once(" (")?;
+            let (_, mount_point) = device.split_once(" on ")?;
+            let fs_type = options.split([',', ')']).next()?;
+            Some((PathBuf::from(mount_point), fs_type.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Mount points and their filesystem types
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn mount_table() -> Vec<(PathBuf, String)> {
+    Vec::new()
+}
+
+/// Undo the octal escapes (`\040` for a space) in `/proc/self/mounts`
+#[cfg(target_os = "linux")]
+fn unescape_mount(field: &str) -> String {
+    let mut bytes = Vec::with_capacity(field.len());
+    let mut rest = field.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = tail
+            .get(..3)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        if let (b'\\', Some(value)) = (byte, escaped) {
+            bytes.push(value);
+            rest = &tail[3..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder(id: &str, path: &Path) -> FolderConfiguration {
+        FolderConfiguration::new(id.into(), String::new(), path.to_string_lossy().into())
+    }
+
+    fn rejection(result: Result<PathCheck, SyncthingError>) -> String {
+        match result {
+            Err(SyncthingError::Validation { field, message }) if field == "path" => message,
+            other => panic!("expected the path to be rejected, got {other:?}"),
+        }
+    }
+
+    fn check_path(
+        path: &Path,
+        others: &[FolderConfiguration],
+    ) -> Result<PathCheck, SyncthingError> {
+        check(&path.to_string_lossy(), others)
+    }
+
+    #[test]
+    fn empty_and_relative_paths_are_rejected() {
+        assert!(rejection(check("  ", &[])).contains("empty"));
+        assert!(rejection(check("photos/2024", &[])).contains("not an absolute path"));
+    }
+
+    #[test]
+    fn nested_and_shared_paths_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let photos = dir.path().join("photos");
+        let others = [folder("photos", &photos)];
+
+        assert!(rejection(check_path(&photos, &others)).contains("already used"));
+        let inside = photos.join("2024");
+        assert!(rejection(check_path(&inside, &others)).contains("is inside folder `photos`"));
+        assert!(rejection(check_path(dir.path(), &others)).contains("contains folder `photos`"));
+        // Spelled differently, still the same place
+        let dotted = dir.path().join("music/../photos/.");
+        assert!(rejection(check_path(&dotted, &others)).contains("already used"));
+
+        assert!(check_path(&dir.path().join("photos-old"), &others).is_ok());
+    }
+
+    #[test]
+    fn files_are_not_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, b"").unwrap();
+        assert!(rejection(check_path(&file, &[])).contains("not a directory"));
+        assert!(rejection(check_path(&file.join("inner"), &[])).contains("inside the file"));
+    }
+
+    #[test]
+    fn checking_changes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("new/photos");
+        let check = check_path(&path, &[]).unwrap();
+
+        assert_eq!(Path::new(&check.path), path);
+        assert!(!check.created);
+        assert!(!dir.path().join("new").exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn create_makes_the_directory_and_marker_and_undo_removes_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("new/photos");
+        let mut check = check_path(&path, &[]).unwrap();
+
+        let created = create(&mut check, ".stfolder").unwrap();
+        assert!(check.created);
+        assert!(path.join(".stfolder").is_dir());
+
+        created.undo();
+        assert!(!dir.path().join("new").exists());
+        assert!(dir.path().exists());
+    }
+
+    #[test]
+    fn undo_leaves_an_existing_directory_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("keep.txt"), b"").unwrap();
+        let mut check = check_path(dir.path(), &[]).unwrap();
+
+        let created = create(&mut check, ".stfolder").unwrap();
+        assert!(!check.created);
+        created.undo();
+        assert!(!dir.path().join(".stfolder").exists());
+        assert!(dir.path().join("keep.txt").exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn mount_points_are_unescaped() {
+        assert_eq!(unescape_mount("/mnt/my\\040share"), "/mnt/my share");
+        assert_eq!(
+            unescape_mount("/mnt/tab\\011and\\134slash"),
+            "/mnt/tab\tand\\slash"
+        );
+        assert_eq!(unescape_mount("/mnt/plain"), "/mnt/plain");
+        assert_eq!(unescape_mount("/mnt/odd\\9x"), "/mnt/odd\\9x");
+        assert_eq!(unescape_mount("/mnt/end\\"), "/mnt/end\\");
+    }
+}
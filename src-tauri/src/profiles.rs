@@ -0,0 +1,336 @@
+//! Named Syncthing connection profiles.
+//!
+//! The built-in `local` profile is always derived from the local Syncthing
+//! install at startup. User-defined profiles (a NAS, a home server, ...) are
+//! persisted to `profiles.json` in the app config dir together with the name
+//! of the active profile.
+
+use crate::{SyncthingConfig, SyncthingError};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the built-in profile pointing at the local instance
+pub const LOCAL_PROFILE: &str = "local";
+
+const PROFILES_FILE: &str = "profiles.json";
+
+/// Connection settings for one Syncthing instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionProfile {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    /// Empty when editing a profile means keep the stored key
+    #[serde(default)]
+    pub api_key: String,
+    /// Connect over HTTPS
+    #[serde(default)]
+    pub use_tls: bool,
+    /// Pinned SHA-256 fingerprint of the instance's GUI certificate
+    #[serde(default)]
+    pub cert_fingerprint: Option<String>,
+}
+
+impl ConnectionProfile {
+    pub fn local(config: &SyncthingConfig) -> Self {
+        Self {
+            name: LOCAL_PROFILE.to_string(),
+            host: config.host.clone(),
+            port: config.port,
+            api_key: config.api_key.clone(),
+            use_tls: config.use_tls,
+            cert_fingerprint: config.cert_fingerprint.clone(),
+        }
+    }
+
+    pub fn to_config(&self) -> SyncthingConfig {
+        SyncthingConfig {
+            api_key: self.api_key.clone(),
+            port: self.port,
+            host: self.host.clone(),
+            use_tls: self.use_tls,
+            cert_fingerprint: self.cert_fingerprint.clone(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), SyncthingError> {
+        if self.name.trim().is_empty() {
+            return Err(SyncthingError::validation("name", "must not be empty"));
+        }
+        if self.host.trim().is_empty() {
+            return Err(SyncthingError::validation("host", "must not be empty"));
+        }
+        if self.port == 0 {
+            return Err(SyncthingError::validation("port", "must not be 0"));
+        }
+        match &self.cert_fingerprint {
+            Some(fingerprint) => {
+                crate::tls::parse_fingerprint(fingerprint)?;
+            },
+            None if self.use_tls => return Err(crate::tls::missing_fingerprint()),
+            None => {},
+        }
+        Ok(())
+    }
+}
+
+/// A profile as shown to the frontend (the API key never leaves the backend)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub use_tls: bool,
+    pub cert_fingerprint: Option<String>,
+    pub has_api_key: bool,
+    pub active: bool,
+    pub builtin: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfilesFile {
+    active: Option<String>,
+    profiles: Vec<ConnectionProfile>,
+}
+
+/// All known profiles plus which one is active
+#[derive(Debug)]
+pub struct ProfileStore {
+    path: Option<PathBuf>,
+    local: ConnectionProfile,
+    profiles: Vec<ConnectionProfile>,
+    active: String,
+}
+
+impl ProfileStore {
+    /// Load persisted profiles from `config_dir`, falling back to just the
+    /// local profile if the file is missing or unreadable
+    pub fn load(config_dir: Option<&Path>, local: ConnectionProfile) -> Self {
+        let path = config_dir.map(|dir| dir.join(PROFILES_FILE));
+        let file: ProfilesFile = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let mut store = Self {
+            path,
+            local,
+            profiles: file.profiles,
+            active: LOCAL_PROFILE.to_string(),
+        };
+        if let Some(active) = file.active {
+            if store.get(&active).is_some() {
+                store.active = active;
+            }
+        }
+        store
+    }
+
+    pub fn active(&self) -> &ConnectionProfile {
+        self.get(&self.active).unwrap_or(&self.local)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ConnectionProfile> {
+        if name == LOCAL_PROFILE {
+            return Some(&self.local);
+        }
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    pub fn list(&self) -> Vec<ProfileInfo> {
+        std::iter::once(&self.local)
+            .chain(&self.profiles)
+            .map(|p| ProfileInfo {
+                name: p.name.clone(),
+                host: p.host.clone(),
+                port: p.port,
+                use_tls: p.use_tls,
+                cert_fingerprint: p.cert_fingerprint.clone(),
+                has_api_key: !p.api_key.is_empty(),
+                active: p.name == self.active,
+                builtin: p.name == LOCAL_PROFILE,
+            })
+            .collect()
+    }
+
+    /// Validate `profile` for [`Self::upsert`], filling in the stored API
+    /// key when it has none
+    ///
+    /// The frontend never sees API keys, so an empty key keeps the one
+    /// already stored.
+    pub fn prepare(
+        &self,
+        mut profile: ConnectionProfile,
+    ) -> Result<ConnectionProfile, SyncthingError> {
+        profile.validate()?;
+        if profile.name == LOCAL_PROFILE {
+            return Err(SyncthingError::validation(
+                "name",
+                format!("`{LOCAL_PROFILE}` is reserved"),
+            ));
+        }
+        if profile.api_key.is_empty() {
+            if let Some(existing) = self.profiles.iter().find(|p| p.name == profile.name) {
+                profile.api_key = existing.api_key.clone();
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Add a new profile, or replace an existing user profile of the same name
+    pub fn upsert(&mut self, profile: ConnectionProfile) -> Result<(), SyncthingError> {
+        let profile = self.prepare(profile)?;
+        if let Some(existing) = self.profiles.iter_mut().find(|p| p.name == profile.name) {
+            *existing = profile;
+        } else {
+            self.profiles.push(profile);
+        }
+        self.save()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), SyncthingError> {
+        if name == LOCAL_PROFILE {
+            return Err(SyncthingError::validation(
+                "name",
+                "the local profile cannot be removed",
+            ));
+        }
+        let before = self.profiles.len();
+        self.profiles.retain(|p| p.name != name);
+        if self.profiles.len() == before {
+            return Err(SyncthingError::validation(
+                "name",
+                format!("profile `{name}` not found"),
+            ));
+        }
+        if self.active == name {
+            self.active = LOCAL_PROFILE.to_string();
+        }
+        self.save()
+    }
+
+    /// Like [`Self::get`], but a missing profile is an error
+    pub fn require(&self, name: &str) -> Result<&ConnectionProfile, SyncthingError> {
+        self.get(name).ok_or_else(|| {
+            SyncthingError::validation("name", format!("profile `{name}` not found"))
+        })
+    }
+
+    pub fn set_active(&mut self, name: &str) -> Result<&ConnectionProfile, SyncthingError> {
+        self.require(name)?;
+        self.active = name.to_string();
+        self.save()?;
+        Ok(self.active())
+    }
+
+    /// Use the local profile for this session without persisting the switch,
+    /// e.g. because the saved active profile cannot be connected to
+    pub fn fall_back_to_local(&mut self) -> &ConnectionProfile {
+        self.active = LOCAL_PROFILE.to_string();
+        &self.local
+    }
+
+    fn save(&self) -> Result<(), SyncthingError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| SyncthingError::filesystem(parent, e))?;
+        }
+
+        let file = ProfilesFile {
+            active: Some(self.active.clone()),
+            profiles: self.profiles.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
+        fs::write(path, json).map_err(|e| SyncthingError::filesystem(path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local() -> ConnectionProfile {
+        ConnectionProfile::local(&SyncthingConfig {
+            api_key: "local-key".into(),
+            port: 8384,
+            host: "127.0.0.1".into(),
+            use_tls: false,
+            cert_fingerprint: None,
+        })
+    }
+
+    fn profile(name: &str, api_key: &str) -> ConnectionProfile {
+        ConnectionProfile {
+            name: name.into(),
+            host: "nas.lan".into(),
+            port: 8384,
+            api_key: api_key.into(),
+            use_tls: false,
+            cert_fingerprint: None,
+        }
+    }
+
+    #[test]
+    fn upsert_without_api_key_keeps_the_stored_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = ProfileStore::load(Some(dir.path()), local());
+        store.upsert(profile("nas", "nas-key")).unwrap();
+
+        let mut edited = profile("nas", "");
+        edited.port = 8385;
+        store.upsert(edited).unwrap();
+
+        let reloaded = ProfileStore::load(Some(dir.path()), local());
+        let nas = reloaded.get("nas").unwrap();
+        assert_eq!(nas.port, 8385);
+        assert_eq!(nas.api_key, "nas-key");
+    }
+
+    #[test]
+    fn local_is_reserved() {
+        let mut store = ProfileStore::load(None, local());
+        assert!(store.upsert(profile(LOCAL_PROFILE, "key")).is_err());
+        assert!(store.remove(LOCAL_PROFILE).is_err());
+        assert_eq!(store.get(LOCAL_PROFILE).unwrap().api_key, "local-key");
+    }
+
+    #[test]
+    fn removing_the_active_profile_falls_back_to_local() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = ProfileStore::load(Some(dir.path()), local());
+        store.upsert(profile("nas", "nas-key")).unwrap();
+        store.set_active("nas").unwrap();
+        store.remove("nas").unwrap();
+        assert_eq!(store.active().name, LOCAL_PROFILE);
+
+        let reloaded = ProfileStore::load(Some(dir.path()), local());
+        assert_eq!(reloaded.active().name, LOCAL_PROFILE);
+    }
+
+    #[test]
+    fn load_ignores_an_unknown_active_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = ProfilesFile {
+            active: Some("gone".into()),
+            profiles: vec![profile("nas", "nas-key")],
+        };
+        fs::write(
+            dir.path().join(PROFILES_FILE),
+            serde_json::to_string(&file).unwrap(),
+        )
+        .unwrap();
+
+        let store = ProfileStore::load(Some(dir.path()), local());
+        assert_eq!(store.active().name, LOCAL_PROFILE);
+        assert!(store.get("nas").is_some());
+    }
+}
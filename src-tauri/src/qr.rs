@@ -0,0 +1,128 @@
+//! QR codes for device invitations.
+//!
+//! Encoding is left to the `qrcode` crate, at medium error correction; this
+//! module renders the result as a PNG for the desktop app and as half-block
+//! text for the CLI.
+
+use crate::SyncthingError;
+use qrcode::types::QrError;
+use qrcode::{Color, EcLevel};
+
+/// Modules of light margin the spec asks for around the symbol
+pub const QUIET_ZONE: usize = 4;
+
+/// A square grid of dark and light modules
+#[derive(Debug, Clone)]
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encode `text` in the smallest symbol that fits
+    pub fn encode(text: &str) -> Result<Self, SyncthingError> {
+        let code =
+            qrcode::QrCode::with_error_correction_level(text, EcLevel::M).map_err(|e| match e {
+                QrError::DataTooLong => {
+                    SyncthingError::validation("payload", "is too long for a QR code")
+                },
+                other => SyncthingError::validation("payload", other.to_string()),
+            })?;
+        Ok(Self {
+            size: code.width(),
+            modules: code
+                .into_colors()
+                .into_iter()
+                .map(|color| color == Color::Dark)
+                .collect(),
+        })
+    }
+
+    /// Modules per side, without the quiet zone
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the module at column `x`, row `y` is dark
+    ///
+    /// Coordinates outside the symbol, i.e. in the quiet zone, are light.
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        x < self.size && y < self.size && self.modules[y * self.size + x]
+    }
+
+    /// Render as a grayscale PNG with `scale` pixels per module
+    #[cfg(feature = "desktop")]
+    pub fn to_png(&self, scale: u32) -> Result<Vec<u8>, SyncthingError> {
+        let modules = u32::try_from(self.size + 2 * QUIET_ZONE)
+            .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
+        let image = image::GrayImage::from_fn(modules * scale, modules * scale, |x, y| {
+            let module = |pixel: u32| (pixel / scale) as usize;
+            let dark = module(x)
+                .checked_sub(QUIET_ZONE)
+                .zip(module(y).checked_sub(QUIET_ZONE))
+                .is_some_and(|(x, y)| self.is_dark(x, y));
+            image::Luma([if dark { 0 } else { 255 }])
+        });
+        let mut png = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| SyncthingError::ParseError(format!("Failed to encode PNG: {e}")))?;
+        Ok(png)
+    }
+
+    /// Render for a terminal, two rows per line using half blocks
+    pub fn to_text(&self) -> String {
+        let span = self.size + 2 * QUIET_ZONE;
+        let dark = |x: usize, y: usize| {
+            x.checked_sub(QUIET_ZONE)
+                .zip(y.checked_sub(QUIET_ZONE))
+                .is_some_and(|(x, y)| self.is_dark(x, y))
+        };
+        let mut text = String::new();
+        for y in (0..span).step_by(2) {
+            for x in 0..span {
+                // Light modules are drawn, so it also scans on dark terminals
+                text.push(match (dark(x, y), dark(x, y + 1)) {
+                    (false, false) => '█',
+                    (false, true) => '▀',
+                    (true, false) => '▄',
+                    (true, true) => ' ',
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_smallest_symbol() {
+        let qr = QrCode::encode("eigen://invite").unwrap();
+        assert_eq!(qr.size(), 21);
+        // Top-left finder pattern: dark ring, light ring, dark centre
+        assert!(qr.is_dark(0, 0) && !qr.is_dark(1, 1) && qr.is_dark(3, 3));
+        assert!(!qr.is_dark(qr.size(), 0));
+    }
+
+    #[test]
+    fn rejects_payloads_over_capacity() {
+        assert!(QrCode::encode(&"x".repeat(2331)).is_ok());
+        assert!(matches!(
+            QrCode::encode(&"x".repeat(2332)),
+            Err(SyncthingError::Validation { .. })
+        ));
+    }
+
+    #[test]
+    fn text_covers_the_quiet_zone() {
+        let qr = QrCode::encode("eigen://invite").unwrap();
+        let text = qr.to_text();
+        let span = qr.size() + 2 * QUIET_ZONE;
+        assert_eq!(text.lines().count(), (span + 1) / 2);
+        assert!(text.lines().all(|line| line.chars().count() == span));
+    }
+}
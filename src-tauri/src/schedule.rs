@@ -0,0 +1,253 @@
+//! Weekly time ranges shared by the bandwidth schedule and sync windows.
+//!
+//! Everything repeats every week in the local time zone, at minute
+//! resolution: a range covers some weekdays from `start` until `end`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+/// Minutes in a week, the period every schedule repeats with
+pub const MINUTES_PER_WEEK: u32 = 7 * MINUTES_PER_DAY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl Weekday {
+    const ALL: [Self; 7] = [
+        Self::Mon,
+        Self::Tue,
+        Self::Wed,
+        Self::Thu,
+        Self::Fri,
+        Self::Sat,
+        Self::Sun,
+    ];
+
+    /// Days since Monday
+    pub const fn index(self) -> u32 {
+        self as u32
+    }
+
+    const fn from_index(index: u32) -> Self {
+        Self::ALL[(index % 7) as usize]
+    }
+}
+
+/// Minutes since midnight, written `HH:MM`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay(u32);
+
+impl TimeOfDay {
+    pub const fn minutes(self) -> u32 {
+        self.0
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("`{value}` is not a time of day (HH:MM)");
+        let (hours, minutes) = value.split_once(':').ok_or_else(invalid)?;
+        let hours: u32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+        if hours >= 24 || minutes >= 60 {
+            return Err(invalid());
+        }
+        Ok(Self(hours * 60 + minutes))
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(time: TimeOfDay) -> Self {
+        time.to_string()
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.0 / 60, self.0 % 60)
+    }
+}
+
+/// A minute within the week, counted from Monday 00:00
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WeekMinute(u32);
+
+impl WeekMinute {
+    pub const fn new(day: Weekday, time: TimeOfDay) -> Self {
+        Self(day.index() * MINUTES_PER_DAY + time.minutes())
+    }
+
+    /// The current minute in the local time zone
+    pub fn now() -> Self {
+        use chrono::{Datelike, Timelike};
+        let now = chrono::Local::now();
+        Self(
+            now.weekday().num_days_from_monday() * MINUTES_PER_DAY + now.hour() * 60 + now.minute(),
+        )
+    }
+
+    pub const fn day(self) -> Weekday {
+        Weekday::from_index(self.0 / MINUTES_PER_DAY)
+    }
+
+    pub const fn time(self) -> TimeOfDay {
+        TimeOfDay(self.0 % MINUTES_PER_DAY)
+    }
+
+    /// `minutes` later, wrapping around at the end of the week
+    #[must_use]
+    pub const fn plus(self, minutes: u32) -> Self {
+        Self((self.0 + minutes) % MINUTES_PER_WEEK)
+    }
+}
+
+/// A time range repeating on some weekdays
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyRange {
+    /// Days the range starts on; empty means every day
+    #[serde(default)]
+    pub days: BTreeSet<Weekday>,
+    pub start: TimeOfDay,
+    /// Exclusive; an end at or before `start` runs past midnight
+    pub end: TimeOfDay,
+}
+
+impl WeeklyRange {
+    fn starts_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    pub fn covers(&self, at: WeekMinute) -> bool {
+        let (day, time) = (at.day(), at.time());
+        if self.start < self.end {
+            return self.starts_on(day) && self.start <= time && time < self.end;
+        }
+        // Runs past midnight, or around the clock when start == end
+        let yesterday = Weekday::from_index(day.index() + 6);
+        (self.starts_on(day) && time >= self.start)
+            || (self.starts_on(yesterday) && time < self.end)
+    }
+}
+
+/// Minutes from `at` until `state` changes, if it ever does
+pub fn minutes_until_change<T: PartialEq>(
+    at: WeekMinute,
+    state: impl Fn(WeekMinute) -> T,
+) -> Option<u32> {
+    let current = state(at);
+    (1..=MINUTES_PER_WEEK).find(|&m| state(at.plus(m)) != current)
+}
+
+/// Milliseconds since the Unix epoch at the start of the minute `minutes`
+/// from now
+pub fn epoch_millis_in(minutes: u32) -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let minute_start = now - now % 60_000;
+    u64::try_from(minute_start + u128::from(minutes) * 60_000).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Weekday::{Fri, Mon, Sat, Sun, Thu, Tue};
+
+    fn time(hhmm: &str) -> TimeOfDay {
+        TimeOfDay::try_from(hhmm.to_string()).unwrap()
+    }
+
+    fn range(days: &[Weekday], start: &str, end: &str) -> WeeklyRange {
+        WeeklyRange {
+            days: days.iter().copied().collect(),
+            start: time(start),
+            end: time(end),
+        }
+    }
+
+    fn at(day: Weekday, hhmm: &str) -> WeekMinute {
+        WeekMinute::new(day, time(hhmm))
+    }
+
+    #[test]
+    fn same_day_range_excludes_its_end() {
+        let office = range(&[Mon], "09:00", "17:00");
+        assert!(office.covers(at(Mon, "09:00")));
+        assert!(office.covers(at(Mon, "16:59")));
+        assert!(!office.covers(at(Mon, "17:00")));
+        assert!(!office.covers(at(Mon, "08:59")));
+        assert!(!office.covers(at(Tue, "10:00")));
+    }
+
+    #[test]
+    fn range_past_midnight_belongs_to_its_start_day() {
+        let night = range(&[Fri], "22:00", "06:00");
+        assert!(night.covers(at(Fri, "23:00")));
+        assert!(night.covers(at(Sat, "05:59")));
+        assert!(!night.covers(at(Sat, "06:00")));
+        assert!(!night.covers(at(Fri, "21:59")));
+        // The early hours of Friday belong to a Thursday night
+        assert!(!night.covers(at(Fri, "05:00")));
+        assert!(!night.covers(at(Thu, "23:00")));
+    }
+
+    #[test]
+    fn sunday_night_wraps_into_monday() {
+        let night = range(&[Sun], "22:00", "02:00");
+        assert!(night.covers(at(Sun, "23:30")));
+        assert!(night.covers(at(Mon, "00:00")));
+        assert!(night.covers(at(Mon, "01:59")));
+        assert!(!night.covers(at(Mon, "02:00")));
+        assert!(!night.covers(at(Sun, "01:00")));
+    }
+
+    #[test]
+    fn equal_start_and_end_cover_a_whole_day() {
+        let saturday = range(&[Sat], "08:00", "08:00");
+        assert!(saturday.covers(at(Sat, "08:00")));
+        assert!(saturday.covers(at(Sun, "07:59")));
+        assert!(!saturday.covers(at(Sun, "08:00")));
+        assert!(!saturday.covers(at(Sat, "07:59")));
+
+        let always = range(&[], "00:00", "00:00");
+        assert!(always.covers(at(Mon, "00:00")));
+        assert!(always.covers(at(Sun, "23:59")));
+    }
+
+    #[test]
+    fn minutes_until_change_wraps_around_the_week() {
+        let night = range(&[Sun], "22:00", "02:00");
+        let covered = |at| night.covers(at);
+        assert_eq!(minutes_until_change(at(Sun, "21:00"), covered), Some(60));
+        assert_eq!(minutes_until_change(at(Sun, "23:00"), covered), Some(180));
+        assert_eq!(minutes_until_change(at(Tue, "00:00"), |_| true), None);
+    }
+
+    #[test]
+    fn time_of_day_rejects_out_of_range_values() {
+        for invalid in ["24:00", "12:60", "noon", "12"] {
+            assert!(
+                TimeOfDay::try_from(invalid.to_string()).is_err(),
+                "{invalid}"
+            );
+        }
+        assert_eq!(time("07:05").to_string(), "07:05");
+    }
+}
@@ -0,0 +1,173 @@
+//! Background tasks that apply the bandwidth schedule and sync windows.
+//!
+//! The limits of the bandwidth rule in effect are written at every rule
+//! boundary, when the schedule is saved, when the active profile changes and
+//! whenever Syncthing has restarted (noticed through a new `startTime`),
+//! which is checked once a minute. The active rule is shown in the tray
+//! tooltip. Turning the schedule off writes the default limits one last
+//! time, so the limits of the rule that was in effect do not linger.
+//!
+//! Folders and devices with sync windows are paused and resumed at every
+//! window boundary. They are also checked once a minute, which picks up
+//! pauses and resumes made outside the app.
+
+use crate::bandwidth::BandwidthLimits;
+use crate::core::bandwidth::{apply_bandwidth_limits, bandwidth_status};
+use crate::core::sync_windows::apply_sync_windows;
+use crate::core::system::get_system_status;
+use crate::schedule::{epoch_millis_in, WeekMinute};
+use crate::{tray, SyncthingError, SyncthingState};
+use std::collections::BTreeMap;
+use std::sync::PoisonError;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+/// How often a running Syncthing is checked for restarts
+const HEARTBEAT: Duration = Duration::from_secs(60);
+
+/// Start the bandwidth schedule and sync window tasks
+pub fn spawn(app: &AppHandle) {
+    tauri::async_runtime::spawn(run_bandwidth(app.clone()));
+    tauri::async_runtime::spawn(run_sync_windows(app.clone()));
+}
+
+async fn run_bandwidth(app: AppHandle) {
+    let state = app.state::<SyncthingState>();
+    let mut profile_changes = state.subscribe_profile_changes();
+    let mut schedule_changes = state.subscribe_schedule_changes();
+    // The limits last written and the Syncthing run they were written to
+    let mut applied: Option<(BandwidthLimits, String)> = None;
+    // Whether limits may have been written since the schedule was last off
+    let mut was_enabled = false;
+
+    loop {
+        let schedule = state
+            .bandwidth
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .schedule()
+            .clone();
+
+        let wait = if schedule.enabled {
+            was_enabled = true;
+            let status = bandwidth_status(&schedule);
+            tray::set_bandwidth(&app, Some(status.summary()));
+
+            let ctx = state.context();
+            match get_system_status(&ctx).await {
+                Ok(system) => {
+                    let started = system["startTime"].as_str().unwrap_or_default().to_string();
+                    let target = (status.limits, started);
+                    if applied.as_ref() != Some(&target) {
+                        match apply_bandwidth_limits(&ctx, &target.0).await {
+                            Ok(_) => applied = Some(target),
+                            Err(e) => log::warn!("Failed to apply bandwidth limits: {e}"),
+                        }
+                    }
+                },
+                // Not running; limits are applied once it is back
+                Err(_) => applied = None,
+            }
+            Some(
+                status
+                    .next_change
+                    .map_or(HEARTBEAT, |at| until(at).min(HEARTBEAT)),
+            )
+        } else {
+            tray::set_bandwidth(&app, None);
+            applied = None;
+            if was_enabled {
+                match apply_bandwidth_limits(&state.context(), &schedule.resolve(None)).await {
+                    Ok(_) => was_enabled = false,
+                    // Not running; retried on the next heartbeat
+                    Err(SyncthingError::NotRunning { .. }) => {},
+                    Err(e) => {
+                        log::warn!("Failed to restore default bandwidth limits: {e}");
+                        was_enabled = false;
+                    },
+                }
+            }
+            was_enabled.then_some(HEARTBEAT)
+        };
+
+        tokio::select! {
+            () = sleep(wait) => {},
+            _ = profile_changes.changed() => applied = None,
+            _ = schedule_changes.changed() => applied = None,
+        }
+    }
+}
+
+async fn run_sync_windows(app: AppHandle) {
+    let state = app.state::<SyncthingState>();
+    let mut profile_changes = state.subscribe_profile_changes();
+    let mut schedule_changes = state.subscribe_schedule_changes();
+    // Whether each target's window was open when last applied
+    let mut last_open = BTreeMap::new();
+
+    loop {
+        let ctx = state.context();
+        let (windows, before) = {
+            let mut store = state
+                .sync_windows
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            (
+                store.windows().clone(),
+                store.scheduled_pauses(&ctx.profile),
+            )
+        };
+
+        // Keep going while disabled until every scheduled pause is lifted
+        let wait = if windows.enabled || !before.is_empty() {
+            let at = WeekMinute::now();
+            let mut after = before.clone();
+            match apply_sync_windows(&ctx, &windows, at, &mut after, &mut last_open).await {
+                // Not running; windows are applied once it is back
+                Ok(()) | Err(SyncthingError::NotRunning { .. }) => {},
+                Err(e) => log::warn!("Failed to apply sync windows: {e}"),
+            }
+            let recorded = state
+                .sync_windows
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .update_scheduled_pauses(&ctx.profile, &before, &after);
+            if let Err(e) = recorded {
+                log::warn!("Failed to record scheduled pauses: {e}");
+            }
+
+            let next_change = windows
+                .targets()
+                .filter_map(|target| windows.minutes_until_change(&target, at))
+                .min();
+            Some(next_change.map_or(HEARTBEAT, |minutes| {
+                until(epoch_millis_in(minutes)).min(HEARTBEAT)
+            }))
+        } else {
+            last_open.clear();
+            None
+        };
+
+        tokio::select! {
+            () = sleep(wait) => {},
+            _ = profile_changes.changed() => last_open.clear(),
+            _ = schedule_changes.changed() => {},
+        }
+    }
+}
+
+/// Time until `at`, in milliseconds since the Unix epoch
+fn until(at: u64) -> Duration {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Duration::from_millis(at).saturating_sub(now)
+}
+
+/// Sleep for `duration`, or forever without one
+async fn sleep(duration: Option<Duration>) {
+    match duration {
+        Some(duration) => tokio::time::sleep(duration).await,
+        None => std::future::pending().await,
+    }
+}
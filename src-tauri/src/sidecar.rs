@@ -0,0 +1,399 @@
+//! Supervisor for the bundled Syncthing sidecar.
+//!
+//! The supervisor task owns the sidecar's event stream: stdout/stderr lines go
+//! into a bounded ring buffer, readiness is detected by pinging the local REST
+//! API, and an unexpected exit is restarted with exponential backoff until a
+//! crash loop is detected. Every transition is emitted as `sidecar-status` so
+//! the frontend can tell a crashed sidecar from a network blip.
+
+use crate::client::SyncthingClient;
+use crate::{SyncthingError, SyncthingState};
+use reqwest::Method;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::async_runtime::Receiver;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent, TerminatedPayload};
+use tauri_plugin_shell::ShellExt;
+use tokio::sync::watch;
+
+/// Emitted on every lifecycle transition of the sidecar
+pub const SIDECAR_STATUS: &str = "sidecar-status";
+
+/// Output lines kept for `get_sidecar_output`
+const OUTPUT_CAPACITY: usize = 2000;
+const READY_PROBE_INTERVAL: Duration = Duration::from_millis(500);
+const READY_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A run lasting this long is considered healthy and resets the backoff
+const STABLE_RUN: Duration = Duration::from_secs(60);
+/// More than `MAX_CRASHES` crashes within `CRASH_WINDOW` is a crash loop
+const CRASH_WINDOW: Duration = Duration::from_secs(300);
+const MAX_CRASHES: usize = 5;
+
+/// Syncthing exit codes that are not crashes
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_RESTART: i32 = 3;
+const EXIT_UPGRADING: i32 = 4;
+
+/// Lifecycle state reported in `sidecar-status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidecarState {
+    Starting,
+    Ready,
+    Crashed,
+    Stopped,
+}
+
+/// Payload of `sidecar-status`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarStatus {
+    pub state: SidecarState,
+    /// Restarts since the sidecar was last started by the user
+    pub restarts: u32,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    /// Delay before the next restart attempt, if one is scheduled
+    pub restart_in_ms: Option<u64>,
+    pub message: Option<String>,
+}
+
+impl SidecarStatus {
+    const fn new(state: SidecarState, restarts: u32) -> Self {
+        Self {
+            state,
+            restarts,
+            exit_code: None,
+            signal: None,
+            restart_in_ms: None,
+            message: None,
+        }
+    }
+}
+
+/// One line of sidecar output
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputLine {
+    /// Monotonic sequence number, for fetching only newer lines
+    pub seq: u64,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    /// `stdout`, `stderr` or `supervisor`
+    pub stream: &'static str,
+    pub line: String,
+}
+
+#[derive(Debug, Default)]
+struct OutputBuffer {
+    lines: VecDeque<OutputLine>,
+    next_seq: u64,
+}
+
+/// Sidecar process handle, output buffer and desired run state
+pub struct Sidecar {
+    child: Mutex<Option<CommandChild>>,
+    output: Mutex<OutputBuffer>,
+    /// `Some(generation)` while the user wants the sidecar running; each start
+    /// gets a new generation so a stale supervisor can tell it was replaced
+    wanted: watch::Sender<Option<u64>>,
+    next_generation: AtomicU64,
+}
+
+impl Default for Sidecar {
+    fn default() -> Self {
+        Self {
+            child: Mutex::new(None),
+            output: Mutex::new(OutputBuffer::default()),
+            wanted: watch::channel(None).0,
+            next_generation: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Sidecar {
+    /// Whether a supervisor is currently keeping the sidecar alive
+    pub fn is_running(&self) -> bool {
+        self.wanted.borrow().is_some()
+    }
+
+    /// Stop supervising and kill the process; returns whether it was running
+    pub fn stop(&self) -> Result<bool, SyncthingError> {
+        let was_running = self.wanted.send_replace(None).is_some();
+        let child = self
+            .child
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(child) = child {
+            child.kill().map_err(|e| {
+                SyncthingError::ProcessError(format!("Failed to kill sidecar process: {e}"))
+            })?;
+        }
+        Ok(was_running)
+    }
+
+    /// Buffered output lines with a sequence number greater than `since`
+    pub fn output(&self, since: Option<u64>) -> Vec<OutputLine> {
+        let buffer = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+        buffer
+            .lines
+            .iter()
+            .filter(|line| since.map_or(true, |since| line.seq > since))
+            .cloned()
+            .collect()
+    }
+
+    fn push_output(&self, stream: &'static str, bytes: &[u8]) {
+        let line = String::from_utf8_lossy(bytes).trim_end().to_string();
+        if line.is_empty() {
+            return;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+
+        let mut buffer = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+        let seq = buffer.next_seq;
+        buffer.next_seq += 1;
+        if buffer.lines.len() == OUTPUT_CAPACITY {
+            buffer.lines.pop_front();
+        }
+        buffer.lines.push_back(OutputLine {
+            seq,
+            timestamp,
+            stream,
+            line,
+        });
+    }
+
+    fn is_current(&self, generation: u64) -> bool {
+        *self.wanted.borrow() == Some(generation)
+    }
+
+    /// Stop wanting the sidecar, unless a newer start has already replaced `generation`
+    fn give_up(&self, generation: u64) {
+        self.wanted.send_if_modified(|wanted| {
+            let current = *wanted == Some(generation);
+            if current {
+                *wanted = None;
+            }
+            current
+        });
+    }
+}
+
+/// Start the sidecar under supervision; returns `false` if it was already running
+pub fn start(app: &AppHandle) -> bool {
+    let state = app.state::<SyncthingState>();
+    let mut generation = None;
+    state.sidecar.wanted.send_if_modified(|wanted| {
+        if wanted.is_some() {
+            return false;
+        }
+        generation = Some(
+            state
+                .sidecar
+                .next_generation
+                .fetch_add(1, Ordering::Relaxed),
+        );
+        *wanted = generation;
+        true
+    });
+
+    generation.is_some_and(|generation| {
+        tauri::async_runtime::spawn(supervise(app.clone(), generation));
+        true
+    })
+}
+
+async fn supervise(app: AppHandle, generation: u64) {
+    let state = app.state::<SyncthingState>();
+    let sidecar = &state.sidecar;
+    let mut wanted = sidecar.wanted.subscribe();
+    let probe = SyncthingClient::new(&state.local_config).ok();
+
+    let mut backoff = INITIAL_BACKOFF;
+    let mut crashes: VecDeque<Instant> = VecDeque::new();
+    let mut restarts = 0;
+
+    while sidecar.is_current(generation) {
+        emit_status(&app, SidecarStatus::new(SidecarState::Starting, restarts));
+        let started = Instant::now();
+
+        let exit = match spawn(&app, &state) {
+            Ok((rx, child)) => {
+                // Stopped (or replaced) while spawning: don't leave it orphaned
+                if !sidecar.is_current(generation) {
+                    let _ = child.kill();
+                    break;
+                }
+                *sidecar.child.lock().unwrap_or_else(PoisonError::into_inner) = Some(child);
+                monitor(&app, sidecar, rx, probe.as_ref(), restarts).await
+            },
+            Err(e) => {
+                sidecar.push_output("supervisor", e.to_string().as_bytes());
+                Err(e.to_string())
+            },
+        };
+
+        if !sidecar.is_current(generation) {
+            break;
+        }
+        sidecar
+            .child
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+
+        // Syncthing exits instead of restarting itself because of `-no-restart`
+        match exit
+            .as_ref()
+            .ok()
+            .and_then(|p| p.as_ref())
+            .and_then(|p| p.code)
+        {
+            Some(EXIT_SUCCESS) => {
+                // Shut down through the API: respect it
+                sidecar.give_up(generation);
+                break;
+            },
+            Some(EXIT_RESTART | EXIT_UPGRADING) => {
+                restarts += 1;
+                continue;
+            },
+            _ => {},
+        }
+
+        // A healthy run forgets earlier crashes
+        let now = Instant::now();
+        if now.duration_since(started) >= STABLE_RUN {
+            backoff = INITIAL_BACKOFF;
+            crashes.clear();
+        }
+        crashes.push_back(now);
+        while crashes
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > CRASH_WINDOW)
+        {
+            crashes.pop_front();
+        }
+
+        let mut status = SidecarStatus::new(SidecarState::Crashed, restarts);
+        match exit {
+            Ok(payload) => {
+                status.exit_code = payload.as_ref().and_then(|p| p.code);
+                status.signal = payload.as_ref().and_then(|p| p.signal);
+            },
+            Err(message) => status.message = Some(message),
+        }
+
+        if crashes.len() > MAX_CRASHES {
+            status.message = Some(format!(
+                "Syncthing crashed {} times within {} minutes; not restarting",
+                crashes.len(),
+                CRASH_WINDOW.as_secs() / 60
+            ));
+            sidecar.push_output(
+                "supervisor",
+                status.message.as_deref().unwrap_or_default().as_bytes(),
+            );
+            sidecar.give_up(generation);
+            emit_status(&app, status);
+            return;
+        }
+
+        status.restart_in_ms = Some(u64::try_from(backoff.as_millis()).unwrap_or(u64::MAX));
+        emit_status(&app, status);
+
+        // Sleep out the backoff unless the user stops or restarts meanwhile
+        let stopped = tokio::select! {
+            () = tokio::time::sleep(backoff) => false,
+            _ = wanted.wait_for(|wanted| *wanted != Some(generation)) => true,
+        };
+        if stopped {
+            break;
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+        restarts += 1;
+    }
+
+    // Only report a stop if no newer supervisor has taken over
+    if sidecar.wanted.borrow().is_none() {
+        emit_status(&app, SidecarStatus::new(SidecarState::Stopped, restarts));
+    }
+}
+
+fn spawn(
+    app: &AppHandle,
+    state: &SyncthingState,
+) -> Result<(Receiver<CommandEvent>, CommandChild), SyncthingError> {
+    let config = &state.local_config;
+    let mut args = vec![
+        "-no-browser".to_string(),
+        "-no-restart".to_string(),
+        format!("-gui-apikey={}", config.api_key),
+        format!("-gui-address={}:{}", config.host, config.port),
+    ];
+    // Run against the config.xml `local_config` was read from
+    if let Some(home) = &state.discovery.home {
+        args.push(format!("-home={}", home.display()));
+    }
+    app.shell()
+        .sidecar("syncthing")
+        .map_err(|e| {
+            SyncthingError::ProcessError(format!("Failed to create sidecar command: {e}"))
+        })?
+        .args(args)
+        .spawn()
+        .map_err(|e| {
+            SyncthingError::ProcessError(format!("Failed to spawn syncthing sidecar: {e}"))
+        })
+}
+
+/// Drain the process's events until it exits, reporting `ready` once the REST
+/// API answers. Returns the termination payload, if one was received.
+async fn monitor(
+    app: &AppHandle,
+    sidecar: &Sidecar,
+    mut rx: Receiver<CommandEvent>,
+    probe: Option<&SyncthingClient>,
+    restarts: u32,
+) -> Result<Option<TerminatedPayload>, String> {
+    let mut ready = probe.is_none();
+    let mut probe_timer = tokio::time::interval(READY_PROBE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Some(CommandEvent::Stdout(bytes)) => sidecar.push_output("stdout", &bytes),
+                Some(CommandEvent::Stderr(bytes)) => sidecar.push_output("stderr", &bytes),
+                Some(CommandEvent::Error(e)) => sidecar.push_output("supervisor", e.as_bytes()),
+                Some(CommandEvent::Terminated(payload)) => return Ok(Some(payload)),
+                None => return Ok(None),
+                Some(_) => {},
+            },
+            _ = probe_timer.tick(), if !ready => {
+                if let Some(client) = probe {
+                    let request = client
+                        .request(Method::GET, "/rest/system/ping")
+                        .timeout(READY_PROBE_TIMEOUT);
+                    if client.execute(request).await.is_ok() {
+                        ready = true;
+                        emit_status(app, SidecarStatus::new(SidecarState::Ready, restarts));
+                    }
+                }
+            },
+        }
+    }
+}
+
+fn emit_status(app: &AppHandle, status: SidecarStatus) {
+    let _ = app.emit(SIDECAR_STATUS, status);
+}
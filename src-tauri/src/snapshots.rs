@@ -0,0 +1,236 @@
+//! Automatic snapshots of the Syncthing configuration.
+//!
+//! Every mutating command first saves the live `/rest/config` to
+//! `snapshots/<profile>/` in the app data dir, keeping the newest
+//! [`DEFAULT_RETENTION`] per profile, so a bad write can be inspected and
+//! rolled back. The GUI API key and password are left out of every snapshot.
+
+use crate::models::Configuration;
+use crate::SyncthingError;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Snapshots kept per profile before the oldest are deleted
+pub const DEFAULT_RETENTION: usize = 50;
+
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// A stored snapshot without its configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    pub id: String,
+    /// Milliseconds since the Unix epoch
+    pub created_at: u64,
+    /// The command that was about to change the configuration
+    pub reason: String,
+    pub profile: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotFile {
+    #[serde(flatten)]
+    info: SnapshotInfo,
+    config: Configuration,
+}
+
+/// Snapshot directory layout and retention
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    root: Option<PathBuf>,
+    retention: usize,
+}
+
+impl SnapshotStore {
+    /// Store snapshots under `data_dir`; without one, snapshots are disabled
+    pub fn new(data_dir: Option<&Path>) -> Self {
+        Self {
+            root: data_dir.map(|dir| dir.join(SNAPSHOTS_DIR)),
+            retention: DEFAULT_RETENTION,
+        }
+    }
+
+    /// Save `config` for `profile` and prune old snapshots
+    pub fn save(
+        &self,
+        profile: &str,
+        reason: &str,
+        mut config: Configuration,
+    ) -> Result<Option<SnapshotInfo>, SyncthingError> {
+        let Some(dir) = self.profile_dir(profile) else {
+            return Ok(None);
+        };
+        fs::create_dir_all(&dir).map_err(|e| SyncthingError::filesystem(&dir, e))?;
+
+        // Credentials have no business sitting around in plain text
+        config.gui.api_key.clear();
+        config.gui.password.clear();
+
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+        let (id, path, mut file) =
+            create_unique(&dir, &format!("{created_at}-{}", sanitize(reason)))?;
+        let info = SnapshotInfo {
+            id,
+            created_at,
+            reason: reason.to_string(),
+            profile: profile.to_string(),
+        };
+
+        let json = serde_json::to_string_pretty(&SnapshotFile {
+            info: info.clone(),
+            config,
+        })
+        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
+        file.write_all(json.as_bytes())
+            .map_err(|e| SyncthingError::filesystem(&path, e))?;
+
+        self.prune(&dir);
+        Ok(Some(info))
+    }
+
+    /// Snapshots of `profile`, newest first
+    pub fn list(&self, profile: &str) -> Vec<SnapshotInfo> {
+        let mut snapshots: Vec<SnapshotInfo> = self
+            .files(profile)
+            .iter()
+            .filter_map(|path| read(path).ok())
+            .map(|file| file.info)
+            .collect();
+        snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+        snapshots
+    }
+
+    /// The configuration stored in snapshot `id` of `profile`
+    pub fn load(&self, profile: &str, id: &str) -> Result<Configuration, SyncthingError> {
+        let path = self
+            .profile_dir(profile)
+            .filter(|_| sanitize(id) == id)
+            .map(|dir| dir.join(format!("{id}.json")))
+            .filter(|path| path.is_file())
+            .ok_or_else(|| {
+                SyncthingError::validation("id", format!("snapshot `{id}` not found"))
+            })?;
+        read(&path).map(|file| file.config)
+    }
+
+    fn profile_dir(&self, profile: &str) -> Option<PathBuf> {
+        self.root.as_ref().map(|root| root.join(sanitize(profile)))
+    }
+
+    fn files(&self, profile: &str) -> Vec<PathBuf> {
+        let Some(dir) = self.profile_dir(profile) else {
+            return Vec::new();
+        };
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Delete all but the newest `retention` snapshots in `dir`
+    fn prune(&self, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        // IDs start with the creation time
+        let mut names: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        names.sort_by_key(|path| created_at(path));
+
+        let excess = names.len().saturating_sub(self.retention);
+        for path in names.into_iter().take(excess) {
+            if let Err(e) = fs::remove_file(&path) {
+                log::warn!("Failed to prune snapshot {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+/// Create a new snapshot file named after `base`, adding a counter if
+/// another snapshot was saved in the same millisecond for the same reason
+fn create_unique(dir: &Path, base: &str) -> Result<(String, PathBuf, fs::File), SyncthingError> {
+    let mut id = base.to_string();
+    for n in 1.. {
+        let path = dir.join(format!("{id}.json"));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((id, path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => id = format!("{base}-{n}"),
+            Err(e) => return Err(SyncthingError::filesystem(&path, e)),
+        }
+    }
+    unreachable!("ran out of snapshot IDs")
+}
+
+fn read(path: &Path) -> Result<SnapshotFile, SyncthingError> {
+    let content = fs::read_to_string(path).map_err(|e| SyncthingError::filesystem(path, e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| SyncthingError::ParseError(format!("{}: {e}", path.display())))
+}
+
+fn created_at(path: &Path) -> u64 {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.split('-').next())
+        .and_then(|millis| millis.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Keep file names portable
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_get_unique_ids_and_no_credentials() {
+        let data_dir = std::env::temp_dir().join(format!("eigen-snapshots-{}", std::process::id()));
+        let store = SnapshotStore::new(Some(&data_dir));
+        let mut config = Configuration::default();
+        config.gui.api_key = "secret-key".into();
+        config.gui.password = "$2a$10$hash".into();
+
+        let ids: Vec<String> = (0..3)
+            .map(|_| {
+                store
+                    .save("local", "add_folder", config.clone())
+                    .unwrap()
+                    .unwrap()
+                    .id
+            })
+            .collect();
+        let stored = store.load("local", &ids[2]).unwrap();
+        fs::remove_dir_all(&data_dir).unwrap();
+
+        assert_ne!(ids[0], ids[1]);
+        assert_ne!(ids[1], ids[2]);
+        assert_ne!(ids[0], ids[2]);
+        assert!(stored.gui.api_key.is_empty());
+        assert!(stored.gui.password.is_empty());
+    }
+}
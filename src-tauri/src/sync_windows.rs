@@ -0,0 +1,305 @@
+//! Sync windows for folders and devices.
+//!
+//! A folder or device with windows only syncs inside them, e.g. a media
+//! folder overnight or a device on a metered hotspot at weekends. The desktop
+//! app's scheduler pauses it when a window closes and resumes it when the
+//! next one opens. Every pause the scheduler makes is recorded per profile,
+//! so it only ever resumes what it paused itself: anything paused by hand
+//! stays paused. Windows are persisted to `sync-windows.json` and the
+//! recorded pauses to `sync-window-pauses.json` in the app config dir. The
+//! pauses file is re-read before every use, since `eigen-cli` forgets pauses
+//! while the desktop app is running.
+
+use crate::device_id;
+use crate::schedule::{self, WeekMinute, WeeklyRange};
+use crate::SyncthingError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const WINDOWS_FILE: &str = "sync-windows.json";
+const PAUSES_FILE: &str = "sync-window-pauses.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetKind {
+    Folder,
+    Device,
+}
+
+/// A folder or device a window applies to
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Target {
+    pub kind: TargetKind,
+    /// Folder ID, or device ID in canonical form
+    pub id: String,
+}
+
+impl Target {
+    pub fn folder(folder_id: impl Into<String>) -> Self {
+        Self {
+            kind: TargetKind::Folder,
+            id: folder_id.into(),
+        }
+    }
+
+    pub fn device(device_id: impl Into<String>) -> Self {
+        Self {
+            kind: TargetKind::Device,
+            id: device_id.into(),
+        }
+    }
+}
+
+/// Why a folder or device is paused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PauseReason {
+    /// By the user, here or anywhere else; never resumed by the scheduler
+    Manual,
+    /// By the scheduler, outside the target's windows
+    Scheduled,
+}
+
+/// The persisted windows
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SyncWindows {
+    pub enabled: bool,
+    /// Keyed by folder ID
+    pub folders: BTreeMap<String, Vec<WeeklyRange>>,
+    /// Keyed by device ID
+    pub devices: BTreeMap<String, Vec<WeeklyRange>>,
+}
+
+impl SyncWindows {
+    /// Check every target has windows, and bring device IDs into canonical
+    /// form
+    pub fn validate(&mut self) -> Result<(), SyncthingError> {
+        for (folder_id, windows) in &self.folders {
+            if folder_id.trim().is_empty() {
+                return Err(SyncthingError::validation(
+                    "folders",
+                    "folder ID must not be empty",
+                ));
+            }
+            if windows.is_empty() {
+                return Err(no_windows("folders", folder_id));
+            }
+        }
+        self.devices = std::mem::take(&mut self.devices)
+            .into_iter()
+            .map(|(id, windows)| {
+                if windows.is_empty() {
+                    return Err(no_windows("devices", &id));
+                }
+                Ok((device_id::normalize(&id)?, windows))
+            })
+            .collect::<Result<_, SyncthingError>>()?;
+        Ok(())
+    }
+
+    /// Every folder and device that has windows
+    pub fn targets(&self) -> impl Iterator<Item = Target> + '_ {
+        let folders = self.folders.keys().map(Target::folder);
+        let devices = self.devices.keys().map(Target::device);
+        folders.chain(devices)
+    }
+
+    fn windows(&self, target: &Target) -> Option<&[WeeklyRange]> {
+        match target.kind {
+            TargetKind::Folder => self.folders.get(&target.id),
+            TargetKind::Device => self.devices.get(&target.id),
+        }
+        .map(Vec::as_slice)
+    }
+
+    /// Whether `target` may sync at `at`; always true while disabled and for
+    /// targets without windows
+    pub fn is_open(&self, target: &Target, at: WeekMinute) -> bool {
+        if !self.enabled {
+            return true;
+        }
+        self.windows(target)
+            .map_or(true, |windows| windows.iter().any(|w| w.covers(at)))
+    }
+
+    /// Minutes from `at` until a window of `target` opens or closes
+    pub fn minutes_until_change(&self, target: &Target, at: WeekMinute) -> Option<u32> {
+        if !self.enabled {
+            return None;
+        }
+        schedule::minutes_until_change(at, |at| self.is_open(target, at))
+    }
+}
+
+fn no_windows(field: &str, id: &str) -> SyncthingError {
+    SyncthingError::validation(field, format!("`{id}` needs at least one window"))
+}
+
+/// The windows, the pauses the scheduler made and where both are persisted
+#[derive(Debug)]
+pub struct SyncWindowStore {
+    path: Option<PathBuf>,
+    pauses_path: Option<PathBuf>,
+    windows: SyncWindows,
+    /// Targets paused by the scheduler, keyed by profile name
+    pauses: BTreeMap<String, BTreeSet<Target>>,
+}
+
+impl SyncWindowStore {
+    /// Load windows and recorded pauses from `config_dir`; missing,
+    /// unreadable or invalid files mean none
+    pub fn load(config_dir: Option<&Path>) -> Self {
+        let path = config_dir.map(|dir| dir.join(WINDOWS_FILE));
+        let pauses_path = config_dir.map(|dir| dir.join(PAUSES_FILE));
+        let windows = read_json::<SyncWindows>(path.as_deref())
+            .and_then(|mut windows| match windows.validate() {
+                Ok(()) => Some(windows),
+                Err(e) => {
+                    log::warn!("Ignoring invalid sync windows: {e}");
+                    None
+                },
+            })
+            .unwrap_or_default();
+        let pauses = read_json(pauses_path.as_deref()).unwrap_or_default();
+        Self {
+            path,
+            pauses_path,
+            windows,
+            pauses,
+        }
+    }
+
+    pub const fn windows(&self) -> &SyncWindows {
+        &self.windows
+    }
+
+    /// Validate and persist `windows`
+    pub fn set_windows(&mut self, mut windows: SyncWindows) -> Result<(), SyncthingError> {
+        windows.validate()?;
+        write_json(self.path.as_deref(), &windows)?;
+        self.windows = windows;
+        Ok(())
+    }
+
+    /// Targets the scheduler paused on `profile`
+    pub fn scheduled_pauses(&mut self, profile: &str) -> BTreeSet<Target> {
+        self.reload_pauses();
+        self.pauses.get(profile).cloned().unwrap_or_default()
+    }
+
+    /// Record the pauses the scheduler made and lifted since it read `before`
+    ///
+    /// Only the difference is applied, so a pause forgotten in the meantime
+    /// (because the user took over) is not brought back.
+    pub fn update_scheduled_pauses(
+        &mut self,
+        profile: &str,
+        before: &BTreeSet<Target>,
+        after: &BTreeSet<Target>,
+    ) -> Result<(), SyncthingError> {
+        if before == after {
+            return Ok(());
+        }
+        self.reload_pauses();
+        let pauses = self.pauses.entry(profile.to_string()).or_default();
+        for target in before.difference(after) {
+            pauses.remove(target);
+        }
+        for target in after.difference(before) {
+            pauses.insert(target.clone());
+        }
+        self.save_pauses()
+    }
+
+    /// Forget a scheduled pause of `target`, e.g. because the user paused or
+    /// resumed it by hand
+    pub fn forget_pause(&mut self, profile: &str, target: &Target) -> Result<(), SyncthingError> {
+        self.reload_pauses();
+        let removed = self
+            .pauses
+            .get_mut(profile)
+            .is_some_and(|pauses| pauses.remove(target));
+        if removed {
+            self.save_pauses()?;
+        }
+        Ok(())
+    }
+
+    /// Pick up pauses recorded or forgotten by another process
+    fn reload_pauses(&mut self) {
+        if self.pauses_path.is_some() {
+            self.pauses = read_json(self.pauses_path.as_deref()).unwrap_or_default();
+        }
+    }
+
+    fn save_pauses(&mut self) -> Result<(), SyncthingError> {
+        self.pauses.retain(|_, pauses| !pauses.is_empty());
+        write_json(self.pauses_path.as_deref(), &self.pauses)
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: Option<&Path>) -> Option<T> {
+    let content = fs::read_to_string(path?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_json<T: Serialize>(path: Option<&Path>, value: &T) -> Result<(), SyncthingError> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| SyncthingError::filesystem(parent, e))?;
+    }
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| SyncthingError::ParseError(e.to_string()))?;
+    fs::write(path, json).map_err(|e| SyncthingError::filesystem(path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forgotten_pause_is_not_recorded_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = SyncWindowStore::load(Some(dir.path()));
+        let mut cli = SyncWindowStore::load(Some(dir.path()));
+        let (photos, music) = (Target::folder("photos"), Target::folder("music"));
+
+        app.update_scheduled_pauses("local", &BTreeSet::new(), &BTreeSet::from([photos.clone()]))
+            .unwrap();
+
+        // The CLI forgets `photos` while the app applies the windows
+        let before = app.scheduled_pauses("local");
+        cli.forget_pause("local", &photos).unwrap();
+        let after = BTreeSet::from([photos.clone(), music.clone()]);
+        app.update_scheduled_pauses("local", &before, &after)
+            .unwrap();
+
+        assert_eq!(
+            app.scheduled_pauses("local"),
+            BTreeSet::from([music.clone()])
+        );
+        let mut reloaded = SyncWindowStore::load(Some(dir.path()));
+        assert_eq!(reloaded.scheduled_pauses("local"), BTreeSet::from([music]));
+    }
+
+    #[test]
+    fn pauses_are_kept_per_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = SyncWindowStore::load(Some(dir.path()));
+        let photos = Target::folder("photos");
+
+        store
+            .update_scheduled_pauses("work", &BTreeSet::new(), &BTreeSet::from([photos.clone()]))
+            .unwrap();
+        store.forget_pause("local", &photos).unwrap();
+
+        assert_eq!(store.scheduled_pauses("work"), BTreeSet::from([photos]));
+        assert!(store.scheduled_pauses("local").is_empty());
+    }
+}
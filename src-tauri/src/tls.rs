@@ -0,0 +1,312 @@
+//! HTTPS support for Syncthing's self-signed GUI certificate.
+//!
+//! Syncthing generates its own GUI certificate (`https-cert.pem` in its home),
+//! so normal CA validation cannot succeed. Instead of disabling certificate
+//! checks, the SHA-256 fingerprint of the expected certificate is pinned: it
+//! is read from `https-cert.pem` for the local instance, or confirmed by the
+//! user for remote profiles.
+
+use crate::SyncthingError;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{CertificateError, ClientConfig, DigitallySignedStruct, SignatureScheme};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// File Syncthing stores its GUI certificate in
+pub const GUI_CERT_FILE: &str = "https-cert.pem";
+
+/// SHA-256 fingerprint of a DER certificate, as colon-separated upper-case hex
+pub fn fingerprint(cert: &[u8]) -> String {
+    let digest = Sha256::digest(cert);
+    let mut out = String::with_capacity(digest.len() * 3);
+    for (i, byte) in digest.iter().enumerate() {
+        if i > 0 {
+            out.push(':');
+        }
+        let _ = write!(out, "{byte:02X}");
+    }
+    out
+}
+
+/// Fingerprint of the first certificate in a PEM file
+pub fn fingerprint_from_pem_file(path: &Path) -> Result<String, SyncthingError> {
+    let pem = std::fs::read(path).map_err(|e| SyncthingError::filesystem(path, e))?;
+    let cert = CertificateDer::from_pem_slice(&pem).map_err(|e| {
+        SyncthingError::ParseError(format!("Invalid certificate in {}: {e}", path.display()))
+    })?;
+    Ok(fingerprint(&cert))
+}
+
+/// Parse a user-supplied SHA-256 fingerprint (hex, with or without `:` separators)
+pub fn parse_fingerprint(input: &str) -> Result<[u8; 32], SyncthingError> {
+    let hex: String = input
+        .chars()
+        .filter(|c| !matches!(c, ':' | ' ' | '-'))
+        .collect();
+    let invalid = || {
+        SyncthingError::validation(
+            "certFingerprint",
+            format!("`{input}` is not a SHA-256 certificate fingerprint"),
+        )
+    };
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(bytes)
+}
+
+/// The error for HTTPS without a pinned fingerprint
+pub fn missing_fingerprint() -> SyncthingError {
+    SyncthingError::validation(
+        "certFingerprint",
+        "HTTPS needs the GUI certificate's fingerprint pinned; fetch it with \
+         `fetch_certificate_fingerprint` and confirm it first",
+    )
+}
+
+/// TLS settings that accept only the certificate with the given fingerprint
+pub fn pinned_client_config(fingerprint: &str) -> Result<ClientConfig, SyncthingError> {
+    let pin = parse_fingerprint(fingerprint)?;
+    client_config(Arc::new(PinnedVerifier {
+        pin,
+        provider: provider(),
+    }))
+}
+
+/// Connect to `host:port` and return the fingerprint of the certificate it presents
+///
+/// Used to show the fingerprint of a remote instance to the user for
+/// confirmation before it is pinned. Only the unauthenticated health endpoint
+/// is requested, so no credentials are sent to the unverified server.
+pub async fn fetch_fingerprint(host: &str, port: u16) -> Result<String, SyncthingError> {
+    let verifier = Arc::new(CapturingVerifier {
+        seen: Mutex::new(None),
+        provider: provider(),
+    });
+    let http = reqwest::Client::builder()
+        .use_preconfigured_tls(client_config(verifier.clone())?)
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| SyncthingError::HttpError(format!("Failed to build HTTP client: {e}")))?;
+
+    let url = format!("https://{host}:{port}/rest/noauth/health");
+    let result = http.get(&url).send().await;
+
+    let seen = verifier
+        .seen
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+    match (seen, result) {
+        (Some(fingerprint), _) => Ok(fingerprint),
+        (None, Err(e)) => Err(SyncthingError::HttpError(format!(
+            "Failed to connect to {host}:{port}: {e}"
+        ))),
+        (None, Ok(_)) => Err(SyncthingError::HttpError(format!(
+            "{host}:{port} did not present a certificate"
+        ))),
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn client_config(verifier: Arc<dyn ServerCertVerifier>) -> Result<ClientConfig, SyncthingError> {
+    Ok(ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| SyncthingError::HttpError(format!("Failed to configure TLS: {e}")))?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier)
+        .with_no_client_auth())
+}
+
+/// Accepts exactly one certificate, identified by its SHA-256 fingerprint
+#[derive(Debug)]
+struct PinnedVerifier {
+    pin: [u8; 32],
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        // Syncthing's certificate names "syncthing", never the host, so only
+        // the fingerprint is meaningful
+        if Sha256::digest(end_entity).as_slice() == self.pin {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Records the presented certificate's fingerprint without trusting it
+#[derive(Debug)]
+struct CapturingVerifier {
+    seen: Mutex<Option<String>>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for CapturingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        *self.seen.lock().unwrap_or_else(PoisonError::into_inner) = Some(fingerprint(end_entity));
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SHA-256 of no bytes at all
+    const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    fn verify(pin: [u8; 32], cert: &[u8]) -> Result<ServerCertVerified, rustls::Error> {
+        let verifier = PinnedVerifier {
+            pin,
+            provider: provider(),
+        };
+        verifier.verify_server_cert(
+            &CertificateDer::from(cert.to_vec()),
+            &[],
+            &ServerName::try_from("syncthing").unwrap(),
+            &[],
+            UnixTime::now(),
+        )
+    }
+
+    #[test]
+    fn fingerprint_is_colon_separated_upper_case_hex() {
+        let fingerprint = fingerprint(b"");
+        assert_eq!(fingerprint.len(), 32 * 3 - 1);
+        assert_eq!(fingerprint.replace(':', ""), EMPTY_SHA256.to_uppercase());
+        assert!(fingerprint.starts_with("E3:B0:C4:42:"));
+    }
+
+    #[test]
+    fn parse_fingerprint_accepts_colon_and_plain_hex() {
+        let plain = parse_fingerprint(EMPTY_SHA256).unwrap();
+        assert_eq!(parse_fingerprint(&fingerprint(b"")).unwrap(), plain);
+        assert_eq!(
+            parse_fingerprint(&EMPTY_SHA256.to_uppercase()).unwrap(),
+            plain
+        );
+        assert_eq!(plain.as_slice(), Sha256::digest(b"").as_slice());
+    }
+
+    #[test]
+    fn parse_fingerprint_rejects_wrong_lengths_and_non_hex() {
+        for invalid in [&EMPTY_SHA256[..62], &format!("{EMPTY_SHA256}00"), ""] {
+            assert!(parse_fingerprint(invalid).is_err(), "{invalid}");
+        }
+        let not_hex = format!("zz{}", &EMPTY_SHA256[2..]);
+        assert!(parse_fingerprint(&not_hex).is_err());
+    }
+
+    #[test]
+    fn pinned_verifier_accepts_only_the_pinned_certificate() {
+        let pin = parse_fingerprint(&fingerprint(b"pinned certificate")).unwrap();
+        assert!(verify(pin, b"pinned certificate").is_ok());
+        assert!(matches!(
+            verify(pin, b"another certificate"),
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure
+            ))
+        ));
+    }
+}
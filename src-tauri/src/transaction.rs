@@ -0,0 +1,538 @@
+//! Batched configuration changes.
+//!
+//! A transaction is a list of typed operations applied in order to a copy of
+//! the configuration. The result is validated as a whole and compared with
+//! the original to produce a structured diff, which is either returned as a
+//! preview or written back to Syncthing in a single PUT, so either every
+//! operation takes effect or none does.
+
+use crate::core::folders::validate_folder_id;
+use crate::device_id::normalize;
+use crate::models::{apply_update, Configuration, DeviceConfiguration, Extra, FolderConfiguration};
+use crate::SyncthingError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// One change within a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ConfigOperation {
+    AddFolder {
+        folder_id: String,
+        label: String,
+        path: String,
+        /// Further folder settings, as in `update_folder_config`
+        #[serde(default)]
+        settings: Extra,
+    },
+    RemoveFolder {
+        folder_id: String,
+    },
+    UpdateFolder {
+        folder_id: String,
+        updates: Extra,
+    },
+    AddDevice {
+        device_id: String,
+        name: String,
+        /// Further device settings, as in `update_device_config`
+        #[serde(default)]
+        settings: Extra,
+    },
+    RemoveDevice {
+        device_id: String,
+    },
+    UpdateDevice {
+        device_id: String,
+        updates: Extra,
+    },
+    ShareFolder {
+        folder_id: String,
+        device_id: String,
+        /// Share encrypted, treating the device as untrusted
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        encryption_password: Option<String>,
+    },
+    UnshareFolder {
+        folder_id: String,
+        device_id: String,
+    },
+    UpdateOptions {
+        updates: Extra,
+    },
+}
+
+/// How an object differs between the current and resulting configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A single changed setting
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+/// A folder or device that is added, removed or modified
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectChange {
+    pub id: String,
+    pub kind: ChangeKind,
+    /// Changed settings (empty for added/removed objects)
+    pub changes: Vec<FieldChange>,
+}
+
+/// Everything a transaction changes
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDiff {
+    pub folders: Vec<ObjectChange>,
+    pub devices: Vec<ObjectChange>,
+    pub options: Vec<FieldChange>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.folders.is_empty() && self.devices.is_empty() && self.options.is_empty()
+    }
+}
+
+/// Apply `operations` in order to `config`, then validate the result
+///
+/// On error `config` is left as it was.
+pub fn apply(
+    config: &mut Configuration,
+    operations: &[ConfigOperation],
+) -> Result<(), SyncthingError> {
+    let mut result = config.clone();
+    for (index, operation) in operations.iter().enumerate() {
+        apply_one(&mut result, operation).map_err(|e| match e {
+            SyncthingError::Validation { field, message } => {
+                SyncthingError::validation(format!("operations[{index}].{field}"), message)
+            },
+            other => other,
+        })?;
+    }
+    validate(&result)?;
+    *config = result;
+    Ok(())
+}
+
+fn apply_one(
+    config: &mut Configuration,
+    operation: &ConfigOperation,
+) -> Result<(), SyncthingError> {
+    match operation {
+        ConfigOperation::AddFolder {
+            folder_id,
+            label,
+            path,
+            settings,
+        } => {
+            validate_folder_id(folder_id)?;
+            if config.folder(folder_id).is_some() {
+                return Err(SyncthingError::conflict(
+                    "/rest/config",
+                    format!("Folder `{folder_id}` already exists"),
+                ));
+            }
+            let folder = config.new_folder(folder_id.clone(), label.clone(), path.clone());
+            let folder = apply_update(&folder, settings, "folder")?;
+            folder.validate()?;
+            config.folders.push(folder);
+        },
+        ConfigOperation::RemoveFolder { folder_id } => {
+            folder_mut(config, folder_id)?;
+            config.folders.retain(|f| f.id != *folder_id);
+        },
+        ConfigOperation::UpdateFolder { folder_id, updates } => {
+            let folder = folder_mut(config, folder_id)?;
+            *folder = apply_update(folder, updates, "folder")?;
+            // The ID is the key everything else refers to
+            if folder.id != *folder_id {
+                return Err(SyncthingError::validation("id", "cannot be changed"));
+            }
+            folder.validate()?;
+        },
+        ConfigOperation::AddDevice {
+            device_id,
+            name,
+            settings,
+        } => {
+            let device_id = &normalize(device_id)?;
+            if config.device(device_id).is_some() {
+                return Err(SyncthingError::conflict(
+                    "/rest/config",
+                    format!("Device `{device_id}` already exists"),
+                ));
+            }
+            let device = config.new_device(device_id.clone(), name.clone());
+            config
+                .devices
+                .push(apply_update(&device, settings, "device")?);
+        },
+        ConfigOperation::RemoveDevice { device_id } => {
+            let device_id = &normalize(device_id)?;
+            device_mut(config, device_id)?;
+            config.devices.retain(|d| d.device_id != *device_id);
+            for folder in &mut config.folders {
+                folder.unshare_with(device_id);
+            }
+        },
+        ConfigOperation::UpdateDevice { device_id, updates } => {
+            let device_id = &normalize(device_id)?;
+            let device = device_mut(config, device_id)?;
+            *device = apply_update(device, updates, "device")?;
+            if device.device_id != *device_id {
+                return Err(SyncthingError::validation("deviceID", "cannot be changed"));
+            }
+        },
+        ConfigOperation::ShareFolder {
+            folder_id,
+            device_id,
+            encryption_password,
+        } => {
+            let device_id = &normalize(device_id)?;
+            let folder = folder_mut(config, folder_id)?;
+            match encryption_password.as_deref() {
+                Some("") => {
+                    return Err(SyncthingError::validation(
+                        "encryptionPassword",
+                        "cannot be empty",
+                    ));
+                },
+                Some(password) => folder.share_encrypted_with(device_id, password),
+                None => folder.share_with(device_id),
+            };
+            folder.validate()?;
+        },
+        ConfigOperation::UnshareFolder {
+            folder_id,
+            device_id,
+        } => {
+            let device_id = &normalize(device_id)?;
+            folder_mut(config, folder_id)?.unshare_with(device_id);
+        },
+        ConfigOperation::UpdateOptions { updates } => {
+            config.options = apply_update(&config.options, updates, "options")?;
+        },
+    }
+    Ok(())
+}
+
+fn folder_mut<'a>(
+    config: &'a mut Configuration,
+    folder_id: &str,
+) -> Result<&'a mut FolderConfiguration, SyncthingError> {
+    config
+        .folder_mut(folder_id)
+        .ok_or_else(|| SyncthingError::FolderNotFound(folder_id.to_string()))
+}
+
+fn device_mut<'a>(
+    config: &'a mut Configuration,
+    device_id: &str,
+) -> Result<&'a mut DeviceConfiguration, SyncthingError> {
+    config
+        .devices
+        .iter_mut()
+        .find(|d| d.device_id == device_id)
+        .ok_or_else(|| SyncthingError::DeviceNotFound(device_id.to_string()))
+}
+
+/// Checks Syncthing would otherwise only report after the write
+fn validate(config: &Configuration) -> Result<(), SyncthingError> {
+    let mut device_ids = BTreeSet::new();
+    for device in &config.devices {
+        if device.device_id.trim().is_empty() {
+            return Err(SyncthingError::validation("deviceID", "must not be empty"));
+        }
+        if !device_ids.insert(device.device_id.as_str()) {
+            return Err(SyncthingError::conflict(
+                "/rest/config",
+                format!("Device `{}` appears more than once", device.device_id),
+            ));
+        }
+    }
+
+    let mut folder_ids = BTreeSet::new();
+    for folder in &config.folders {
+        if folder.id.trim().is_empty() {
+            return Err(SyncthingError::validation("id", "must not be empty"));
+        }
+        if folder.path.trim().is_empty() {
+            return Err(SyncthingError::validation(
+                "path",
+                format!("folder `{}` has no path", folder.id),
+            ));
+        }
+        if !folder_ids.insert(folder.id.as_str()) {
+            return Err(SyncthingError::conflict(
+                "/rest/config",
+                format!("Folder `{}` appears more than once", folder.id),
+            ));
+        }
+        if let Some(unknown) = folder
+            .devices
+            .iter()
+            .find(|d| !device_ids.contains(d.device_id.as_str()))
+        {
+            return Err(SyncthingError::DeviceNotFound(unknown.device_id.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Folder, device and option changes between `before` and `after`
+pub fn diff(before: &Configuration, after: &Configuration) -> ConfigDiff {
+    ConfigDiff {
+        folders: diff_objects(
+            before.folders.iter().map(|f| (f.id.as_str(), f)),
+            after.folders.iter().map(|f| (f.id.as_str(), f)),
+        ),
+        devices: diff_objects(
+            before.devices.iter().map(|d| (d.device_id.as_str(), d)),
+            after.devices.iter().map(|d| (d.device_id.as_str(), d)),
+        ),
+        options: diff_fields(&to_value(&before.options), &to_value(&after.options)),
+    }
+}
+
+fn diff_objects<'a, T, B, A>(before: B, after: A) -> Vec<ObjectChange>
+where
+    T: Serialize + 'a,
+    B: Iterator<Item = (&'a str, &'a T)>,
+    A: Iterator<Item = (&'a str, &'a T)>,
+{
+    let before: Vec<_> = before.collect();
+    let after: Vec<_> = after.collect();
+    let find =
+        |list: &[(&str, &'a T)], id: &str| list.iter().find(|(i, _)| *i == id).map(|(_, o)| *o);
+
+    let mut changes = Vec::new();
+    for (id, old) in &before {
+        let (kind, fields) = find(&after, id).map_or_else(
+            || (ChangeKind::Removed, Vec::new()),
+            |new| {
+                (
+                    ChangeKind::Modified,
+                    diff_fields(&to_value(*old), &to_value(new)),
+                )
+            },
+        );
+        if kind == ChangeKind::Modified && fields.is_empty() {
+            continue;
+        }
+        changes.push(ObjectChange {
+            id: (*id).to_string(),
+            kind,
+            changes: fields,
+        });
+    }
+    for (id, _) in &after {
+        if find(&before, id).is_none() {
+            changes.push(ObjectChange {
+                id: (*id).to_string(),
+                kind: ChangeKind::Added,
+                changes: Vec::new(),
+            });
+        }
+    }
+    changes
+}
+
+/// Top-level keys whose values differ between two serialized objects
+fn diff_fields(before: &Value, after: &Value) -> Vec<FieldChange> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let old = before.get(key).unwrap_or(&Value::Null);
+            let new = after.get(key).unwrap_or(&Value::Null);
+            (old != new).then(|| FieldChange {
+                field: key.clone(),
+                before: old.clone(),
+                after: new.clone(),
+            })
+        })
+        .collect()
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const LAPTOP: &str = "MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD";
+    const NAS: &str = "P56IOI7-MZJNU2Y-IQGDREY-DM2MGTI-MGL3BXN-PQ6W5BM-TBBZ4TJ-XZWICQ2";
+
+    fn fixture() -> Configuration {
+        serde_json::from_value(json!({
+            "version": 37,
+            "devices": [{ "deviceID": LAPTOP, "name": "laptop" }],
+            "folders": [{
+                "id": "docs",
+                "label": "Docs",
+                "path": "/srv/docs",
+                "type": "sendreceive",
+                "devices": [{ "deviceID": LAPTOP }],
+            }],
+            "options": { "maxRecvKbps": 0 },
+        }))
+        .unwrap()
+    }
+
+    fn updates(value: Value) -> Extra {
+        match value {
+            Value::Object(map) => map,
+            other => panic!("updates must be an object, got {other}"),
+        }
+    }
+
+    fn ids(changes: &[ObjectChange], kind: ChangeKind) -> Vec<&str> {
+        changes
+            .iter()
+            .filter(|c| c.kind == kind)
+            .map(|c| c.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn diff_reports_what_apply_changed() {
+        let before = fixture();
+        let mut after = before.clone();
+        apply(
+            &mut after,
+            &[
+                ConfigOperation::AddDevice {
+                    device_id: NAS.to_lowercase().replace('-', ""),
+                    name: "nas".into(),
+                    settings: Extra::new(),
+                },
+                ConfigOperation::AddFolder {
+                    folder_id: "photos".into(),
+                    label: "Photos".into(),
+                    path: "/srv/photos".into(),
+                    settings: Extra::new(),
+                },
+                ConfigOperation::ShareFolder {
+                    folder_id: "photos".into(),
+                    device_id: NAS.into(),
+                    encryption_password: None,
+                },
+                ConfigOperation::UpdateFolder {
+                    folder_id: "docs".into(),
+                    updates: updates(json!({ "label": "Documents" })),
+                },
+                ConfigOperation::UpdateOptions {
+                    updates: updates(json!({ "maxRecvKbps": 500 })),
+                },
+            ],
+        )
+        .unwrap();
+
+        assert!(after.device(NAS).is_some());
+        assert!(after.folder("photos").unwrap().is_shared_with(NAS));
+
+        let diff = diff(&before, &after);
+        assert_eq!(ids(&diff.devices, ChangeKind::Added), [NAS]);
+        assert_eq!(ids(&diff.folders, ChangeKind::Added), ["photos"]);
+        assert_eq!(ids(&diff.folders, ChangeKind::Modified), ["docs"]);
+        let docs = diff.folders.iter().find(|c| c.id == "docs").unwrap();
+        assert_eq!(docs.changes.len(), 1);
+        assert_eq!(docs.changes[0].field, "label");
+        assert_eq!(docs.changes[0].after, json!("Documents"));
+        assert_eq!(diff.options.len(), 1);
+        assert_eq!(diff.options[0].field, "maxRecvKbps");
+        assert_eq!(diff.options[0].after, json!(500));
+    }
+
+    #[test]
+    fn failing_operation_applies_nothing() {
+        let before = fixture();
+        let mut config = before.clone();
+        let result = apply(
+            &mut config,
+            &[
+                ConfigOperation::AddDevice {
+                    device_id: NAS.into(),
+                    name: "nas".into(),
+                    settings: Extra::new(),
+                },
+                ConfigOperation::ShareFolder {
+                    folder_id: "missing".into(),
+                    device_id: NAS.into(),
+                    encryption_password: None,
+                },
+                ConfigOperation::UpdateOptions {
+                    updates: updates(json!({ "maxRecvKbps": 500 })),
+                },
+            ],
+        );
+
+        assert!(matches!(result, Err(SyncthingError::FolderNotFound(id)) if id == "missing"));
+        assert!(diff(&before, &config).is_empty());
+    }
+
+    #[test]
+    fn validation_errors_name_the_operation() {
+        let mut config = fixture();
+        let result = apply(
+            &mut config,
+            &[
+                ConfigOperation::UpdateFolder {
+                    folder_id: "docs".into(),
+                    updates: updates(json!({ "label": "Documents" })),
+                },
+                ConfigOperation::UpdateFolder {
+                    folder_id: "docs".into(),
+                    updates: updates(json!({ "type": "mirror" })),
+                },
+            ],
+        );
+
+        assert!(matches!(
+            result,
+            Err(SyncthingError::Validation { field, .. }) if field == "operations[1].type"
+        ));
+        assert_eq!(config.folder("docs").unwrap().label, "Docs");
+    }
+
+    #[test]
+    fn removing_a_device_unshares_it() {
+        let before = fixture();
+        let mut after = before.clone();
+        apply(
+            &mut after,
+            &[ConfigOperation::RemoveDevice {
+                device_id: LAPTOP.into(),
+            }],
+        )
+        .unwrap();
+
+        let diff = diff(&before, &after);
+        assert_eq!(ids(&diff.devices, ChangeKind::Removed), [LAPTOP]);
+        let docs = &diff.folders[0];
+        assert_eq!(
+            (docs.id.as_str(), docs.kind),
+            ("docs", ChangeKind::Modified)
+        );
+        assert_eq!(docs.changes[0].field, "devices");
+    }
+}
@@ -0,0 +1,51 @@
+//! The tray icon's tooltip.
+//!
+//! The frontend reports the overall sync status and background tasks add a
+//! line of their own, such as the bandwidth rule in effect; the tooltip shows
+//! all of them, so neither overwrites the other.
+
+use crate::SyncthingState;
+use std::sync::PoisonError;
+use tauri::{AppHandle, Manager};
+
+pub const TRAY_ID: &str = "main";
+/// Shown until the frontend reports a status
+pub const DEFAULT_TOOLTIP: &str = "Eigen - Syncthing Manager";
+
+/// The parts the tooltip is built from
+#[derive(Debug, Default)]
+pub struct TrayTooltip {
+    status: Option<String>,
+    bandwidth: Option<String>,
+}
+
+impl TrayTooltip {
+    fn render(&self) -> String {
+        std::iter::once(self.status.as_deref().unwrap_or(DEFAULT_TOOLTIP))
+            .chain(self.bandwidth.as_deref())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Replace the status line reported by the frontend
+pub fn set_status(app: &AppHandle, status: String) {
+    update(app, |tooltip| tooltip.status = Some(status));
+}
+
+/// Replace the bandwidth schedule line, or drop it with `None`
+pub fn set_bandwidth(app: &AppHandle, line: Option<String>) {
+    update(app, |tooltip| tooltip.bandwidth = line);
+}
+
+fn update(app: &AppHandle, change: impl FnOnce(&mut TrayTooltip)) {
+    let text = {
+        let state = app.state::<SyncthingState>();
+        let mut tooltip = state.tray.lock().unwrap_or_else(PoisonError::into_inner);
+        change(&mut tooltip);
+        tooltip.render()
+    };
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(&text));
+    }
+}
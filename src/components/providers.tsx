@@ -30,12 +30,12 @@ function DeepLinkHandler() {
         // eslint-disable-next-line @typescript-eslint/no-explicit-any
         const deepLink = await import('@tauri-apps/plugin-deep-link' as any);
         const onOpenUrl = deepLink.onOpenUrl as (
-          callback: (urls: string[]) => void
+          callback: (urls: string[]) => void | Promise<void>
         ) => Promise<() => void>;
 
-        const unlisten = await onOpenUrl((urls: string[]) => {
+        const unlisten = await onOpenUrl(async (urls: string[]) => {
           for (const url of urls) {
-            const invitation = parseInviteUrl(url);
+            const invitation = await parseInviteUrl(url);
             if (invitation) {
               logger.info('Received device invitation via deep link', {
                 deviceId: invitation.deviceId,
@@ -203,7 +203,7 @@ export function SyncthingManager({ children }: { children: React.ReactNode }) {
   );
 
   // Subscribe to Syncthing events when connected
-  const { isPolling } = useSyncthingEvents({
+  const { isConnected: isStreaming } = useSyncthingEvents({
     onEvent: handleEvent,
     enabled: !!status?.myID,
   });
@@ -277,12 +277,12 @@ export function SyncthingManager({ children }: { children: React.ReactNode }) {
     const uptime = status.uptime ? Math.floor(status.uptime / 60) : 0;
     const uptimeStr = uptime > 60 ? `${Math.floor(uptime / 60)}h ${uptime % 60}m` : `${uptime}m`;
 
-    if (isPolling) {
+    if (isStreaming) {
       updateTrayStatus('Connected', `Uptime: ${uptimeStr} • Listening for events`);
     } else {
       updateTrayStatus('Connected', `Uptime: ${uptimeStr}`);
     }
-  }, [status?.myID, status?.uptime, isPolling]);
+  }, [status?.myID, status?.uptime, isStreaming]);
 
   return <>{children}</>;
 }
@@ -0,0 +1,39 @@
+'use client';
+
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
+import {
+  getBandwidthSchedule,
+  getBandwidthStatus,
+  setBandwidthSchedule,
+  type BandwidthSchedule,
+} from '@/lib/tauri-commands';
+
+export function useBandwidthSchedule() {
+  return useQuery({
+    queryKey: ['bandwidthSchedule'],
+    queryFn: getBandwidthSchedule,
+    staleTime: 60000,
+  });
+}
+
+export function useBandwidthStatus() {
+  return useQuery({
+    queryKey: ['bandwidthStatus'],
+    queryFn: getBandwidthStatus,
+    refetchInterval: 60000,
+    staleTime: 30000,
+  });
+}
+
+export function useSetBandwidthSchedule() {
+  const queryClient = useQueryClient();
+
+  return useMutation({
+    mutationFn: (schedule: BandwidthSchedule) => setBandwidthSchedule(schedule),
+    onSuccess: (status) => {
+      queryClient.invalidateQueries({ queryKey: ['bandwidthSchedule'] });
+      queryClient.setQueryData(['bandwidthStatus'], status);
+      queryClient.invalidateQueries({ queryKey: ['config'] });
+    },
+  });
+}
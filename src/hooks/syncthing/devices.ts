@@ -4,6 +4,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
 import { DeviceConfigSchema } from './schemas';
 import type { DeviceConfig, AdvancedDeviceOptions, Config } from './types';
+import { getDeviceOverview } from '@/lib/tauri-commands';
 
 export function useDeviceId() {
   return useQuery({
@@ -28,6 +29,15 @@ export function useDeviceConfig(deviceId: string) {
   });
 }
 
+export function useDeviceOverview() {
+  return useQuery({
+    queryKey: ['deviceOverview'],
+    queryFn: getDeviceOverview,
+    refetchInterval: 30000,
+    staleTime: 10000,
+  });
+}
+
 export function useAddDevice() {
   const queryClient = useQueryClient();
 
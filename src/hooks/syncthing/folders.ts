@@ -5,6 +5,12 @@ import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
 import { FolderStatusSchema, FolderConfigSchema, IgnorePatternsSchema } from './schemas';
 import type { FolderConfig, AdvancedFolderOptions } from './types';
 import type { Config } from './types';
+import {
+  getCompletionMatrix,
+  getFileInfo,
+  getFolderStats,
+  type AddedFolder,
+} from '@/lib/tauri-commands';
 
 export function useFolderStatus(folderId: string) {
   return useQuery({
@@ -19,6 +25,24 @@ export function useFolderStatus(folderId: string) {
   });
 }
 
+export function useCompletionMatrix() {
+  return useQuery({
+    queryKey: ['completionMatrix'],
+    queryFn: getCompletionMatrix,
+    refetchInterval: 30000,
+    staleTime: 10000,
+  });
+}
+
+export function useFolderStats() {
+  return useQuery({
+    queryKey: ['folderStats'],
+    queryFn: getFolderStats,
+    refetchInterval: 30000,
+    staleTime: 10000,
+  });
+}
+
 export function useFolderConfig(folderId: string) {
   return useQuery({
     queryKey: ['folderConfig', folderId],
@@ -58,6 +82,15 @@ export function useBrowseFolder(folderId: string, prefix?: string) {
   });
 }
 
+export function useFileInfo(folderId: string, file: string | null) {
+  return useQuery({
+    queryKey: ['fileInfo', folderId, file],
+    queryFn: () => getFileInfo(folderId, file as string),
+    enabled: !!folderId && !!file,
+    staleTime: 5000,
+  });
+}
+
 export function usePauseFolder() {
   const queryClient = useQueryClient();
 
@@ -148,7 +181,7 @@ export function useAddFolder() {
       folderLabel: string;
       folderPath: string;
     }) => {
-      await invoke('add_folder', { folderId, folderLabel, folderPath });
+      return invoke<AddedFolder>('add_folder', { folderId, folderLabel, folderPath });
     },
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: ['config'] });
@@ -161,16 +194,18 @@ export function useAddFolderAdvanced() {
 
   return useMutation({
     mutationFn: async (options: AdvancedFolderOptions) => {
-      await invoke('add_folder_advanced', {
-        folderId: options.folderId,
+      return invoke<AddedFolder>('add_folder_advanced', {
+        folderId: options.folderId || null,
         folderLabel: options.folderLabel,
         folderPath: options.folderPath,
+        folderType: options.folderType || null,
         versioningType: options.versioningType || null,
         versioningParams: options.versioningParams || null,
         rescanIntervalS: options.rescanIntervalS ?? null,
         fsWatcherEnabled: options.fsWatcherEnabled ?? null,
         fsWatcherDelayS: options.fsWatcherDelayS ?? null,
         ignorePerms: options.ignorePerms ?? null,
+        devices: options.devices ?? null,
       });
     },
     onSuccess: () => {
@@ -196,8 +231,16 @@ export function useShareFolder() {
   const queryClient = useQueryClient();
 
   return useMutation({
-    mutationFn: async ({ folderId, deviceId }: { folderId: string; deviceId: string }) => {
-      await invoke('share_folder', { folderId, deviceId });
+    mutationFn: async ({
+      folderId,
+      deviceId,
+      encryptionPassword,
+    }: {
+      folderId: string;
+      deviceId: string;
+      encryptionPassword?: string;
+    }) => {
+      await invoke('share_folder', { folderId, deviceId, encryptionPassword });
     },
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: ['config'] });
@@ -21,9 +21,12 @@ export {
 // Re-export folder hooks
 export {
   useFolderStatus,
+  useCompletionMatrix,
+  useFolderStats,
   useFolderConfig,
   useFolderIgnores,
   useBrowseFolder,
+  useFileInfo,
   usePauseFolder,
   useResumeFolder,
   useRescanFolder,
@@ -41,6 +44,7 @@ export {
 export {
   useDeviceId,
   useDeviceConfig,
+  useDeviceOverview,
   useAddDevice,
   useAddDeviceAdvanced,
   useRemoveDevice,
@@ -64,6 +68,12 @@ export { useSystemLogs } from './logs';
 // Re-export options hooks
 export { useUpdateOptions } from './options';
 
+// Re-export bandwidth schedule hooks
+export { useBandwidthSchedule, useBandwidthStatus, useSetBandwidthSchedule } from './bandwidth';
+
+// Re-export sync window hooks
+export { useSyncWindows, useSyncWindowState, useSetSyncWindows } from './sync-windows';
+
 // Re-export pending request hooks
 export {
   usePendingDevices,
@@ -12,6 +12,7 @@ import {
   type PendingDevice,
   type PendingFolder,
   type PendingRequests,
+  type FolderType,
 } from '@/lib/tauri-commands';
 import { QUERY_KEYS } from '@/constants/routes';
 
@@ -100,13 +101,24 @@ export function useAcceptPendingFolder() {
       deviceId,
       folderPath,
       folderLabel,
+      folderType,
+      encryptionPassword,
     }: {
       folderId: string;
       deviceId: string;
       folderPath: string;
       folderLabel?: string;
+      folderType?: FolderType;
+      encryptionPassword?: string;
     }) => {
-      await acceptPendingFolder(folderId, deviceId, folderPath, folderLabel);
+      return acceptPendingFolder(
+        folderId,
+        deviceId,
+        folderPath,
+        folderLabel,
+        folderType,
+        encryptionPassword
+      );
     },
     onSuccess: () => {
       // Invalidate pending requests and config queries
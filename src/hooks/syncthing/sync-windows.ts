@@ -0,0 +1,38 @@
+'use client';
+
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
+import {
+  getSyncWindows,
+  getSyncWindowState,
+  setSyncWindows,
+  type SyncWindows,
+} from '@/lib/tauri-commands';
+
+export function useSyncWindows() {
+  return useQuery({
+    queryKey: ['syncWindows'],
+    queryFn: getSyncWindows,
+    staleTime: 60000,
+  });
+}
+
+export function useSyncWindowState() {
+  return useQuery({
+    queryKey: ['syncWindowState'],
+    queryFn: getSyncWindowState,
+    refetchInterval: 30000,
+    staleTime: 10000,
+  });
+}
+
+export function useSetSyncWindows() {
+  const queryClient = useQueryClient();
+
+  return useMutation({
+    mutationFn: (windows: SyncWindows) => setSyncWindows(windows),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ['syncWindows'] });
+      queryClient.invalidateQueries({ queryKey: ['syncWindowState'] });
+    },
+  });
+}
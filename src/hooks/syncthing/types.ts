@@ -1,6 +1,7 @@
 'use client';
 
 import { z } from 'zod';
+import type { FolderType } from '@/lib/tauri-commands';
 import {
   SyncthingInfoSchema,
   SystemStatusSchema,
@@ -34,15 +35,19 @@ export type LogEntry = z.infer<typeof LogEntrySchema>;
 export type SyncthingEvent = z.infer<typeof SyncthingEventSchema>;
 
 export interface AdvancedFolderOptions {
-  folderId: string;
+  /** Generated when left out */
+  folderId?: string;
   folderLabel: string;
   folderPath: string;
+  folderType?: FolderType;
   versioningType?: 'simple' | 'staggered' | 'trashcan' | 'external' | '';
   versioningParams?: Record<string, string>;
   rescanIntervalS?: number;
   fsWatcherEnabled?: boolean;
   fsWatcherDelayS?: number;
   ignorePerms?: boolean;
+  /** Devices to share the folder with; a password encrypts what a device receives */
+  devices?: { deviceId: string; encryptionPassword?: string }[];
 }
 
 export interface AdvancedDeviceOptions {
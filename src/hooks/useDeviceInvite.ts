@@ -3,83 +3,30 @@
 import { useState, useEffect, useCallback } from 'react';
 import { useDeviceId } from './useSyncthing';
 import { logger } from '@/lib/logger';
+import { decodeDeviceInvite, getDeviceInvite, type Invite } from '@/lib/tauri-commands';
 
-export interface DeviceInvitation {
-  deviceId: string;
-  name?: string;
-  introducer?: boolean;
-  expiresAt?: number;
-}
+/** An invitation as decoded by the backend */
+export type DeviceInvitation = Invite;
 
 export interface PendingInvitation extends DeviceInvitation {
   receivedAt: number;
 }
 
-const INVITE_SCHEME = 'eigen';
-const INVITE_HOST = 'invite';
 const DEFAULT_EXPIRY_HOURS = 24;
 
-export function generateInviteUrl(
-  deviceId: string,
-  deviceName?: string,
-  options: {
-    introducer?: boolean;
-    expiryHours?: number;
-  } = {}
-): string {
-  const params = new URLSearchParams();
-  params.set('id', deviceId);
-
-  if (deviceName) {
-    params.set('name', deviceName);
-  }
-
-  if (options.introducer) {
-    params.set('introducer', '1');
-  }
-
-  if (options.expiryHours) {
-    const expiresAt = Date.now() + options.expiryHours * 60 * 60 * 1000;
-    params.set('exp', expiresAt.toString());
+/**
+ * Decode an `eigen://invite` link with the backend, which owns the format
+ *
+ * Returns `null` for anything that is not a valid, unexpired invitation.
+ */
+export async function parseInviteUrl(url: string): Promise<DeviceInvitation | null> {
+  if (!url.toLowerCase().startsWith('eigen:')) {
+    return null;
   }
-
-  return `${INVITE_SCHEME}://${INVITE_HOST}?${params.toString()}`;
-}
-
-export function parseInviteUrl(url: string): DeviceInvitation | null {
   try {
-    // Handle both eigen:// and eigen://invite? formats
-    const urlObj = new URL(url);
-
-    if (urlObj.protocol !== `${INVITE_SCHEME}:`) {
-      return null;
-    }
-
-    const params = urlObj.searchParams;
-    const deviceId = params.get('id');
-
-    if (!deviceId) {
-      return null;
-    }
-
-    const name = params.get('name') || undefined;
-    const introducer = params.get('introducer') === '1';
-    const expStr = params.get('exp');
-    const expiresAt = expStr ? parseInt(expStr, 10) : undefined;
-
-    // Check if expired
-    if (expiresAt && Date.now() > expiresAt) {
-      logger.warn('Received expired invitation', { deviceId, expiresAt });
-      return null;
-    }
-
-    return {
-      deviceId,
-      name,
-      introducer,
-      expiresAt,
-    };
-  } catch {
+    return await decodeDeviceInvite(url);
+  } catch (error) {
+    logger.warn('Ignoring invalid invitation', { url, error });
     return null;
   }
 }
@@ -125,7 +72,7 @@ interface UseDeviceInviteReturn {
   /** Whether QR code is being generated */
   isGeneratingQR: boolean;
   /** Generate new invite URL */
-  generateInvite: (options?: { introducer?: boolean; expiryHours?: number }) => void;
+  generateInvite: (options?: { introducer?: boolean; expiryHours?: number }) => Promise<void>;
   /** Generate QR code */
   generateQRCode: () => Promise<void>;
   /** Accept a pending invitation */
@@ -153,12 +100,12 @@ export function useDeviceInvite(): UseDeviceInviteReturn {
         // eslint-disable-next-line @typescript-eslint/no-explicit-any
         const deepLink = await import('@tauri-apps/plugin-deep-link' as any);
         const onOpenUrl = deepLink.onOpenUrl as (
-          callback: (urls: string[]) => void
+          callback: (urls: string[]) => void | Promise<void>
         ) => Promise<() => void>;
 
-        unlisten = await onOpenUrl((urls: string[]) => {
+        unlisten = await onOpenUrl(async (urls: string[]) => {
           for (const url of urls) {
-            const invitation = parseInviteUrl(url);
+            const invitation = await parseInviteUrl(url);
             if (invitation) {
               logger.info('Received device invitation via deep link', {
                 deviceId: invitation.deviceId,
@@ -197,16 +144,19 @@ export function useDeviceInvite(): UseDeviceInviteReturn {
 
   // Generate invite URL when device ID is available
   const generateInvite = useCallback(
-    (options: { introducer?: boolean; expiryHours?: number } = {}) => {
+    async (options: { introducer?: boolean; expiryHours?: number } = {}) => {
       if (!deviceId) return;
 
-      const url = generateInviteUrl(deviceId, 'My Device', {
-        introducer: options.introducer,
-        expiryHours: options.expiryHours || DEFAULT_EXPIRY_HOURS,
-      });
-
-      setInviteUrl(url);
-      setQRCodeUrl(null); // Reset QR when URL changes
+      try {
+        const invite = await getDeviceInvite(undefined, {
+          introducer: options.introducer,
+          expiresInHours: options.expiryHours || DEFAULT_EXPIRY_HOURS,
+        });
+        setInviteUrl(invite.payload);
+        setQRCodeUrl(null); // Reset QR when URL changes
+      } catch (error) {
+        logger.error('Failed to create invitation', { error });
+      }
     },
     [deviceId]
   );
@@ -214,22 +164,20 @@ export function useDeviceInvite(): UseDeviceInviteReturn {
   // Generate QR code
   const generateQRCode = useCallback(async () => {
     if (!inviteUrl) {
-      generateInvite();
+      await generateInvite();
+      return; // Rendered once the new invite URL arrives
     }
 
-    const urlToEncode = inviteUrl || (deviceId ? generateInviteUrl(deviceId) : null);
-    if (!urlToEncode) return;
-
     setIsGeneratingQR(true);
     try {
-      const dataUrl = await generateQRCodeDataUrl(urlToEncode);
+      const dataUrl = await generateQRCodeDataUrl(inviteUrl);
       setQRCodeUrl(dataUrl);
     } catch (error) {
       logger.error('Failed to generate QR code', { error });
     } finally {
       setIsGeneratingQR(false);
     }
-  }, [inviteUrl, deviceId, generateInvite]);
+  }, [inviteUrl, generateInvite]);
 
   // Accept invitation handler
   const acceptInvitation = useCallback((invitation: PendingInvitation) => {
@@ -255,16 +203,15 @@ export function useDeviceInvite(): UseDeviceInviteReturn {
 
   // Copy to clipboard
   const copyInviteUrl = useCallback(async (): Promise<boolean> => {
-    const urlToCopy = inviteUrl || (deviceId ? generateInviteUrl(deviceId) : null);
-    if (!urlToCopy) return false;
+    if (!inviteUrl) return false;
 
     try {
-      await navigator.clipboard.writeText(urlToCopy);
+      await navigator.clipboard.writeText(inviteUrl);
       return true;
     } catch {
       return false;
     }
-  }, [inviteUrl, deviceId]);
+  }, [inviteUrl]);
 
   return {
     deviceId,
@@ -54,9 +54,14 @@ async function invokeWithRetry<T>(
     const result = await syncthingCircuitBreaker.execute(async () => {
       return await retry(
         async () => {
-          const result = await invoke<T>(command, args);
-          logger.debug(`Command succeeded: ${command}`);
-          return result;
+          try {
+            const result = await invoke<T>(command, args);
+            logger.debug(`Command succeeded: ${command}`);
+            return result;
+          } catch (error) {
+            // Turn the backend's `{ code, message, ... }` payload into an AppError
+            throw ErrorHandler.normalize(error);
+          }
         },
         {
           maxAttempts,
@@ -352,7 +357,7 @@ export function withErrorHandling<T>(
       // Transform error for better UX
       if (error instanceof Error) {
         if (error.message.includes('ECONNREFUSED')) {
-          throw new SyncthingError('Cannot connect to Syncthing', 'CONNECTION_REFUSED', {
+          throw new SyncthingError('Cannot connect to Syncthing', 'NOT_RUNNING', {
             context,
           });
         }
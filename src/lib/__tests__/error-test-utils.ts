@@ -32,7 +32,7 @@ export const mockErrors = {
     }),
 
   syncthingConnection: () =>
-    new SyncthingError('Connection refused', 'CONNECTION_REFUSED', {
+    new SyncthingError('Connection refused', 'NOT_RUNNING', {
       context: { host: '127.0.0.1', port: 8384 },
     }),
 
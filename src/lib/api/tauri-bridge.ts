@@ -6,6 +6,8 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { SYNCTHING_EVENTS } from '@/lib/tauri-commands';
 import type { SyncthingClient } from './client';
 import type {
   SystemStatus,
@@ -147,15 +149,17 @@ export class TauriBridge implements SyncthingClient {
 
     addAdvanced: async (options: AdvancedFolderOptions): Promise<void> => {
       await invoke('add_folder_advanced', {
-        folderId: options.folderId,
+        folderId: options.folderId || null,
         folderLabel: options.folderLabel,
         folderPath: options.folderPath,
+        folderType: options.folderType || null,
         versioningType: options.versioningType || null,
         versioningParams: options.versioningParams || null,
         rescanIntervalS: options.rescanIntervalS || null,
         fsWatcherEnabled: options.fsWatcherEnabled ?? null,
         fsWatcherDelayS: options.fsWatcherDelayS || null,
         ignorePerms: options.ignorePerms ?? null,
+        devices: options.devices ?? null,
       });
     },
 
@@ -285,38 +289,16 @@ export class TauriBridge implements SyncthingClient {
   // ---
   events = {
     subscribe: (callback: (event: SyncthingEvent) => void): (() => void) => {
-      // For Tauri, we use long-polling via the hook
-      // This is a placeholder - the actual implementation uses useSyncthingEvents hook
-      let running = true;
-      let lastEventId = 0;
-
-      const poll = async () => {
-        while (running) {
-          try {
-            const data = await invoke<SyncthingEvent[]>('get_events', {
-              since: lastEventId,
-              limit: 100,
-              timeout: 30,
-            });
-
-            if (Array.isArray(data) && data.length > 0) {
-              for (const event of data) {
-                const parsed = SyncthingEventSchema.parse(event);
-                lastEventId = Math.max(lastEventId, parsed.id);
-                callback(parsed);
-              }
-            }
-          } catch {
-            // Ignore polling errors
-          }
-          await new Promise((resolve) => setTimeout(resolve, 1000));
+      // The backend event pump streams every event; no polling needed here
+      const unlisten = listen<unknown>(SYNCTHING_EVENTS.raw, ({ payload }) => {
+        const parsed = SyncthingEventSchema.safeParse(payload);
+        if (parsed.success) {
+          callback(parsed.data);
         }
-      };
-
-      poll();
+      });
 
       return () => {
-        running = false;
+        unlisten.then((fn) => fn());
       };
     },
 
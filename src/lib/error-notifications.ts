@@ -153,7 +153,7 @@ class ErrorNotificationService {
     // Check specific error codes
     switch (error.code) {
       case 'NETWORK_ERROR':
-      case 'SYNCTHING_CONNECTION_REFUSED':
+      case 'SYNCTHING_NOT_RUNNING':
         return 'warning';
       case 'VALIDATION_ERROR':
         return 'info';
@@ -270,16 +270,22 @@ class ErrorNotificationService {
     switch (error.code) {
       case 'NETWORK_ERROR':
         return 'Network Error';
-      case 'SYNCTHING_CONNECTION_REFUSED':
+      case 'SYNCTHING_NOT_RUNNING':
         return 'Syncthing Unavailable';
       case 'SYNCTHING_TIMEOUT':
         return 'Request Timeout';
       case 'VALIDATION_ERROR':
         return 'Invalid Input';
+      case 'CONFLICT':
+        return 'Conflicting Change';
       case 'CONFIG_ERROR':
         return 'Configuration Error';
       case 'FILESYSTEM_ERROR':
         return 'File System Error';
+      case 'PARSE_ERROR':
+        return 'Unexpected Response';
+      case 'PROCESS_ERROR':
+        return 'Syncthing Process Error';
       case 'DATABASE_ERROR':
         return 'Database Error';
       default:
@@ -139,9 +139,8 @@ export class SyncthingError extends AppError {
 
   static getDefaultMessage(code: string): string {
     const messages: Record<string, string> = {
-      CONNECTION_REFUSED: 'Cannot connect to Syncthing. Is it running?',
       TIMEOUT: 'Syncthing is taking too long to respond',
-      NOT_RUNNING: 'Syncthing is not running',
+      NOT_RUNNING: 'Cannot connect to Syncthing. Is it running?',
       CONFIG_ERROR: 'Syncthing configuration error',
       FOLDER_NOT_FOUND: 'Folder not found in Syncthing',
       DEVICE_NOT_FOUND: 'Device not found in Syncthing',
@@ -212,6 +211,67 @@ export class FileSystemError extends AppError {
   }
 }
 
+/**
+ * Unexpected responses or data that could not be read
+ */
+export class ParseError extends AppError {
+  constructor(
+    message: string,
+    options?: {
+      userMessage?: string;
+      context?: Record<string, unknown>;
+      cause?: Error;
+    }
+  ) {
+    super(message, 'PARSE_ERROR', {
+      ...options,
+      userMessage: options?.userMessage || 'Received an unexpected response from Syncthing',
+      recoverable: false,
+    });
+  }
+}
+
+/**
+ * Errors starting, stopping or talking to the Syncthing process
+ */
+export class ProcessError extends AppError {
+  constructor(
+    message: string,
+    options?: {
+      userMessage?: string;
+      context?: Record<string, unknown>;
+      cause?: Error;
+    }
+  ) {
+    super(message, 'PROCESS_ERROR', {
+      ...options,
+      userMessage: options?.userMessage || 'Could not manage the Syncthing process',
+    });
+  }
+}
+
+/**
+ * A change that clashes with existing state, e.g. a folder ID already in use
+ * or a config that kept changing while it was being updated
+ */
+export class ConflictError extends AppError {
+  constructor(
+    message: string,
+    options?: {
+      userMessage?: string;
+      context?: Record<string, unknown>;
+      cause?: Error;
+    }
+  ) {
+    super(message, 'CONFLICT', {
+      ...options,
+      userMessage: options?.userMessage || 'The change conflicts with the current configuration',
+      statusCode: 409,
+      recoverable: true,
+    });
+  }
+}
+
 /**
  * Database errors
  */
@@ -252,6 +312,91 @@ export class InvokeError extends AppError {
   }
 }
 
+/**
+ * Codes `SyncthingError::code()` produces (see `src-tauri/src/error.rs`);
+ * `API_ERROR_<status>` covers every other HTTP status Syncthing answers with
+ */
+export type BackendErrorCode =
+  | 'SYNCTHING_NOT_RUNNING'
+  | 'SYNCTHING_API_KEY_INVALID'
+  | 'SYNCTHING_FOLDER_NOT_FOUND'
+  | 'SYNCTHING_DEVICE_NOT_FOUND'
+  | 'SYNCTHING_TIMEOUT'
+  | 'CONFLICT'
+  | `API_ERROR_${number}`
+  | 'VALIDATION_ERROR'
+  | 'FILESYSTEM_ERROR'
+  | 'NETWORK_ERROR'
+  | 'PARSE_ERROR'
+  | 'PROCESS_ERROR';
+
+/**
+ * Error payload returned by failed Tauri commands (see `src-tauri/src/error.rs`)
+ */
+export interface BackendError {
+  code: BackendErrorCode | (string & {});
+  message: string;
+  status: number | null;
+  endpoint: string | null;
+  field: string | null;
+  path: string | null;
+}
+
+/**
+ * Check whether a rejected `invoke()` value is a structured backend error
+ */
+export function isBackendError(error: unknown): error is BackendError {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as BackendError).code === 'string' &&
+    typeof (error as BackendError).message === 'string'
+  );
+}
+
+/**
+ * Convert a structured backend error into the matching AppError
+ */
+export function fromBackendError(error: BackendError): AppError {
+  const context = {
+    ...(error.endpoint && { endpoint: error.endpoint }),
+    ...(error.path && { path: error.path }),
+  };
+
+  switch (error.code) {
+    case 'SYNCTHING_NOT_RUNNING':
+    case 'SYNCTHING_API_KEY_INVALID':
+    case 'SYNCTHING_FOLDER_NOT_FOUND':
+    case 'SYNCTHING_DEVICE_NOT_FOUND':
+    case 'SYNCTHING_TIMEOUT':
+      return new SyncthingError(error.message, error.code.slice('SYNCTHING_'.length), { context });
+    case 'CONFLICT':
+      return new ConflictError(error.message, { userMessage: error.message, context });
+    case 'NETWORK_ERROR':
+      return new NetworkError(error.message, { context });
+    case 'VALIDATION_ERROR':
+      return new ValidationError(
+        error.message,
+        error.field ? { [error.field]: [error.message] } : undefined,
+        { userMessage: error.message, context }
+      );
+    case 'FILESYSTEM_ERROR':
+      return new FileSystemError(error.message, { context });
+    case 'PARSE_ERROR':
+      return new ParseError(error.message, { context });
+    case 'PROCESS_ERROR':
+      return new ProcessError(error.message, { userMessage: error.message, context });
+  }
+
+  if (error.code.startsWith('API_ERROR_')) {
+    const status = error.status ?? Number(error.code.slice('API_ERROR_'.length));
+    if (Number.isInteger(status)) {
+      return new APIError(error.message, status, { context });
+    }
+  }
+  return new UnknownError(error.message, { userMessage: error.message, context });
+}
+
 /**
  * Error handler utilities
  */
@@ -329,13 +474,17 @@ export class ErrorHandler {
       return error;
     }
 
+    if (isBackendError(error)) {
+      return fromBackendError(error);
+    }
+
     if (error instanceof Error) {
       // Try to infer error type
       if (error.message.includes('network') || error.message.includes('fetch')) {
         return new NetworkError(error.message, { cause: error });
       }
       if (error.message.includes('ECONNREFUSED')) {
-        return new SyncthingError(error.message, 'CONNECTION_REFUSED', { cause: error });
+        return new SyncthingError(error.message, 'NOT_RUNNING', { cause: error });
       }
 
       // Generic unknown error - create a concrete implementation
@@ -428,7 +577,7 @@ ErrorRecovery.register('NETWORK_ERROR', async () => {
   await new Promise((resolve) => setTimeout(resolve, 2000));
 });
 
-ErrorRecovery.register('SYNCTHING_CONNECTION_REFUSED', async () => {
+ErrorRecovery.register('SYNCTHING_NOT_RUNNING', async () => {
   // Could try to start Syncthing via Tauri
   // await invoke('start_syncthing_sidecar');
   await new Promise((resolve) => setTimeout(resolve, 3000));
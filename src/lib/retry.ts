@@ -1,5 +1,6 @@
 // src/lib/retry.ts
 import { logger } from './logger';
+import { AppError } from './errors';
 
 export interface RetryOptions {
   maxAttempts?: number;
@@ -42,6 +43,16 @@ function calculateDelay(
  * Handles both Linux and Windows error patterns
  */
 function defaultShouldRetry(error: unknown): boolean {
+  // Structured backend errors say what happened; only transient failures are retried
+  if (error instanceof AppError) {
+    return (
+      error.code === 'NETWORK_ERROR' ||
+      error.code === 'SYNCTHING_NOT_RUNNING' ||
+      error.code === 'SYNCTHING_TIMEOUT' ||
+      (error.statusCode !== undefined && error.statusCode >= 500)
+    );
+  }
+
   if (error instanceof Error) {
     const message = error.message.toLowerCase();
 
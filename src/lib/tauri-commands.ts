@@ -60,6 +60,33 @@ export async function stopSyncthingSidecar(): Promise<string> {
   return invoke<string>('stop_syncthing_sidecar');
 }
 
+export interface SidecarOutputLine {
+  seq: number;
+  /** Milliseconds since the Unix epoch */
+  timestamp: number;
+  stream: 'stdout' | 'stderr' | 'supervisor';
+  line: string;
+}
+
+/**
+ * Payload of the `sidecar-status` event
+ */
+export interface SidecarStatus {
+  state: 'starting' | 'ready' | 'crashed' | 'stopped';
+  restarts: number;
+  exitCode: number | null;
+  signal: number | null;
+  restartInMs: number | null;
+  message: string | null;
+}
+
+/**
+ * Get buffered sidecar output, optionally only lines after sequence number `since`
+ */
+export async function getSidecarOutput(since?: number): Promise<SidecarOutputLine[]> {
+  return invoke<SidecarOutputLine[]>('get_sidecar_output', { since });
+}
+
 /**
  * Ping Syncthing to check if it's responding
  * @returns Ping response with { ping: 'pong' } if successful
@@ -107,6 +134,125 @@ export async function updateOptions(options: Record<string, unknown>): Promise<v
   return invoke('update_options', { options });
 }
 
+/**
+ * Folder types Syncthing accepts
+ */
+export type FolderType = 'sendreceive' | 'sendonly' | 'receiveonly' | 'receiveencrypted';
+
+/**
+ * One change within a configuration transaction
+ */
+export type ConfigOperation =
+  | {
+      op: 'addFolder';
+      folderId: string;
+      label: string;
+      path: string;
+      settings?: Record<string, unknown>;
+    }
+  | { op: 'removeFolder'; folderId: string }
+  | { op: 'updateFolder'; folderId: string; updates: Record<string, unknown> }
+  | { op: 'addDevice'; deviceId: string; name: string; settings?: Record<string, unknown> }
+  | { op: 'removeDevice'; deviceId: string }
+  | { op: 'updateDevice'; deviceId: string; updates: Record<string, unknown> }
+  | { op: 'shareFolder'; folderId: string; deviceId: string; encryptionPassword?: string }
+  | { op: 'unshareFolder'; folderId: string; deviceId: string }
+  | { op: 'updateOptions'; updates: Record<string, unknown> };
+
+export interface FieldChange {
+  field: string;
+  before: unknown;
+  after: unknown;
+}
+
+export interface ObjectChange {
+  id: string;
+  kind: 'added' | 'removed' | 'modified';
+  changes: FieldChange[];
+}
+
+export interface ConfigDiff {
+  folders: ObjectChange[];
+  devices: ObjectChange[];
+  options: FieldChange[];
+}
+
+/**
+ * Preview what a batch of config operations would change, without applying it
+ */
+export async function previewConfigTransaction(operations: ConfigOperation[]): Promise<ConfigDiff> {
+  return invoke<ConfigDiff>('preview_config_transaction', { operations });
+}
+
+/**
+ * Apply a batch of config operations in a single write (all or nothing)
+ */
+export async function applyConfigTransaction(operations: ConfigOperation[]): Promise<ConfigDiff> {
+  return invoke<ConfigDiff>('apply_config_transaction', { operations });
+}
+
+export type ManifestFormat = 'toml' | 'yaml';
+
+/**
+ * Export the folders, devices and sharing of the live config as a TOML or YAML manifest
+ */
+export async function exportConfigManifest(format: ManifestFormat): Promise<string> {
+  return invoke<string>('export_config_manifest', { format });
+}
+
+/**
+ * Compute what applying a manifest would change, without applying it
+ */
+export async function previewConfigManifest(
+  document: string,
+  format: ManifestFormat,
+  prune = false
+): Promise<ConfigDiff> {
+  return invoke<ConfigDiff>('preview_config_manifest', { document, format, prune });
+}
+
+/**
+ * Converge the live config on a manifest; with `prune`, unlisted folders and devices are removed
+ */
+export async function applyConfigManifest(
+  document: string,
+  format: ManifestFormat,
+  prune = false
+): Promise<ConfigDiff> {
+  return invoke<ConfigDiff>('apply_config_manifest', { document, format, prune });
+}
+
+export interface SnapshotInfo {
+  id: string;
+  /** Milliseconds since the Unix epoch */
+  createdAt: number;
+  /** The command that was about to change the configuration */
+  reason: string;
+  profile: string;
+}
+
+/**
+ * List automatic config snapshots of the active profile, newest first
+ */
+export async function listConfigSnapshots(): Promise<SnapshotInfo[]> {
+  return invoke<SnapshotInfo[]>('list_config_snapshots');
+}
+
+/**
+ * What changed in the live config since a snapshot was taken
+ */
+export async function diffConfigSnapshot(id: string): Promise<ConfigDiff> {
+  return invoke<ConfigDiff>('diff_config_snapshot', { id });
+}
+
+/**
+ * Restore the folders, devices and options of a config snapshot (the current
+ * config is snapshotted first)
+ */
+export async function restoreConfigSnapshot(id: string): Promise<ConfigDiff> {
+  return invoke<ConfigDiff>('restore_config_snapshot', { id });
+}
+
 /**
  * Get the current API configuration (for debugging)
  */
@@ -114,6 +260,35 @@ export async function getApiConfig(): Promise<[string, number]> {
   return invoke('get_api_config');
 }
 
+export interface GuiSettings {
+  enabled: boolean;
+  address: string;
+  host: string;
+  port: number;
+  tls: boolean;
+  unixSocket: string | null;
+  hasApiKey: boolean;
+  hasUser: boolean;
+  hasPassword: boolean;
+}
+
+export interface SyncthingDiscovery {
+  /** Home directory set with `--home` or `STHOMEDIR`, also passed to the sidecar */
+  home: string | null;
+  configPath: string | null;
+  certFingerprint: string | null;
+  searched: string[];
+  gui: GuiSettings | null;
+  warnings: string[];
+}
+
+/**
+ * Get which Syncthing config.xml was used for the local instance and what it contained
+ */
+export async function getSyncthingDiscovery(): Promise<SyncthingDiscovery> {
+  return invoke('get_syncthing_discovery');
+}
+
 // =============================================================================
 // Device Commands
 // =============================================================================
@@ -125,6 +300,72 @@ export async function getDeviceId(): Promise<string> {
   return invoke('get_device_id');
 }
 
+/**
+ * Normalize a device ID typed or pasted by the user. Accepts lowercase,
+ * missing dashes and the old form without check characters; rejects typos
+ * with a validation error.
+ */
+export async function validateDeviceId(deviceId: string): Promise<string> {
+  return invoke('validate_device_id', { deviceId });
+}
+
+export interface FolderOffer {
+  folderId: string;
+  label: string;
+}
+
+/** A device ID plus suggested name and folders, as carried by a QR code */
+export interface Invite {
+  deviceId: string;
+  name: string | null;
+  folders: FolderOffer[];
+  /** Suggest adding the device as an introducer */
+  introducer: boolean;
+  /** Milliseconds since the Unix epoch after which the invite is refused */
+  expiresAt: number | null;
+}
+
+export interface DeviceInvite extends Invite {
+  /** The `eigen://invite?...` URI encoded in the QR code */
+  payload: string;
+}
+
+export interface InviteOptions {
+  introducer?: boolean;
+  /** Hours until the invite expires; never if unset */
+  expiresInHours?: number;
+}
+
+/**
+ * Invitation to this device, optionally suggesting folders to share
+ */
+export async function getDeviceInvite(
+  folderIds?: string[],
+  options?: InviteOptions
+): Promise<DeviceInvite> {
+  return invoke<DeviceInvite>('get_device_invite', { folderIds, options });
+}
+
+/**
+ * The invitation from `getDeviceInvite` as a QR code PNG, ready for an
+ * `<img>` via `URL.createObjectURL`
+ */
+export async function renderDeviceInviteQr(
+  folderIds?: string[],
+  options?: InviteOptions
+): Promise<Blob> {
+  const png = await invoke<ArrayBuffer>('render_device_invite_qr', { folderIds, options });
+  return new Blob([png], { type: 'image/png' });
+}
+
+/**
+ * Decode a scanned or pasted invitation (or a bare device ID) into a
+ * validated device ID and folder offers; expired invites are rejected
+ */
+export async function decodeDeviceInvite(payload: string): Promise<Invite> {
+  return invoke<Invite>('decode_device_invite', { payload });
+}
+
 /**
  * Add a new device to Syncthing
  */
@@ -195,6 +436,169 @@ export async function getFolderStatus(folderId: string): Promise<unknown> {
   return invoke('get_folder_status', { folderId });
 }
 
+/**
+ * Completion of one folder on one device (`/rest/db/completion`)
+ */
+export interface Completion {
+  /** Percentage of the global state the device has, 0–100 */
+  completion: number;
+  globalBytes: number;
+  globalItems: number;
+  needBytes: number;
+  needItems: number;
+  needDeletes: number;
+  remoteState: 'valid' | 'paused' | 'notSharing' | 'unknown';
+  sequence: number;
+}
+
+/**
+ * Completion of every folder on every remote device it is shared with
+ */
+export interface CompletionMatrix {
+  devices: { deviceId: string; name: string }[];
+  folders: {
+    folderId: string;
+    label: string;
+    /** Paused folders report no completion */
+    paused: boolean;
+    /** Only the devices the folder is shared with */
+    devices: (Completion & { deviceId: string })[];
+  }[];
+}
+
+/**
+ * How much of a folder a device still needs
+ */
+export async function getFolderCompletion(folderId: string, deviceId: string): Promise<Completion> {
+  return invoke<Completion>('get_folder_completion', { folderId, deviceId });
+}
+
+/**
+ * Completion of every folder on every remote device, kept fresh from
+ * FolderCompletion events
+ */
+export async function getCompletionMatrix(): Promise<CompletionMatrix> {
+  return invoke<CompletionMatrix>('get_completion_matrix');
+}
+
+/**
+ * A file as recorded in Syncthing's database
+ */
+export interface FileEntry {
+  name: string;
+  type: 'file' | 'directory' | 'symlink';
+  size: number;
+  /** RFC 3339 modification time */
+  modified: string;
+  /** Short ID of the device that made the last change */
+  modifiedBy: string;
+  deleted: boolean;
+  invalid: boolean;
+  ignored: boolean;
+  noPermissions: boolean;
+  sequence: number;
+  /** Version vector as `SHORTID:counter` entries */
+  version: string[];
+  /** Devices that have this version; only set for needed items when asked for */
+  availability?: string[];
+  /** Why the availability lookup for this item failed */
+  availabilityError?: string;
+}
+
+/**
+ * One page of what the local device still needs
+ */
+export interface NeedPage {
+  /** Being downloaded right now */
+  progress: FileEntry[];
+  /** Next in line */
+  queued: FileEntry[];
+  /** Everything else */
+  rest: FileEntry[];
+  page: number;
+  perPage: number;
+}
+
+/**
+ * One page of file entries
+ */
+export interface FilePage {
+  files: FileEntry[];
+  page: number;
+  perPage: number;
+}
+
+/**
+ * Files the local device still needs in a folder
+ * @param page - 1-based page number (default 1)
+ * @param perPage - Items per page (default 100, at most 1000)
+ * @param availability - Also look up the devices that have each file (one request per file)
+ */
+export async function getFolderNeed(
+  folderId: string,
+  page?: number,
+  perPage?: number,
+  availability?: boolean
+): Promise<NeedPage> {
+  return invoke<NeedPage>('get_folder_need', { folderId, page, perPage, availability });
+}
+
+/**
+ * Files a remote device still needs from us in a folder
+ */
+export async function getRemoteNeed(
+  folderId: string,
+  deviceId: string,
+  page?: number,
+  perPage?: number
+): Promise<FilePage> {
+  return invoke<FilePage>('get_remote_need', { folderId, deviceId, page, perPage });
+}
+
+/**
+ * Files changed locally in a receive-only folder (what a revert would undo)
+ */
+export async function getLocalChanged(
+  folderId: string,
+  page?: number,
+  perPage?: number
+): Promise<FilePage> {
+  return invoke<FilePage>('get_local_changed', { folderId, page, perPage });
+}
+
+/**
+ * A device holding the global version of a file
+ */
+export interface FileAvailability {
+  deviceId: string;
+  /** Configured name, if the device is known */
+  name: string | null;
+  connected: boolean;
+  /** The device only has a temporary, partly downloaded copy */
+  fromTemporary: boolean;
+}
+
+/**
+ * Everything Syncthing knows about one file
+ */
+export interface FileDetails {
+  /** The local copy; null if this device does not have the file */
+  local: FileEntry | null;
+  /** The newest version in the cluster */
+  global: FileEntry;
+  /** Whether the local copy is the global version */
+  inSync: boolean;
+  availability: FileAvailability[];
+}
+
+/**
+ * Local and global versions of a file, and which devices have it
+ * @param file - Path of the file relative to the folder root
+ */
+export async function getFileInfo(folderId: string, file: string): Promise<FileDetails> {
+  return invoke<FileDetails>('get_file_info', { folderId, file });
+}
+
 /**
  * Pause a folder
  */
@@ -216,31 +620,73 @@ export async function rescanFolder(folderId: string): Promise<void> {
   return invoke('rescan_folder', { folderId });
 }
 
+/**
+ * Something about a folder path worth telling the user, but not fatal
+ */
+export interface PathWarning {
+  kind: 'networkFilesystem' | 'caseInsensitive' | 'notChecked';
+  message: string;
+}
+
+/**
+ * Outcome of the pre-flight check run on a new folder's path
+ */
+export interface PathCheck {
+  /** The path as checked, with `~` expanded */
+  path: string;
+  /** Whether the directory had to be created */
+  created: boolean;
+  warnings: PathWarning[];
+}
+
+/**
+ * A folder that was just added, with the pre-flight check of its path
+ */
+export interface AddedFolder extends PathCheck {
+  /** The folder's ID, which may have been generated */
+  folderId: string;
+}
+
+/**
+ * Generate a random, unused Syncthing-style folder ID (`abcde-12345`)
+ */
+export async function generateFolderId(): Promise<string> {
+  return invoke<string>('generate_folder_id');
+}
+
 /**
  * Add a new folder to Syncthing
+ *
+ * The path is checked first (absolute, writable, not nested in another
+ * folder) and the `.stfolder` marker is created. Pass a null `folderId` to
+ * have one generated.
  */
 export async function addFolder(
-  folderId: string,
+  folderId: string | null,
   folderLabel: string,
   folderPath: string
-): Promise<void> {
-  return invoke('add_folder', { folderId, folderLabel, folderPath });
+): Promise<AddedFolder> {
+  return invoke<AddedFolder>('add_folder', { folderId, folderLabel, folderPath });
 }
 
 /**
  * Add a folder with advanced configuration options
  */
 export async function addFolderAdvanced(params: {
-  folderId: string;
-  label: string;
-  path: string;
-  folderType?: string;
-  rescanInterval?: number;
+  folderId?: string;
+  folderLabel: string;
+  folderPath: string;
+  folderType?: FolderType;
+  versioningType?: string;
+  versioningParams?: Record<string, string>;
+  rescanIntervalS?: number;
   fsWatcherEnabled?: boolean;
+  fsWatcherDelayS?: number;
   ignorePerms?: boolean;
-  autoNormalize?: boolean;
-}): Promise<void> {
-  return invoke('add_folder_advanced', params);
+  /** Devices to share the folder with; a password encrypts what a device receives */
+  devices?: { deviceId: string; encryptionPassword?: string }[];
+}): Promise<AddedFolder> {
+  return invoke<AddedFolder>('add_folder_advanced', params);
 }
 
 /**
@@ -269,9 +715,14 @@ export async function updateFolderConfig(
 
 /**
  * Share a folder with a specific device
+ * @param encryptionPassword - Share encrypted, treating the device as untrusted
  */
-export async function shareFolder(folderId: string, deviceId: string): Promise<void> {
-  return invoke('share_folder', { folderId, deviceId });
+export async function shareFolder(
+  folderId: string,
+  deviceId: string,
+  encryptionPassword?: string
+): Promise<void> {
+  return invoke('share_folder', { folderId, deviceId, encryptionPassword });
 }
 
 /**
@@ -281,6 +732,20 @@ export async function unshareFolder(folderId: string, deviceId: string): Promise
   return invoke('unshare_folder', { folderId, deviceId });
 }
 
+/**
+ * Override Changes: push a send-only folder's state to all other devices
+ */
+export async function overrideFolderChanges(folderId: string): Promise<void> {
+  return invoke('override_folder_changes', { folderId });
+}
+
+/**
+ * Revert Local Changes: discard local changes in a receive-only folder
+ */
+export async function revertFolderChanges(folderId: string): Promise<void> {
+  return invoke('revert_folder_changes', { folderId });
+}
+
 /**
  * Get ignore patterns for a folder
  */
@@ -295,6 +760,225 @@ export async function setFolderIgnores(folderId: string, ignorePatterns: string[
   return invoke('set_folder_ignores', { folderId, ignorePatterns });
 }
 
+// =============================================================================
+// Statistics Commands
+// =============================================================================
+
+/**
+ * When a device was last connected
+ */
+export interface DeviceStats {
+  deviceId: string;
+  name: string;
+  /** RFC 3339; null if the device has never connected */
+  lastSeen: string | null;
+  /** How long the last connection lasted, in seconds */
+  lastConnectionDurationS: number;
+}
+
+/**
+ * When a folder was last scanned and what it last synced
+ */
+export interface FolderStats {
+  folderId: string;
+  label: string;
+  /** RFC 3339; null if the folder was never scanned */
+  lastScan: string | null;
+  lastFile: { at: string; filename: string; deleted: boolean } | null;
+}
+
+export type ConnectionType = 'tcp' | 'quic' | 'relay' | 'other';
+
+/**
+ * Everything about a remote device at a glance
+ */
+export interface DeviceOverview {
+  deviceId: string;
+  name: string;
+  paused: boolean;
+  connected: boolean;
+  /** Remote address of the current connection */
+  address: string | null;
+  /** null while disconnected */
+  connectionType: ConnectionType | null;
+  /** e.g. `v1.27.0`; null while disconnected */
+  clientVersion: string | null;
+  /** RFC 3339; null if the device has never connected */
+  lastSeen: string | null;
+  lastConnectionDurationS: number;
+  /** Folders shared with the device */
+  folders: { folderId: string; label: string }[];
+}
+
+/**
+ * When each device was last seen and how long it stayed connected
+ */
+export async function getDeviceStats(): Promise<DeviceStats[]> {
+  return invoke<DeviceStats[]>('get_device_stats');
+}
+
+/**
+ * When each folder was last scanned and which file it last synced
+ */
+export async function getFolderStats(): Promise<FolderStats[]> {
+  return invoke<FolderStats[]>('get_folder_stats');
+}
+
+/**
+ * Connection state, statistics and shared folders of every remote device
+ */
+export async function getDeviceOverview(): Promise<DeviceOverview[]> {
+  return invoke<DeviceOverview[]>('get_device_overview');
+}
+
+// =============================================================================
+// Bandwidth Schedule Commands
+// =============================================================================
+
+export type Weekday = 'mon' | 'tue' | 'wed' | 'thu' | 'fri' | 'sat' | 'sun';
+
+/**
+ * Upload and download limits in KiB/s; 0 means unlimited
+ */
+export interface Limits {
+  maxSendKbps: number;
+  maxRecvKbps: number;
+}
+
+/**
+ * Global limits plus limits for individual devices, keyed by device ID
+ */
+export interface BandwidthLimits extends Limits {
+  devices: Record<string, Limits>;
+}
+
+/**
+ * A time range repeating on some weekdays
+ */
+export interface WeeklyRange {
+  /** Days the range starts on; empty means every day */
+  days: Weekday[];
+  /** `HH:MM` */
+  start: string;
+  /** `HH:MM`, exclusive; an end at or before `start` runs past midnight */
+  end: string;
+}
+
+/**
+ * Limits that apply during a weekly time range
+ */
+export interface BandwidthRule extends BandwidthLimits, WeeklyRange {
+  /** Shown in the tray, e.g. `Office hours` */
+  name: string;
+}
+
+/**
+ * The persisted schedule; the first rule covering a minute wins
+ */
+export interface BandwidthSchedule {
+  enabled: boolean;
+  /** Limits outside every rule */
+  default: BandwidthLimits;
+  rules: BandwidthRule[];
+}
+
+/**
+ * What the schedule wants right now
+ */
+export interface BandwidthStatus {
+  enabled: boolean;
+  /** Name of the rule in effect; null while the default limits apply */
+  rule: string | null;
+  /** Global limits and those of every device the schedule manages */
+  limits: BandwidthLimits;
+  /** When a different rule takes over, in milliseconds since the Unix epoch */
+  nextChange: number | null;
+}
+
+/**
+ * The persisted bandwidth schedule
+ */
+export async function getBandwidthSchedule(): Promise<BandwidthSchedule> {
+  return invoke<BandwidthSchedule>('get_bandwidth_schedule');
+}
+
+/**
+ * Save the schedule; the background scheduler applies it straight away.
+ * Turning it off puts the default limits back in place.
+ */
+export async function setBandwidthSchedule(
+  schedule: BandwidthSchedule
+): Promise<BandwidthStatus> {
+  return invoke<BandwidthStatus>('set_bandwidth_schedule', { schedule });
+}
+
+/**
+ * The rule in effect right now and the limits it asks for
+ */
+export async function getBandwidthStatus(): Promise<BandwidthStatus> {
+  return invoke<BandwidthStatus>('get_bandwidth_status');
+}
+
+// =============================================================================
+// Sync Window Commands
+// =============================================================================
+
+/**
+ * Windows folders and devices may sync in; outside them they are paused
+ */
+export interface SyncWindows {
+  enabled: boolean;
+  /** Keyed by folder ID */
+  folders: Record<string, WeeklyRange[]>;
+  /** Keyed by device ID */
+  devices: Record<string, WeeklyRange[]>;
+}
+
+/**
+ * Why a folder or device is paused; scheduled pauses are lifted when a
+ * window opens, manual ones never are
+ */
+export type PauseReason = 'manual' | 'scheduled';
+
+/**
+ * A folder or device under sync windows, right now
+ */
+export interface SyncWindowState {
+  kind: 'folder' | 'device';
+  id: string;
+  /** Folder label or device name; null if it is not configured */
+  name: string | null;
+  /** Whether one of its windows is open */
+  open: boolean;
+  paused: boolean;
+  /** null while not paused */
+  pauseReason: PauseReason | null;
+  /** When a window next opens or closes, in milliseconds since the Unix epoch */
+  nextChange: number | null;
+}
+
+/**
+ * The persisted sync windows
+ */
+export async function getSyncWindows(): Promise<SyncWindows> {
+  return invoke<SyncWindows>('get_sync_windows');
+}
+
+/**
+ * Save the sync windows; the background scheduler applies them straight away
+ */
+export async function setSyncWindows(windows: SyncWindows): Promise<void> {
+  return invoke('set_sync_windows', { windows });
+}
+
+/**
+ * Whether each folder and device under sync windows is inside one, whether it
+ * is paused and whether that pause was manual or scheduled
+ */
+export async function getSyncWindowState(): Promise<SyncWindowState[]> {
+  return invoke<SyncWindowState[]>('get_sync_window_state');
+}
+
 // =============================================================================
 // File Browser Commands
 // =============================================================================
@@ -382,7 +1066,11 @@ export async function getSystemLogs(since?: string): Promise<unknown> {
 }
 
 /**
- * Get events from Syncthing (for real-time updates)
+ * Get events from Syncthing once
+ *
+ * The backend event pump already streams every event to all windows; listen
+ * for `SYNCTHING_EVENTS` with `listen()` from `@tauri-apps/api/event` instead
+ * of polling this.
  */
 export async function getEvents(params?: {
   since?: number;
@@ -392,6 +1080,105 @@ export async function getEvents(params?: {
   return invoke('get_events', params ?? {});
 }
 
+// =============================================================================
+// Event Stream (emitted by the backend event pump)
+// =============================================================================
+
+/**
+ * Names of the Tauri events the event pump emits
+ */
+export const SYNCTHING_EVENTS = {
+  /** Every Syncthing event, raw (`SyncthingEventPayload`) */
+  raw: 'syncthing-event',
+  folderStateChanged: 'folder-state-changed',
+  deviceConnected: 'device-connected',
+  deviceDisconnected: 'device-disconnected',
+  itemStarted: 'item-started',
+  itemFinished: 'item-finished',
+  folderCompletion: 'folder-completion',
+  /** The following carry the Syncthing event's `data` as-is */
+  folderSummary: 'folder-summary',
+  folderErrors: 'folder-errors',
+  folderPausedChanged: 'folder-paused-changed',
+  devicePausedChanged: 'device-paused-changed',
+  configSaved: 'config-saved',
+  pendingDevicesChanged: 'pending-devices-changed',
+  pendingFoldersChanged: 'pending-folders-changed',
+  localChangeDetected: 'local-change-detected',
+  remoteChangeDetected: 'remote-change-detected',
+  /** `EventStreamStatus` */
+  streamStatus: 'event-stream-status',
+} as const;
+
+/**
+ * Payload of `syncthing-event`: an event as returned by `/rest/events`
+ */
+export interface SyncthingEventPayload {
+  id: number;
+  globalID: number;
+  type: string;
+  time: string;
+  data: unknown;
+}
+
+/**
+ * Payload of `folder-state-changed`
+ */
+export interface FolderStateChangedPayload {
+  folder: string;
+  from: string;
+  to: string;
+  error: string | null;
+}
+
+/**
+ * Payload of `device-connected`
+ */
+export interface DeviceConnectedPayload {
+  id: string;
+  deviceName: string;
+  clientName: string;
+  clientVersion: string;
+  addr: string;
+  type: string;
+}
+
+/**
+ * Payload of `device-disconnected`
+ */
+export interface DeviceDisconnectedPayload {
+  id: string;
+  error: string | null;
+}
+
+/**
+ * Payload of `item-started` and `item-finished`
+ */
+export interface ItemEventPayload {
+  folder: string;
+  item: string;
+  type: string;
+  action: string;
+  error: string | null;
+}
+
+/**
+ * Payload of `folder-completion`
+ */
+export interface FolderCompletionPayload extends Completion {
+  folder: string;
+  device: string;
+}
+
+/**
+ * Payload of `event-stream-status`
+ */
+export interface EventStreamStatus {
+  stream: 'events' | 'disk';
+  connected: boolean;
+  error: string | null;
+}
+
 // =============================================================================
 // Tray Commands
 // =============================================================================
@@ -482,14 +1269,26 @@ export async function dismissPendingDevice(deviceId: string): Promise<void> {
  * @param deviceId - The device ID that shared the folder
  * @param folderPath - Local path where the folder will be synced
  * @param folderLabel - Optional label for the folder
+ * @param folderType - Type of the new folder (defaults to Syncthing's folder defaults)
+ * @param encryptionPassword - Share encrypted, treating the device as untrusted
+ * @returns The path check, or null if the folder already existed and was only shared
  */
 export async function acceptPendingFolder(
   folderId: string,
   deviceId: string,
   folderPath: string,
-  folderLabel?: string
-): Promise<void> {
-  return invoke('accept_pending_folder', { folderId, deviceId, folderPath, folderLabel });
+  folderLabel?: string,
+  folderType?: FolderType,
+  encryptionPassword?: string
+): Promise<PathCheck | null> {
+  return invoke<PathCheck | null>('accept_pending_folder', {
+    folderId,
+    deviceId,
+    folderPath,
+    folderLabel,
+    folderType,
+    encryptionPassword,
+  });
 }
 
 /**
@@ -500,3 +1299,71 @@ export async function acceptPendingFolder(
 export async function dismissPendingFolder(folderId: string, deviceId: string): Promise<void> {
   return invoke('dismiss_pending_folder', { folderId, deviceId });
 }
+
+// =============================================================================
+// Connection Profile Commands
+// =============================================================================
+
+/**
+ * A named Syncthing connection profile as returned by the backend
+ * (the API key is never sent to the frontend)
+ */
+export interface ProfileInfo {
+  name: string;
+  host: string;
+  port: number;
+  useTls: boolean;
+  certFingerprint: string | null;
+  hasApiKey: boolean;
+  active: boolean;
+  builtin: boolean;
+}
+
+/**
+ * Connection settings for a new or updated profile
+ */
+export interface ConnectionProfile {
+  name: string;
+  host: string;
+  port: number;
+  /** Leave empty when editing to keep the stored key */
+  apiKey?: string;
+  useTls?: boolean;
+  /** SHA-256 fingerprint of the instance's GUI certificate to pin */
+  certFingerprint?: string | null;
+}
+
+/**
+ * List all connection profiles
+ */
+export async function listProfiles(): Promise<ProfileInfo[]> {
+  return invoke<ProfileInfo[]>('list_profiles');
+}
+
+/**
+ * Add a profile or update an existing one with the same name
+ */
+export async function saveProfile(profile: ConnectionProfile): Promise<void> {
+  return invoke('save_profile', { profile });
+}
+
+/**
+ * Remove a profile (removing the active one switches back to `local`)
+ */
+export async function removeProfile(name: string): Promise<void> {
+  return invoke('remove_profile', { name });
+}
+
+/**
+ * Fetch the certificate fingerprint a remote instance presents, for the user to confirm
+ */
+export async function fetchCertificateFingerprint(host: string, port: number): Promise<string> {
+  return invoke<string>('fetch_certificate_fingerprint', { host, port });
+}
+
+/**
+ * Route all further commands through the named profile
+ */
+export async function switchProfile(name: string): Promise<void> {
+  return invoke('switch_profile', { name });
+}
@@ -260,16 +260,22 @@ export type SyncthingEvent = z.infer<typeof SyncthingEventSchema>;
 
 // --- Advanced Options (non-schema interfaces) ---
 
+export type FolderType = 'sendreceive' | 'sendonly' | 'receiveonly' | 'receiveencrypted';
+
 export interface AdvancedFolderOptions {
-  folderId: string;
+  /** Generated when left out */
+  folderId?: string;
   folderLabel: string;
   folderPath: string;
+  folderType?: FolderType;
   versioningType?: 'simple' | 'staggered' | 'trashcan' | 'external' | '';
   versioningParams?: Record<string, string>;
   rescanIntervalS?: number;
   fsWatcherEnabled?: boolean;
   fsWatcherDelayS?: number;
   ignorePerms?: boolean;
+  /** Devices to share the folder with; a password encrypts what a device receives */
+  devices?: { deviceId: string; encryptionPassword?: string }[];
 }
 
 export interface AdvancedDeviceOptions {